@@ -2,6 +2,8 @@ about = About
 quit = Quit
 
 search-entry-placeholder = Enter a search term or question id
+site-picker-tooltip = Select a site to search in
+site-picker-filter-placeholder = Filter sites
 
 toggle-pin = Pin/Unpin
 close = Close
@@ -18,9 +20,6 @@ translator-credits = zefr0x https://github.com/zefr0x
 release-notes = Release Notes
 
 
-placeholder = Placeholder
-
-
 question-answered = Answered
 question-not-answered = Not Answered
 
@@ -28,6 +27,9 @@ question-view-count = <b>View Count:</b> { $count }
 creation-time = <b>Created:</b> { $time }
 last-active-time = <b>Last Active:</b> { $time }
 question-id = <b>Question ID:</b> { $id }
+question-loading-more = Loading more answers…
+question-loading-more-failed = Couldn't load all answers: { $error }
+question-cached-copy = You are offline, this is a cached copy from { $time }
 
 comments-count =
     { $count ->
@@ -46,6 +48,12 @@ answer-id = <b>Answer ID:</b> { $id }
 comment-tooltip = <b>Comment ID:</b> { $comment_id }
                   <b>Post ID:</b> { $post_id }
 comment-empty = No Content
+comments-show-more =
+    { $count ->
+        [one] Show One More Comment
+        *[other] Show { $count } More Comments
+    }
+comments-loading-failed = Couldn't load comments, click to retry: { $error }
 comment-time = <b> at</b> { $time }
 
 user-tooltip = <b>Name:</b> { $name }
@@ -53,3 +61,156 @@ user-tooltip = <b>Name:</b> { $name }
                <b>Reputation:</b> { $reputation }
 
 not-available = Not Available
+
+
+no-questions-found = No questions were found.
+retry = Retry
+
+api-error-transport = Couldn't reach StackExchange, check your network connection.
+api-error-http-status = StackExchange responded with HTTP status { $status }.
+api-error-throttle-violation = Too many requests, StackExchange asked to slow down: { $message }
+api-error-access-denied = Access denied: { $message }
+api-error-no-method = This kind of request is not supported by StackExchange.
+api-error-temporarily-unavailable = StackExchange is temporarily unavailable, try again later.
+api-error-other = StackExchange error { $id } ({ $name }): { $message }
+api-error-decode = Unexpected response from StackExchange.
+api-error-invalid-uri = Can't open { $uri }: { $reason }
+api-error-quota-exhausted = Daily requests quota is exhausted, it will be reset at midnight UTC.
+api-error-throttled = Too many requests, try again in { $seconds } seconds.
+api-error-dump = Data dump error: { $error }
+api-error-unsupported = This isn't supported by the selected backend.
+api-error-config = Network settings are invalid, fix them in the preferences: { $error }
+
+uri-error-malformed = it's not a valid URI.
+uri-error-unsupported-scheme = "{ $scheme }" links are not supported.
+uri-error-unknown-site = "{ $site }" is not a known StackExchange site.
+uri-error-unknown-route = nothing can be opened at "{ $path }".
+uri-error-invalid-id = "{ $id }" is not a valid ID.
+uri-error-missing-parameter = the "{ $parameter }" parameter is missing.
+
+quota-label = { $remaining }/{ $max }
+quota-tooltip = Remaining StackExchange API requests for today
+
+
+search-tab-title = Search: { $query }
+search-load-more = Load More
+search-no-results = No questions matched your search.
+search-failed = Search failed: { $error }
+search-result-tooltip = <b>Question ID:</b> { $id }
+                        <b>View Count:</b> { $views }
+                        <b>Owner:</b> { $owner }
+search-tag-questions =
+    { $count ->
+        [one] [{ $tag }] has one question.
+        *[other] [{ $tag }] has { $count } questions.
+    }
+
+
+user-tab-title = User { $id }
+user-questions = Questions
+user-no-questions = This user didn't ask any questions.
+user-not-found = User { $id } was not found.
+user-loading-failed = Couldn't load the user profile: { $error }
+user-reputation = <b>Reputation:</b> { $reputation }  ·  🥇 { $gold }  🥈 { $silver }  🥉 { $bronze }
+user-member-since = <b>Member Since:</b> { $time }
+
+
+bookmarks = Bookmarks
+bookmarks-empty = Bookmarked questions will be shown here.
+bookmarks-filter-placeholder = Filter by title, tag, or note
+bookmarks-folder-tooltip = Show bookmarks of a folder
+bookmarks-all-folders = All Folders
+bookmarks-sort-tooltip = Sort bookmarks
+bookmarks-sort-newest = Newest
+bookmarks-sort-title = Title
+bookmarks-sort-score = Score
+bookmarks-load-failed = Couldn't load bookmarks: { $error }
+bookmarks-save-failed = Couldn't save bookmarks: { $error }
+bookmark-add = Bookmark
+bookmark-remove = Remove bookmark
+bookmark-edit = Edit folder, tags, and note
+bookmark-folder-placeholder = Folder
+bookmark-tags-placeholder = Tags, separated by spaces
+bookmark-note-tooltip = Note, written in markdown
+bookmark-save = Save
+bookmark-tooltip = <b>Bookmarked:</b> { $time }
+
+
+history = History
+history-empty = Questions you open will be shown here.
+history-filter-placeholder = Search history
+history-clear = Clear history
+history-clear-last-hour = Last Hour
+history-clear-today = Today
+history-clear-last-week = Last 7 Days
+history-clear-everything = Everything
+history-clear-day = Clear this day
+history-remove = Remove from history
+history-entry-subtitle = { $site } · Read { $read }%
+history-entry-tooltip = <b>Visited:</b> { $time }
+history-load-failed = Couldn't load history: { $error }
+history-save-failed = Couldn't save history: { $error }
+
+
+session-load-failed = Couldn't restore the last session: { $error }
+settings-load-failed = Couldn't load settings, using the defaults: { $error }
+settings-save-failed = Couldn't save settings: { $error }
+
+
+offline-download = Download for Offline
+offline-site = Site
+offline-target = Tag or question ids
+offline-limit = Questions of a tag
+offline-start = Download
+offline-listing = Listing questions…
+offline-progress = { $done }/{ $total } questions
+offline-finished = { $count } questions are available offline.
+offline-failed = Download stopped, start it again to resume: { $error }
+offline-unfinished = Unfinished Downloads
+offline-unfinished-subtitle = { $site } · { $done }/{ $total } questions
+offline-resume = Resume
+
+
+dump-import = Data Dump
+dump-import-description = Import this site from the official StackExchange data dumps, it's read from the imported copy instead of the API afterwards.
+dump-import-row = Extracted dump folder
+dump-import-choose = Choose Folder…
+dump-import-finished = { $site } is now read from the imported dump.
+dump-import-failed = Import failed: { $error }
+
+
+preferences = Preferences
+preferences-saved = Preferences saved, restart StackBloatLess to apply them.
+preferences-network = Network
+preferences-api = StackExchange API
+preferences-restart = Changes are applied after restarting.
+preferences-endpoint = Endpoint
+preferences-key = App key
+preferences-user-agent = User agent
+preferences-connect-timeout = Connection timeout
+preferences-request-timeout = Request timeout
+preferences-timeout-subtitle = Seconds, 0 to wait forever
+preferences-cache-ttl = Cache duration
+preferences-cache-ttl-subtitle = Minutes before cached questions are requested again
+preferences-proxy = Proxy
+preferences-proxy-mode = Mode
+preferences-proxy-system = System
+preferences-proxy-direct = No proxy
+preferences-proxy-manual = Manual
+preferences-proxy-url = Proxy URL (http, https, socks5)
+preferences-proxy-username = Username
+preferences-proxy-password = Password
+preferences-no-proxy = Don't proxy these hosts (comma separated)
+preferences-certificates = Certificates
+preferences-ca-bundle = Extra certificate authorities
+preferences-ca-bundle-none = Only the system's certificates are trusted
+preferences-ca-bundle-choose = Choose PEM File…
+preferences-ca-bundle-clear = Clear
+preferences-ca-bundle-filter = PEM certificates
+
+
+image-load-failed = Couldn't load { $url }: { $error }
+image-viewer = Image
+image-zoom-in = Zoom In
+image-zoom-out = Zoom Out
+image-zoom-original = Original Size
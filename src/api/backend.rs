@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::cache::CachingBackend;
+use super::config::ApiConfig;
+use super::dump::import::ImportProgress;
+use super::dump::{DumpLibrary, DumpRouter};
+use super::error::ApiError;
+use super::fixtures::FixtureBackend;
+use super::offline::{OfflineBackend, OfflinePack, PackProgress};
+use super::scheduler::Quota;
+use super::stackexchange::{
+    Comment, Id, Page, Question, QuestionSummary, SearchQuery, Site, StackExchange, Tag,
+    UserProfile,
+};
+
+/// Environment variable to select the backend at startup, see `from_env`.
+pub const BACKEND_ENV_VAR: &str = "STACKBLOATLESS_BACKEND";
+
+/// Shared backend used by every component.
+pub type Backend = Arc<dyn QaBackend>;
+
+/// Source of the questions, answers, and the rest of the sites data shown in the app.
+///
+/// Every method takes the `api_site_parameter` of the site, so backends could serve many sites.
+#[async_trait]
+pub trait QaBackend: Send + Sync {
+    /// Get questions by their ids, in the same order, skipping the ones that don't exist.
+    async fn get_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError>;
+
+    /// Fetch answers and comments that didn't fit in the question's first page.
+    async fn complete_question(&self, site: &str, question: Question)
+        -> Result<Question, ApiError>;
+
+    async fn search_questions(
+        &self,
+        site: &str,
+        query: &SearchQuery,
+        page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError>;
+
+    /// Get all comments of a post (question or answer), from oldest to newest.
+    async fn get_post_comments(&self, site: &str, post_id: Id) -> Result<Vec<Comment>, ApiError>;
+
+    /// Get a user profile, `None` when the user doesn't exist.
+    async fn get_user(&self, site: &str, user_id: Id) -> Result<Option<UserProfile>, ApiError>;
+
+    /// Get the id of the question an answer belongs to, `None` when the answer doesn't exist.
+    async fn get_answer_question_id(
+        &self,
+        site: &str,
+        answer_id: Id,
+    ) -> Result<Option<Id>, ApiError>;
+
+    /// Get a tag with its questions count, `None` when the tag doesn't exist.
+    async fn get_tag(&self, site: &str, name: &str) -> Result<Option<Tag>, ApiError>;
+
+    async fn get_sites(&self) -> Result<Vec<Site>, ApiError>;
+
+    /// Get an image shown in a post, like screenshots uploaded to i.sstatic.net.
+    async fn get_image(&self, _url: &str) -> Result<Vec<u8>, ApiError> {
+        Err(ApiError::Unsupported("images"))
+    }
+
+    /// Watch the daily requests quota, for backends that have one.
+    fn subscribe_quota(&self) -> Option<tokio::sync::watch::Receiver<Option<Quota>>> {
+        None
+    }
+
+    /// Whether `get_sites` lists every site of the network, not only the ones it has data of.
+    fn lists_every_site(&self) -> bool {
+        false
+    }
+
+    /// Get ids of the highest voted questions of a tag, up to `limit`.
+    async fn list_tag_questions(
+        &self,
+        _site: &str,
+        _tag: &str,
+        _limit: u32,
+    ) -> Result<Vec<Id>, ApiError> {
+        Err(ApiError::Unsupported("offline downloads"))
+    }
+
+    /// Download the questions of a pack for offline reading.
+    async fn download_pack(
+        &self,
+        _pack: &mut OfflinePack,
+        _on_progress: &mut (dyn FnMut(PackProgress) + Send),
+    ) -> Result<(), ApiError> {
+        Err(ApiError::Unsupported("offline downloads"))
+    }
+
+    /// Import the data dump of a site extracted in `dir`, blocking until it's done.
+    fn import_dump(
+        &self,
+        _site: &str,
+        _dir: &Path,
+        _on_progress: &mut dyn FnMut(ImportProgress),
+    ) -> Result<(), ApiError> {
+        Err(ApiError::Unsupported("data dump imports"))
+    }
+}
+
+/// Select the backend from the `STACKBLOATLESS_BACKEND` environment variable:
+///
+/// - `api` (default): The StackExchange API with cached responses and questions downloaded for
+///   offline reading, imported dumps are used instead for their sites.
+/// - `dumps`: Imported dumps only, without any network access.
+/// - `replay:{dir}`: Responses recorded in a directory, for tests.
+/// - `record:{dir}`: The StackExchange API, recording its responses in a directory.
+///
+/// Unknown values fall back to the default. API clients use `config` with its environment
+/// overrides.
+pub fn from_env(config: ApiConfig) -> Backend {
+    let config = config.with_env_overrides();
+    let value = std::env::var(BACKEND_ENV_VAR).unwrap_or_default();
+
+    match value.split_once(':') {
+        Some(("replay", dir)) => Arc::new(FixtureBackend::replay(PathBuf::from(dir))),
+        Some(("record", dir)) => Arc::new(FixtureBackend::record(
+            PathBuf::from(dir),
+            Arc::new(StackExchange::with_config(config)),
+        )),
+        _ if value == "dumps" => Arc::new(DumpLibrary::new()),
+        _ => {
+            let cache_ttl = config.cache_ttl();
+            let api = Arc::new(StackExchange::with_config(config));
+
+            Arc::new(DumpRouter::new(Arc::new(OfflineBackend::new(Arc::new(
+                CachingBackend::new(api, cache_ttl),
+            )))))
+        }
+    }
+}
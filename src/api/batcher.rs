@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use super::error::ApiError;
+use super::stackexchange::{Id, Question};
+
+/// How long to wait for other requests to join a batch before sending it.
+pub const BATCH_WINDOW: Duration = Duration::from_millis(50);
+
+/// Maximum number of ids accepted by `/questions/{ids}`.
+pub const MAX_IDS_PER_REQUEST: usize = 100;
+
+type QuestionsResult = Result<Vec<Question>, ApiError>;
+
+// A requester waiting for its questions out of a batch.
+struct Waiter {
+    ids: Vec<Id>,
+    sender: oneshot::Sender<QuestionsResult>,
+}
+
+/// Questions ids from multiple requesters, to be fetched together from one site.
+#[derive(Default)]
+pub struct QuestionsBatch {
+    pub ids: Vec<Id>,
+    waiters: Vec<Waiter>,
+}
+
+impl QuestionsBatch {
+    /// Send every waiter its own questions, in the same order it requested them.
+    pub fn dispatch(self, result: QuestionsResult) {
+        match result {
+            Ok(questions) => {
+                let questions = questions
+                    .into_iter()
+                    .map(|question| (question.question_id, question))
+                    .collect::<HashMap<Id, Question>>();
+
+                for waiter in self.waiters {
+                    let _ = waiter.sender.send(Ok(waiter
+                        .ids
+                        .iter()
+                        .filter_map(|id| questions.get(id).cloned())
+                        .collect()));
+                }
+            }
+            Err(error) => {
+                for waiter in self.waiters {
+                    let _ = waiter.sender.send(Err(error.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Collects questions lookups per site, so concurrent lookups are merged in batched requests.
+pub struct QuestionsBatcher {
+    pending: Mutex<HashMap<String, QuestionsBatch>>,
+}
+
+impl QuestionsBatcher {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Add ids to the pending batch of a site.
+    ///
+    /// The first requester of a batch is its leader, it's responsible for sending the batch
+    /// after `BATCH_WINDOW` and dispatching its result.
+    pub fn enqueue(&self, site: &str, ids: &[Id]) -> (oneshot::Receiver<QuestionsResult>, bool) {
+        let mut pending = self.pending.lock().unwrap();
+
+        let is_leader = !pending.contains_key(site);
+        let batch = pending.entry(site.to_owned()).or_default();
+
+        for id in ids {
+            if !batch.ids.contains(id) {
+                batch.ids.push(*id);
+            }
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        batch.waiters.push(Waiter {
+            ids: ids.to_vec(),
+            sender,
+        });
+
+        (receiver, is_leader)
+    }
+
+    /// Remove the pending batch of a site, so new lookups start a new one.
+    pub fn take(&self, site: &str) -> QuestionsBatch {
+        self.pending
+            .lock()
+            .unwrap()
+            .remove(site)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuestionsBatcher;
+    use crate::api::error::ApiError;
+    use crate::api::stackexchange::{Id, Question};
+    use crate::test_utils::question;
+
+    fn question_ids(questions: Vec<Question>) -> Vec<Id> {
+        questions
+            .into_iter()
+            .map(|question| question.question_id)
+            .collect()
+    }
+
+    #[test]
+    fn merges_lookups_of_a_site_until_taken() {
+        let batcher = QuestionsBatcher::new();
+
+        assert!(batcher.enqueue("stackoverflow", &[1, 2]).1);
+        assert!(!batcher.enqueue("stackoverflow", &[2, 3]).1);
+        assert!(batcher.enqueue("superuser", &[1]).1);
+
+        assert_eq!(batcher.take("stackoverflow").ids, [1, 2, 3]);
+        assert_eq!(batcher.take("superuser").ids, [1]);
+        assert!(batcher.take("superuser").ids.is_empty());
+
+        // A new batch starts once the previous one was taken.
+        assert!(batcher.enqueue("stackoverflow", &[4]).1);
+        assert_eq!(batcher.take("stackoverflow").ids, [4]);
+    }
+
+    #[test]
+    fn dispatches_each_waiter_its_questions() {
+        let batcher = QuestionsBatcher::new();
+        let (mut first, _) = batcher.enqueue("stackoverflow", &[3, 1]);
+        let (mut second, _) = batcher.enqueue("stackoverflow", &[2, 4]);
+
+        // Question 4 was deleted, so it's missing from the response.
+        batcher
+            .take("stackoverflow")
+            .dispatch(Ok((1..=3).map(|id| question(String::new(), id)).collect()));
+
+        assert_eq!(question_ids(first.try_recv().unwrap().unwrap()), [3, 1]);
+        assert_eq!(question_ids(second.try_recv().unwrap().unwrap()), [2]);
+    }
+
+    #[test]
+    fn dispatches_errors_to_every_waiter() {
+        let batcher = QuestionsBatcher::new();
+        let (mut first, _) = batcher.enqueue("stackoverflow", &[1]);
+        let (mut second, _) = batcher.enqueue("stackoverflow", &[2]);
+
+        batcher
+            .take("stackoverflow")
+            .dispatch(Err(ApiError::QuotaExhausted));
+
+        assert!(matches!(
+            first.try_recv().unwrap(),
+            Err(ApiError::QuotaExhausted)
+        ));
+        assert!(matches!(
+            second.try_recv().unwrap(),
+            Err(ApiError::QuotaExhausted)
+        ));
+    }
+}
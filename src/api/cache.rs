@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json as json;
+
+use super::backend::{Backend, QaBackend};
+use super::dump::import::ImportProgress;
+use super::error::ApiError;
+use super::offline::{OfflinePack, PackProgress};
+use super::scheduler::Quota;
+use super::stackexchange::{
+    Comment, Id, Page, Question, QuestionSummary, SearchQuery, Site, Tag, UserProfile,
+};
+
+/// How long cached responses are used without asking the API again.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    // Unix time of when the response was received.
+    stored: i64,
+    value: T,
+}
+
+/// A cached response and whether it's still within the TTL.
+pub struct CacheHit<T> {
+    pub value: T,
+    pub stored: i64,
+    pub is_fresh: bool,
+}
+
+/// Disk cache of API responses, keyed by method, site and id.
+///
+/// Stale entries are kept, so they could still be read when the API can't be reached.
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        site: &str,
+        id: Id,
+    ) -> Option<CacheHit<T>> {
+        let content = std::fs::read(self.entry_file(method, site, id)).ok()?;
+        // Entries of older versions might not match the current types, they are overwritten.
+        let entry = json::from_slice::<Entry<T>>(&content).ok()?;
+
+        let age = crate::storage::now().saturating_sub(entry.stored);
+
+        Some(CacheHit {
+            value: entry.value,
+            stored: entry.stored,
+            is_fresh: (0..self.ttl.as_secs() as i64).contains(&age),
+        })
+    }
+
+    pub fn put<T: Serialize>(&self, method: &str, site: &str, id: Id, value: &T) {
+        let entry_file = self.entry_file(method, site, id);
+
+        // Failing to write the cache is not fatal, it will be fetched again next time.
+        if let Some(parent) = entry_file.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(content) = json::to_vec(&Entry {
+            stored: crate::storage::now(),
+            value,
+        }) {
+            let _ = std::fs::write(entry_file, content);
+        }
+    }
+
+    /// Ids of every entry of a site, fresh or not.
+    pub fn ids(&self, method: &str, site: &str) -> Vec<Id> {
+        let Ok(entries) = std::fs::read_dir(self.dir.join(method)) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(|entry| {
+                let file_name = entry.ok()?.file_name();
+                let name = file_name.to_str()?.strip_suffix(".json")?;
+                name.strip_prefix(site)?.strip_prefix('_')?.parse().ok()
+            })
+            .collect()
+    }
+
+    // Sites parameters don't include path separators, so they can't escape the cache directory.
+    fn entry_file(&self, method: &str, site: &str, id: Id) -> PathBuf {
+        self.dir.join(method).join(format!("{site}_{id}.json"))
+    }
+}
+
+/// Serves responses of another backend from a disk cache while they are fresh.
+///
+/// Stale responses are only used when the API can't be reached, along with when they were cached.
+pub struct CachingBackend {
+    inner: Backend,
+    cache: ResponseCache,
+}
+
+impl CachingBackend {
+    /// Cache questions, answers and comments of `inner` for `ttl`.
+    pub fn new(inner: Backend, ttl: Duration) -> Self {
+        Self::with_dir(inner, crate::utils::cache_dir().join("api"), ttl)
+    }
+
+    fn with_dir(inner: Backend, dir: PathBuf, ttl: Duration) -> Self {
+        Self {
+            inner,
+            cache: ResponseCache::new(dir, ttl),
+        }
+    }
+
+    // Use a fresh cached response, or fetch it and update the cache.
+    async fn cached<T: Serialize + DeserializeOwned>(
+        &self,
+        method: &str,
+        site: &str,
+        id: Id,
+        fetch: impl Future<Output = Result<T, ApiError>>,
+    ) -> Result<(T, Option<i64>), ApiError> {
+        let hit = match self.cache.get::<T>(method, site, id) {
+            Some(hit) if hit.is_fresh => return Ok((hit.value, None)),
+            hit => hit,
+        };
+
+        match fetch.await {
+            Ok(value) => {
+                self.cache.put(method, site, id, &value);
+                Ok((value, None))
+            }
+            Err(error) if error.is_offline() => {
+                hit.map(|hit| (hit.value, Some(hit.stored))).ok_or(error)
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+#[async_trait]
+impl QaBackend for CachingBackend {
+    async fn get_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+        let mut cached = ids
+            .iter()
+            .filter_map(|id| Some((*id, self.cache.get::<Question>("questions", site, *id)?)))
+            .collect::<HashMap<_, _>>();
+
+        let missing = ids
+            .iter()
+            .copied()
+            .filter(|id| !cached.get(id).is_some_and(|hit| hit.is_fresh))
+            .collect::<Vec<Id>>();
+
+        let mut fetched = HashMap::new();
+
+        if !missing.is_empty() {
+            match self.inner.get_questions(site, &missing).await {
+                Ok(questions) => {
+                    // Questions that are not returned anymore were deleted.
+                    for id in &missing {
+                        cached.remove(id);
+                    }
+
+                    for question in questions {
+                        self.cache
+                            .put("questions", site, question.question_id, &question);
+                        fetched.insert(question.question_id, question);
+                    }
+                }
+                // Every question needs at least a stale copy to be shown offline.
+                Err(error)
+                    if error.is_offline() && missing.iter().all(|id| cached.contains_key(id)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| {
+                fetched.get(id).cloned().or_else(|| {
+                    cached.get(id).map(|hit| Question {
+                        cached_at: (!hit.is_fresh).then_some(hit.stored),
+                        ..hit.value.clone()
+                    })
+                })
+            })
+            .collect())
+    }
+
+    async fn complete_question(
+        &self,
+        site: &str,
+        question: Question,
+    ) -> Result<Question, ApiError> {
+        if question.is_complete() {
+            return Ok(question);
+        }
+
+        let question_id = question.question_id;
+        let cached_at = question.cached_at;
+
+        let (mut completed, completed_cached_at) = self
+            .cached(
+                "complete_question",
+                site,
+                question_id,
+                self.inner.complete_question(site, question),
+            )
+            .await?;
+
+        completed.cached_at = cached_at.or(completed_cached_at);
+
+        Ok(completed)
+    }
+
+    async fn search_questions(
+        &self,
+        site: &str,
+        query: &SearchQuery,
+        page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError> {
+        self.inner.search_questions(site, query, page).await
+    }
+
+    async fn get_post_comments(&self, site: &str, post_id: Id) -> Result<Vec<Comment>, ApiError> {
+        let (comments, _) = self
+            .cached(
+                "comments",
+                site,
+                post_id,
+                self.inner.get_post_comments(site, post_id),
+            )
+            .await?;
+
+        Ok(comments)
+    }
+
+    async fn get_user(&self, site: &str, user_id: Id) -> Result<Option<UserProfile>, ApiError> {
+        self.inner.get_user(site, user_id).await
+    }
+
+    async fn get_answer_question_id(
+        &self,
+        site: &str,
+        answer_id: Id,
+    ) -> Result<Option<Id>, ApiError> {
+        self.inner.get_answer_question_id(site, answer_id).await
+    }
+
+    async fn get_tag(&self, site: &str, name: &str) -> Result<Option<Tag>, ApiError> {
+        self.inner.get_tag(site, name).await
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>, ApiError> {
+        self.inner.get_sites().await
+    }
+
+    async fn get_image(&self, url: &str) -> Result<Vec<u8>, ApiError> {
+        self.inner.get_image(url).await
+    }
+
+    fn subscribe_quota(&self) -> Option<tokio::sync::watch::Receiver<Option<Quota>>> {
+        self.inner.subscribe_quota()
+    }
+
+    fn lists_every_site(&self) -> bool {
+        self.inner.lists_every_site()
+    }
+
+    async fn list_tag_questions(
+        &self,
+        site: &str,
+        tag: &str,
+        limit: u32,
+    ) -> Result<Vec<Id>, ApiError> {
+        self.inner.list_tag_questions(site, tag, limit).await
+    }
+
+    async fn download_pack(
+        &self,
+        pack: &mut OfflinePack,
+        on_progress: &mut (dyn FnMut(PackProgress) + Send),
+    ) -> Result<(), ApiError> {
+        self.inner.download_pack(pack, on_progress).await
+    }
+
+    fn import_dump(
+        &self,
+        site: &str,
+        dir: &Path,
+        on_progress: &mut dyn FnMut(ImportProgress),
+    ) -> Result<(), ApiError> {
+        self.inner.import_dump(site, dir, on_progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::CachingBackend;
+    use crate::api::backend::QaBackend;
+    use crate::api::error::ApiError;
+    use crate::api::stackexchange::Question;
+    use crate::test_utils::{block_on, FakeBackend, TestDir};
+
+    fn titles(questions: &[Question]) -> Vec<&str> {
+        questions
+            .iter()
+            .map(|question| question.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn uses_fresh_responses_without_asking_again() {
+        let dir = TestDir::new("cache-fresh");
+        let inner = Arc::new(FakeBackend::default());
+        let backend =
+            CachingBackend::with_dir(inner.clone(), dir.0.clone(), Duration::from_secs(60));
+
+        block_on(backend.get_questions("stackoverflow", &[1, 2])).unwrap();
+        let questions = block_on(backend.get_questions("stackoverflow", &[3, 1, 2])).unwrap();
+
+        assert_eq!(*inner.requests.lock().unwrap(), [vec![1, 2], vec![3]]);
+        assert_eq!(titles(&questions), ["request 1", "request 0", "request 0"]);
+        assert!(questions
+            .iter()
+            .all(|question| question.cached_at.is_none()));
+
+        // Other sites have their own questions with the same ids.
+        block_on(backend.get_questions("superuser", &[1])).unwrap();
+        assert_eq!(inner.requests.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn uses_stale_responses_only_when_offline() {
+        let dir = TestDir::new("cache-stale");
+        let inner = Arc::new(FakeBackend::default());
+        let backend = CachingBackend::with_dir(inner.clone(), dir.0.clone(), Duration::ZERO);
+
+        block_on(backend.get_questions("stackoverflow", &[1])).unwrap();
+        let questions = block_on(backend.get_questions("stackoverflow", &[1])).unwrap();
+        assert_eq!(titles(&questions), ["request 1"]);
+        assert_eq!(questions[0].cached_at, None);
+
+        inner.offline.store(true, Ordering::Relaxed);
+
+        let questions = block_on(backend.get_questions("stackoverflow", &[1])).unwrap();
+        assert_eq!(titles(&questions), ["request 1"]);
+        assert!(questions[0].cached_at.is_some());
+
+        // Without a copy of every question, the error is shown instead of a partial result.
+        assert!(matches!(
+            block_on(backend.get_questions("stackoverflow", &[1, 2])),
+            Err(ApiError::Transport(_))
+        ));
+    }
+}
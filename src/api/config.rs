@@ -0,0 +1,292 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::{header::HeaderValue, Certificate, NoProxy, Proxy, Url};
+use serde::{Deserialize, Serialize};
+
+use super::cache::DEFAULT_CACHE_TTL;
+use super::error::ApiError;
+
+pub const DEFAULT_API_ENDPOINT: &str = "https://api.stackexchange.com/2.3";
+
+// Environment variables that override the settings, mostly for tests and temporary setups.
+pub const API_ENDPOINT_ENV_VAR: &str = "STACKBLOATLESS_API_ENDPOINT";
+pub const API_KEY_ENV_VAR: &str = "STACKBLOATLESS_API_KEY";
+pub const USER_AGENT_ENV_VAR: &str = "STACKBLOATLESS_USER_AGENT";
+pub const CONNECT_TIMEOUT_ENV_VAR: &str = "STACKBLOATLESS_CONNECT_TIMEOUT";
+pub const REQUEST_TIMEOUT_ENV_VAR: &str = "STACKBLOATLESS_REQUEST_TIMEOUT";
+pub const CACHE_TTL_ENV_VAR: &str = "STACKBLOATLESS_CACHE_TTL";
+
+/// Where and how the API client sends its requests.
+///
+/// Durations are in seconds, so the settings file is easy to edit by hand.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Base URL of the API including its version, like `https://api.stackexchange.com/2.3`.
+    pub endpoint: String,
+    /// App key registered on StackApps, it raises the daily quota from 300 to 10,000 requests.
+    ///
+    /// Docs: https://api.stackexchange.com/docs/throttle
+    pub key: Option<String>,
+    /// Sent in the `User-Agent` header, `None` for the app's name and version.
+    pub user_agent: Option<String>,
+    /// Time to wait for a connection to be established, 0 to wait forever.
+    pub connect_timeout: u64,
+    /// Time to wait for a whole response, 0 to wait forever.
+    pub request_timeout: u64,
+    /// Cached responses are reused without requests for this long.
+    pub cache_ttl: u64,
+    pub proxy: ProxyConfig,
+    /// PEM file of extra certificate authorities to trust, e.g. of a corporate proxy.
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_API_ENDPOINT.to_owned(),
+            key: None,
+            user_agent: None,
+            connect_timeout: 10,
+            request_timeout: 30,
+            cache_ttl: DEFAULT_CACHE_TTL.as_secs(),
+            proxy: ProxyConfig::default(),
+            ca_bundle: None,
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Override the settings with environment variables that are set.
+    ///
+    /// Invalid numbers are ignored, and empty strings unset the key or the user agent.
+    pub fn with_env_overrides(mut self) -> Self {
+        use std::env;
+
+        fn optional(value: String) -> Option<String> {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_owned())
+        }
+
+        if let Ok(endpoint) = env::var(API_ENDPOINT_ENV_VAR) {
+            self.endpoint = endpoint;
+        }
+        if let Ok(key) = env::var(API_KEY_ENV_VAR) {
+            self.key = optional(key);
+        }
+        if let Ok(user_agent) = env::var(USER_AGENT_ENV_VAR) {
+            self.user_agent = optional(user_agent);
+        }
+
+        for (env_var, seconds) in [
+            (CONNECT_TIMEOUT_ENV_VAR, &mut self.connect_timeout),
+            (REQUEST_TIMEOUT_ENV_VAR, &mut self.request_timeout),
+            (CACHE_TTL_ENV_VAR, &mut self.cache_ttl),
+        ] {
+            if let Some(value) = env::var(env_var).ok().and_then(|value| value.parse().ok()) {
+                *seconds = value;
+            }
+        }
+
+        self
+    }
+
+    /// Build an HTTP client with these settings.
+    ///
+    /// An invalid user agent is replaced by the default one, since it's only informative, but an
+    /// invalid endpoint, proxy or CA bundle is an error, so requests never bypass them.
+    pub fn http_client(&self) -> Result<reqwest::Client, ApiError> {
+        self.method_url([])?;
+
+        let user_agent = HeaderValue::from_str(&self.user_agent())
+            .unwrap_or_else(|_| HeaderValue::from_str(&Self::default().user_agent()).unwrap());
+
+        let mut builder = reqwest::Client::builder().gzip(true).user_agent(user_agent);
+
+        if let Some(timeout) = self.connect_timeout() {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.request_timeout() {
+            builder = builder.timeout(timeout);
+        }
+
+        match self.proxy.mode {
+            // The client reads `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` by itself, but not the
+            // desktop settings.
+            ProxyMode::System => {}
+            ProxyMode::Direct => builder = builder.no_proxy(),
+            ProxyMode::Manual => builder = builder.proxy(self.proxy.proxy()?),
+        }
+
+        if let Some(path) = &self.ca_bundle {
+            let pem = std::fs::read(path).map_err(|error| {
+                ApiError::Config(format!("can't read {}: {error}", path.display()))
+            })?;
+
+            for certificate in Certificate::from_pem_bundle(&pem).map_err(config_error)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        builder.build().map_err(config_error)
+    }
+
+    /// URL of an API method from its path segments, like `["tags", name, "info"]`.
+    ///
+    /// Segments are escaped, so they can't change the path or add a query. The endpoint must be
+    /// an HTTP(S) URL that paths can be appended to.
+    pub fn method_url<'a>(
+        &self,
+        segments: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Url, ApiError> {
+        let endpoint = self.endpoint.trim();
+        let invalid_endpoint =
+            || ApiError::Config(format!("`{endpoint}` is not a valid API endpoint"));
+
+        let mut url = Url::parse(endpoint).map_err(|_| invalid_endpoint())?;
+
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(invalid_endpoint());
+        }
+
+        // `Url::join` would replace the API version segment instead of appending to it.
+        url.path_segments_mut()
+            .map_err(|_| invalid_endpoint())?
+            .pop_if_empty()
+            .extend(segments);
+
+        Ok(url)
+    }
+
+    pub fn user_agent(&self) -> String {
+        self.user_agent
+            .clone()
+            .unwrap_or_else(|| format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")))
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        (self.connect_timeout > 0).then(|| Duration::from_secs(self.connect_timeout))
+    }
+
+    pub fn request_timeout(&self) -> Option<Duration> {
+        (self.request_timeout > 0).then(|| Duration::from_secs(self.request_timeout))
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_ttl)
+    }
+}
+
+/// How requests reach the API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Proxy of the environment variables, or of the desktop settings once they are resolved.
+    ///
+    /// The client alone only reads the environment variables, the app looks up the desktop's
+    /// proxy for the endpoint when it starts and uses it as a manual one.
+    #[default]
+    System,
+    /// Connect directly, even when a system proxy is set.
+    Direct,
+    /// Use `ProxyConfig::url` with its credentials.
+    Manual,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub mode: ProxyMode,
+    /// Like `http://proxy:3128`, `https://proxy:443` or `socks5h://127.0.0.1:9050` for Tor.
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Comma separated hosts, domains (`.example.com`) and IP ranges to connect to directly.
+    pub no_proxy: String,
+}
+
+impl ProxyConfig {
+    fn proxy(&self) -> Result<Proxy, ApiError> {
+        let mut proxy = Proxy::all(self.url.trim()).map_err(|_| {
+            ApiError::Config(format!("`{}` is not a valid proxy URL", self.url.trim()))
+        })?;
+
+        if let Some(username) = self
+            .username
+            .as_deref()
+            .filter(|username| !username.is_empty())
+        {
+            proxy = proxy.basic_auth(username, self.password.as_deref().unwrap_or_default());
+        }
+
+        Ok(proxy.no_proxy(NoProxy::from_string(&self.no_proxy)))
+    }
+}
+
+// Client errors are only `builder error` without their sources.
+fn config_error(error: impl std::error::Error) -> ApiError {
+    let mut message = error.to_string();
+    let mut source = error.source();
+
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+
+    ApiError::Config(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(endpoint: &str) -> ApiConfig {
+        ApiConfig {
+            endpoint: endpoint.to_owned(),
+            ..ApiConfig::default()
+        }
+    }
+
+    #[test]
+    fn appends_escaped_segments_to_the_endpoint() {
+        for endpoint in [DEFAULT_API_ENDPOINT, "https://api.stackexchange.com/2.3/"] {
+            assert_eq!(
+                config(endpoint)
+                    .method_url(["tags", "c#/f?", "info"])
+                    .unwrap()
+                    .as_str(),
+                "https://api.stackexchange.com/2.3/tags/c%23%2Ff%3F/info"
+            );
+        }
+
+        assert_eq!(
+            config("http://localhost:8080")
+                .method_url(["questions", "1;2"])
+                .unwrap()
+                .as_str(),
+            "http://localhost:8080/questions/1;2"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_endpoints() {
+        for endpoint in [
+            "",
+            "api.stackexchange.com/2.3",
+            "ftp://example.com",
+            "mailto:a@b.c",
+        ] {
+            let config = config(endpoint);
+
+            assert!(
+                matches!(config.method_url(["sites"]), Err(ApiError::Config(_))),
+                "{endpoint}"
+            );
+            assert!(
+                matches!(config.http_client(), Err(ApiError::Config(_))),
+                "{endpoint}"
+            );
+        }
+    }
+}
@@ -0,0 +1,505 @@
+//! Conversion of post bodies in data dumps, which are rendered HTML, back to markdown.
+
+use crate::api::stackexchange::decode_html_entities;
+
+// Elements that never have children or an end tag.
+const VOID_ELEMENTS: [&str; 5] = ["br", "hr", "img", "input", "wbr"];
+
+enum Node {
+    Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+    Text(String),
+}
+
+impl Node {
+    fn attribute(&self, key: &str) -> Option<&str> {
+        match self {
+            Self::Element { attributes, .. } => attributes
+                .iter()
+                .find_map(|(name, value)| (name == key).then_some(value.as_str())),
+            Self::Text(_) => None,
+        }
+    }
+}
+
+/// Convert the subset of HTML used by StackExchange posts to markdown.
+///
+/// Unknown elements are replaced by their content, so nothing is lost even when it's not styled.
+pub fn html_to_markdown(html: &str) -> String {
+    let mut markdown = String::new();
+    render_nodes(&parse(html), &mut markdown, false);
+
+    // Blocks are separated generously while rendering, tidy up the result.
+    let mut tidy = String::with_capacity(markdown.len());
+    let mut blank_lines = 0;
+    let mut in_code_block = false;
+
+    for line in markdown.trim().lines() {
+        // Code is kept as it is.
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block {
+            tidy.push_str(line);
+            tidy.push('\n');
+            blank_lines = 0;
+            continue;
+        }
+
+        // Keep the trailing spaces of line breaks only.
+        let line = if line.ends_with("  ") {
+            line
+        } else {
+            line.trim_end()
+        };
+
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+
+        tidy.push_str(line);
+        tidy.push('\n');
+    }
+
+    tidy.trim_end().to_owned()
+}
+
+// Name, attributes and children of an element that isn't closed yet.
+type OpenElement = (String, Vec<(String, String)>, Vec<Node>);
+
+// Build a tree, closing unclosed elements the way browsers would do in the common cases.
+fn parse(html: &str) -> Vec<Node> {
+    // Open elements, the first one is a root holding the top level nodes.
+    let mut stack: Vec<OpenElement> = vec![(String::new(), Vec::new(), Vec::new())];
+    let mut rest = html;
+
+    fn close(stack: &mut Vec<OpenElement>) {
+        if let Some((name, attributes, children)) = stack.pop() {
+            if let Some((_, _, parent)) = stack.last_mut() {
+                parent.push(Node::Element {
+                    name,
+                    attributes,
+                    children,
+                });
+            }
+        }
+    }
+
+    while !rest.is_empty() {
+        let Some(tag_start) = rest.find('<') else {
+            stack
+                .last_mut()
+                .unwrap()
+                .2
+                .push(Node::Text(rest.to_owned()));
+            break;
+        };
+
+        if tag_start > 0 {
+            stack
+                .last_mut()
+                .unwrap()
+                .2
+                .push(Node::Text(rest[..tag_start].to_owned()));
+        }
+        rest = &rest[tag_start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let Some(tag_end) = rest.find('>') else {
+            // A lone `<` is just text.
+            stack
+                .last_mut()
+                .unwrap()
+                .2
+                .push(Node::Text(rest.to_owned()));
+            break;
+        };
+        let raw_tag = &rest[..=tag_end];
+        let tag = &rest[1..tag_end];
+        rest = &rest[tag_end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim().to_lowercase();
+
+            // Ignore end tags of elements that are not open, the root is never closed.
+            if let Some(position) = stack[1..]
+                .iter()
+                .rposition(|(open, ..)| !name.is_empty() && *open == name)
+            {
+                while stack.len() > position + 1 {
+                    close(&mut stack);
+                }
+            }
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let name = name.to_lowercase();
+
+        // Not a tag, like in `a <> b` or `a < b > c`.
+        if name.is_empty() {
+            stack
+                .last_mut()
+                .unwrap()
+                .2
+                .push(Node::Text(raw_tag.to_owned()));
+            continue;
+        }
+
+        // Paragraphs and list items end when the next one starts.
+        if matches!(name.as_str(), "p" | "li") {
+            if let Some(position) = stack.iter().rposition(|(open, ..)| *open == name) {
+                if stack[position + 1..]
+                    .iter()
+                    .all(|(open, ..)| !matches!(open.as_str(), "ul" | "ol" | "blockquote"))
+                {
+                    while stack.len() > position {
+                        close(&mut stack);
+                    }
+                }
+            }
+        }
+
+        stack.push((name.clone(), parse_attributes(attributes), Vec::new()));
+
+        if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+            close(&mut stack);
+        }
+    }
+
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+
+    stack
+        .pop()
+        .map(|(.., children)| children)
+        .unwrap_or_default()
+}
+
+fn parse_attributes(mut attributes: &str) -> Vec<(String, String)> {
+    let mut parsed = Vec::new();
+
+    loop {
+        attributes = attributes.trim_start();
+        if attributes.is_empty() {
+            break;
+        }
+
+        let name_end = attributes
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(attributes.len());
+        let name = attributes[..name_end].to_lowercase();
+        attributes = attributes[name_end..].trim_start();
+
+        let value = match attributes.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                let (value, rest) = match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let value = &value[1..];
+                        let end = value.find(quote).unwrap_or(value.len());
+                        (&value[..end], value.get(end + 1..).unwrap_or_default())
+                    }
+                    _ => {
+                        let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                        (&value[..end], &value[end..])
+                    }
+                };
+                attributes = rest;
+                decode_html_entities(value)
+            }
+            None => String::new(),
+        };
+
+        parsed.push((name, value));
+    }
+
+    parsed
+}
+
+fn render_nodes(nodes: &[Node], out: &mut String, preformatted: bool) {
+    for node in nodes {
+        render_node(node, out, preformatted);
+    }
+}
+
+fn render_node(node: &Node, out: &mut String, preformatted: bool) {
+    let (name, children) = match node {
+        Node::Text(text) if preformatted => {
+            out.push_str(&decode_html_entities(text));
+            return;
+        }
+        Node::Text(text) => {
+            // Whitespace between blocks is only formatting of the HTML.
+            if !(text.trim().is_empty() && text.contains('\n')) {
+                let text = decode_html_entities(text);
+                let mut words = text.split_whitespace().peekable();
+
+                if text.starts_with(char::is_whitespace) && !out.ends_with([' ', '\n']) {
+                    out.push(' ');
+                }
+                while let Some(word) = words.next() {
+                    push_escaped(word, out);
+                    if words.peek().is_some() {
+                        out.push(' ');
+                    }
+                }
+                if text.ends_with(char::is_whitespace) && !text.trim().is_empty() {
+                    out.push(' ');
+                }
+            }
+            return;
+        }
+        Node::Element { name, children, .. } => (name.as_str(), children),
+    };
+
+    match name {
+        "p" | "div" => {
+            start_block(out);
+            render_nodes(children, out, false);
+            out.push_str("\n\n");
+        }
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            start_block(out);
+            out.push_str(&"#".repeat(name[1..].parse().unwrap_or(1)));
+            out.push(' ');
+            out.push_str(render_inline(children).trim());
+            out.push_str("\n\n");
+        }
+        "pre" => {
+            start_block(out);
+            let mut code = String::new();
+            render_nodes(children, &mut code, true);
+
+            // Fences must be longer than any backticks run in the code.
+            let fence = "`".repeat(longest_run(&code, '`').max(2) + 1);
+            out.push_str(&format!(
+                "{fence}\n{}\n{fence}\n\n",
+                code.trim_end_matches('\n')
+            ));
+        }
+        "blockquote" => {
+            start_block(out);
+            let mut quote = String::new();
+            render_nodes(children, &mut quote, false);
+
+            for line in quote.trim().lines() {
+                out.push_str(if line.is_empty() { ">" } else { "> " });
+                out.push_str(line);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        "ul" | "ol" => {
+            start_block(out);
+            let mut number = node
+                .attribute("start")
+                .and_then(|start| start.parse::<u32>().ok())
+                .unwrap_or(1);
+
+            for item in children {
+                if !matches!(item, Node::Element { name, .. } if name == "li") {
+                    continue;
+                }
+
+                let marker = if name == "ol" {
+                    format!("{number}. ")
+                } else {
+                    "- ".to_owned()
+                };
+                number += 1;
+
+                let mut content = String::new();
+                render_node(item, &mut content, false);
+
+                let indent = " ".repeat(marker.len());
+                for (index, line) in content.trim().lines().enumerate() {
+                    if index == 0 {
+                        out.push_str(&marker);
+                    } else if !line.is_empty() {
+                        out.push_str(&indent);
+                    }
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+            out.push('\n');
+        }
+        "li" => render_nodes(children, out, false),
+        "hr" => {
+            start_block(out);
+            out.push_str("---\n\n");
+        }
+        "br" => out.push_str("  \n"),
+        "code" if preformatted => render_nodes(children, out, true),
+        "code" | "kbd" => {
+            let mut code = String::new();
+            render_nodes(children, &mut code, true);
+
+            let ticks = "`".repeat(longest_run(&code, '`') + 1);
+            let padding = if code.starts_with('`') || code.ends_with('`') {
+                " "
+            } else {
+                ""
+            };
+            out.push_str(&format!("{ticks}{padding}{code}{padding}{ticks}"));
+        }
+        "strong" | "b" => wrap_inline(children, "**", out),
+        "em" | "i" => wrap_inline(children, "*", out),
+        "del" | "s" | "strike" => wrap_inline(children, "~~", out),
+        "a" => {
+            let text = render_inline(children);
+            match node.attribute("href") {
+                Some(href) if !href.is_empty() => {
+                    out.push_str(&format!("[{}]({href})", text.trim()));
+                }
+                _ => out.push_str(&text),
+            }
+        }
+        "img" => out.push_str(&format!(
+            "![{}]({})",
+            node.attribute("alt").unwrap_or_default(),
+            node.attribute("src").unwrap_or_default()
+        )),
+        _ => render_nodes(children, out, preformatted),
+    }
+}
+
+// Escape what markdown would otherwise take for formatting in a word of text.
+fn push_escaped(word: &str, out: &mut String) {
+    // Block markers only matter at the start of a line.
+    let marker = if !(out.is_empty() || out.ends_with('\n')) {
+        None
+    } else if word.starts_with(['#', '>', '-', '+']) {
+        Some(0)
+    } else {
+        word.find(|c: char| !c.is_ascii_digit())
+            .filter(|&end| end > 0 && word[end..].starts_with(['.', ')']))
+    };
+
+    for (index, c) in word.char_indices() {
+        if Some(index) == marker || matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+fn render_inline(children: &[Node]) -> String {
+    let mut text = String::new();
+    render_nodes(children, &mut text, false);
+    text
+}
+
+fn wrap_inline(children: &[Node], delimiter: &str, out: &mut String) {
+    let text = render_inline(children);
+    let trimmed = text.trim();
+
+    if trimmed.is_empty() {
+        out.push_str(&text);
+    } else {
+        out.push_str(&format!("{delimiter}{trimmed}{delimiter}"));
+    }
+}
+
+fn start_block(out: &mut String) {
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|other| other != c)
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::html_to_markdown;
+
+    #[test]
+    fn converts_blocks_and_inlines() {
+        assert_eq!(
+            html_to_markdown(
+                "<h2>Title</h2>\n<p>Some <strong>bold</strong>, <em>emphasized</em> and \
+                 <code>inline `code`</code> text with a <a href=\"https://example.com\">link</a>.</p>\n\
+                 <pre><code>fn main() {\n    println!(\"&lt;hi&gt;\");\n}\n</code></pre>\n<hr />"
+            ),
+            "## Title\n\n\
+             Some **bold**, *emphasized* and `` inline `code` `` text with a \
+             [link](https://example.com).\n\n\
+             ```\nfn main() {\n    println!(\"<hi>\");\n}\n```\n\n\
+             ---"
+        );
+    }
+
+    #[test]
+    fn converts_nested_lists_and_quotes() {
+        assert_eq!(
+            html_to_markdown(
+                "<ol start=\"3\"><li>First<ul><li>Nested</li></ul></li><li>Second</li></ol>\n\
+                 <blockquote><p>Quoted</p><blockquote><p>Twice</p></blockquote></blockquote>"
+            ),
+            "3. First\n\n   - Nested\n4. Second\n\n> Quoted\n>\n> > Twice"
+        );
+    }
+
+    #[test]
+    fn closes_unclosed_elements() {
+        assert_eq!(
+            html_to_markdown("<p>One<p>Two<ul><li>A<li>B</ul><p><b>Bold"),
+            "One\n\nTwo\n\n- A\n- B\n\n**Bold**"
+        );
+    }
+
+    #[test]
+    fn tolerates_malformed_html() {
+        // Stray end tags, even without a name, must not close the root.
+        assert_eq!(html_to_markdown("</>a</p></div>b</>"), "ab");
+        assert_eq!(html_to_markdown("a <> b < c > d"), r"a \<> b \< c > d");
+        assert_eq!(html_to_markdown("a <b"), r"a \<b");
+        assert_eq!(html_to_markdown("a <!-- unterminated"), "a");
+        assert_eq!(html_to_markdown("<a>No href</a>"), "No href");
+        assert_eq!(html_to_markdown(""), "");
+    }
+
+    #[test]
+    fn escapes_markdown_in_text() {
+        assert_eq!(
+            html_to_markdown(
+                r"<p># Not a *heading* [link] 1_000 `tick` \ &lt;b&gt;</p>
+                  <p>1. Not a list</p><ul><li>- Nor this</li></ul>
+                  <blockquote><p>&gt; Nor a quote</p></blockquote>
+                  <pre><code>*kept* as [is]</code></pre>"
+            ),
+            r"\# Not a \*heading\* \[link\] 1\_000 \`tick\` \\ \<b>
+
+1\. Not a list
+
+- \- Nor this
+
+> \> Nor a quote
+
+```
+*kept* as [is]
+```"
+        );
+    }
+}
@@ -0,0 +1,594 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use quick_xml::{events::Event, Reader};
+use rusqlite::{params, Connection};
+
+use super::html::html_to_markdown;
+use super::DumpStore;
+use crate::api::error::ApiError;
+use crate::api::stackexchange::Id;
+
+// Report the progress after this many rows.
+const PROGRESS_INTERVAL: usize = 10_000;
+
+const SCHEMA: &str = "
+    CREATE TABLE posts (
+        id INTEGER PRIMARY KEY,
+        post_type INTEGER NOT NULL,
+        parent_id INTEGER,
+        accepted_answer_id INTEGER,
+        creation_date INTEGER NOT NULL,
+        last_activity_date INTEGER NOT NULL,
+        closed_date INTEGER,
+        score INTEGER NOT NULL,
+        view_count INTEGER,
+        owner_user_id INTEGER,
+        owner_display_name TEXT,
+        title TEXT,
+        -- Surrounded by spaces, like ` python django `.
+        tags TEXT NOT NULL,
+        body TEXT NOT NULL
+    );
+    CREATE TABLE comments (
+        id INTEGER PRIMARY KEY,
+        post_id INTEGER NOT NULL,
+        score INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        creation_date INTEGER NOT NULL,
+        user_id INTEGER,
+        user_display_name TEXT
+    );
+    CREATE TABLE users (
+        id INTEGER PRIMARY KEY,
+        display_name TEXT NOT NULL,
+        reputation INTEGER NOT NULL,
+        creation_date INTEGER NOT NULL,
+        location TEXT,
+        website_url TEXT
+    );
+    CREATE TABLE tags (
+        name TEXT PRIMARY KEY,
+        count INTEGER NOT NULL
+    );
+    CREATE VIRTUAL TABLE questions_search USING fts5(title, body);
+";
+
+// Created after importing, since it's faster than updating them with every row.
+const INDEXES: &str = "
+    CREATE INDEX posts_parent ON posts(parent_id);
+    CREATE INDEX posts_owner ON posts(owner_user_id);
+    CREATE INDEX comments_post ON comments(post_id);
+    INSERT INTO questions_search(rowid, title, body)
+        SELECT id, title, body FROM posts WHERE post_type = 1;
+";
+
+/// XML files of a site's dump, in the order they are imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFile {
+    Tags,
+    Users,
+    Posts,
+    Comments,
+    /// Optional, it has the original markdown of posts, otherwise it's converted from HTML.
+    PostHistory,
+}
+
+impl DumpFile {
+    pub const ALL: [Self; 5] = [
+        Self::Tags,
+        Self::Users,
+        Self::Posts,
+        Self::Comments,
+        Self::PostHistory,
+    ];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Tags => "Tags.xml",
+            Self::Users => "Users.xml",
+            Self::Posts => "Posts.xml",
+            Self::Comments => "Comments.xml",
+            Self::PostHistory => "PostHistory.xml",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImportProgress {
+    pub file: DumpFile,
+    /// Part of the file that was read, from 0 to 1.
+    pub fraction: f64,
+}
+
+/// Import the extracted dump of a site from `dir`, replacing an older import of it.
+///
+/// Rows are streamed from the XML files, so dumps of large sites don't have to fit in memory.
+/// This blocks until the import is done.
+pub fn import_dump(
+    site: &str,
+    dir: &Path,
+    on_progress: impl FnMut(ImportProgress),
+) -> Result<(), ApiError> {
+    import_dump_to(&DumpStore::path(site), dir, on_progress)
+}
+
+fn import_dump_to(
+    store_path: &Path,
+    dir: &Path,
+    mut on_progress: impl FnMut(ImportProgress),
+) -> Result<(), ApiError> {
+    if !dir.join(DumpFile::Posts.file_name()).is_file() {
+        return Err(ApiError::Dump(format!(
+            "{} doesn't have {}",
+            dir.display(),
+            DumpFile::Posts.file_name()
+        )));
+    }
+
+    // Import next to the store, so it's not replaced until the import is complete.
+    let partial_path = store_path.with_extension("sqlite3.part");
+
+    if let Some(parent) = partial_path.parent() {
+        std::fs::create_dir_all(parent).map_err(dump_error)?;
+    }
+    if partial_path.exists() {
+        std::fs::remove_file(&partial_path).map_err(dump_error)?;
+    }
+
+    let mut connection = Connection::open(&partial_path)?;
+    // Nothing is lost when an interrupted import is started again.
+    connection.execute_batch("PRAGMA journal_mode = OFF; PRAGMA synchronous = OFF;")?;
+    connection.execute_batch(SCHEMA)?;
+
+    for file in DumpFile::ALL {
+        let path = dir.join(file.file_name());
+
+        // Only posts are required, the rest are used when they are there.
+        if !path.is_file() {
+            continue;
+        }
+
+        let transaction = connection.transaction()?;
+        import_file(&transaction, file, &path, &mut on_progress)?;
+        transaction.commit()?;
+    }
+
+    connection.execute_batch(INDEXES)?;
+    drop(connection);
+
+    std::fs::rename(partial_path, store_path).map_err(dump_error)
+}
+
+fn import_file(
+    connection: &Connection,
+    file: DumpFile,
+    path: &Path,
+    on_progress: &mut impl FnMut(ImportProgress),
+) -> Result<(), ApiError> {
+    let file_size = std::fs::metadata(path).map_err(dump_error)?.len().max(1) as f64;
+
+    let mut statement = connection.prepare(match file {
+        DumpFile::Tags => "INSERT OR REPLACE INTO tags VALUES (?1, ?2)",
+        DumpFile::Users => "INSERT OR REPLACE INTO users VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        DumpFile::Posts => {
+            "INSERT OR REPLACE INTO posts
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"
+        }
+        DumpFile::Comments => "INSERT OR REPLACE INTO comments VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        // Revisions are in chronological order, so the last one is the current body.
+        DumpFile::PostHistory => "UPDATE posts SET body = ?2 WHERE id = ?1",
+    })?;
+
+    let mut reader = Reader::from_reader(BufReader::new(File::open(path).map_err(dump_error)?));
+    let mut buffer = Vec::new();
+    let mut rows = 0;
+
+    loop {
+        let row = match reader.read_event_into(&mut buffer).map_err(dump_error)? {
+            Event::Empty(element) if element.name().as_ref() == b"row" => element
+                .attributes()
+                .map(|attribute| {
+                    let attribute = attribute.map_err(dump_error)?;
+                    Ok((
+                        String::from_utf8_lossy(attribute.key.as_ref()).into_owned(),
+                        attribute.unescape_value().map_err(dump_error)?.into_owned(),
+                    ))
+                })
+                .collect::<Result<Row, ApiError>>()?,
+            Event::Eof => break,
+            _ => {
+                buffer.clear();
+                continue;
+            }
+        };
+        buffer.clear();
+
+        match file {
+            DumpFile::Tags => {
+                statement.execute(params![
+                    row.text("TagName"),
+                    row.number("Count").unwrap_or_default()
+                ])?;
+            }
+            DumpFile::Users => {
+                statement.execute(params![
+                    row.id("Id"),
+                    row.text("DisplayName").unwrap_or_default(),
+                    row.number("Reputation").unwrap_or_default(),
+                    row.date("CreationDate").unwrap_or_default(),
+                    row.text("Location"),
+                    row.text("WebsiteUrl"),
+                ])?;
+            }
+            DumpFile::Posts => {
+                // Other types are tag wikis and such, they are never shown.
+                let post_type = row.number("PostTypeId");
+                if !matches!(post_type, Some(1 | 2)) {
+                    continue;
+                }
+
+                statement.execute(params![
+                    row.id("Id"),
+                    post_type,
+                    row.id("ParentId"),
+                    row.id("AcceptedAnswerId"),
+                    row.date("CreationDate").unwrap_or_default(),
+                    row.date("LastActivityDate")
+                        .or_else(|| row.date("CreationDate"))
+                        .unwrap_or_default(),
+                    row.date("ClosedDate"),
+                    row.number("Score").unwrap_or_default(),
+                    row.number("ViewCount"),
+                    row.id("OwnerUserId"),
+                    row.text("OwnerDisplayName"),
+                    row.text("Title"),
+                    normalize_tags(row.text("Tags").unwrap_or_default()),
+                    html_to_markdown(row.text("Body").unwrap_or_default()),
+                ])?;
+            }
+            DumpFile::Comments => {
+                statement.execute(params![
+                    row.id("Id"),
+                    row.id("PostId"),
+                    row.number("Score").unwrap_or_default(),
+                    row.text("Text").unwrap_or_default(),
+                    row.date("CreationDate").unwrap_or_default(),
+                    row.id("UserId"),
+                    row.text("UserDisplayName"),
+                ])?;
+            }
+            DumpFile::PostHistory => {
+                // Initial body, edited body and rolled back body.
+                if matches!(row.number("PostHistoryTypeId"), Some(2 | 5 | 8)) {
+                    if let Some(text) = row.text("Text") {
+                        statement.execute(params![row.id("PostId"), text])?;
+                    }
+                }
+            }
+        }
+
+        rows += 1;
+        if rows % PROGRESS_INTERVAL == 0 {
+            on_progress(ImportProgress {
+                file,
+                fraction: (reader.buffer_position() as f64 / file_size).min(1.0),
+            });
+        }
+    }
+
+    on_progress(ImportProgress {
+        file,
+        fraction: 1.0,
+    });
+
+    Ok(())
+}
+
+// Attributes of a `<row />` element.
+type Row = HashMap<String, String>;
+
+trait RowExt {
+    fn text(&self, key: &str) -> Option<&str>;
+    fn id(&self, key: &str) -> Option<Id>;
+    fn number(&self, key: &str) -> Option<i64>;
+    fn date(&self, key: &str) -> Option<i64>;
+}
+
+impl RowExt for Row {
+    fn text(&self, key: &str) -> Option<&str> {
+        self.get(key).map(String::as_str)
+    }
+
+    fn id(&self, key: &str) -> Option<Id> {
+        self.text(key)?.parse().ok()
+    }
+
+    fn number(&self, key: &str) -> Option<i64> {
+        self.text(key)?.parse().ok()
+    }
+
+    fn date(&self, key: &str) -> Option<i64> {
+        parse_date(self.text(key)?)
+    }
+}
+
+// Older dumps use `<python><django>`, newer ones use `|python|django|`.
+fn normalize_tags(tags: &str) -> String {
+    let tags = tags
+        .split(['<', '>', '|'])
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<&str>>();
+
+    format!(" {} ", tags.join(" "))
+}
+
+// Dates are in UTC, like `2008-07-31T21:42:52.667`.
+fn parse_date(date: &str) -> Option<i64> {
+    let (date, time) = date.split_once('T')?;
+
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+
+    let mut time = time
+        .split('.')
+        .next()?
+        .splitn(3, ':')
+        .map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || !(0..24).contains(&hour)
+        || !(0..60).contains(&minute)
+        || !(0..=60).contains(&second)
+    {
+        return None;
+    }
+
+    // Days since the unix epoch of a date in the proleptic Gregorian calendar.
+    // Ref: https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+fn dump_error(error: impl std::fmt::Display) -> ApiError {
+    ApiError::Dump(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import_dump_to, parse_date, DumpStore};
+    use crate::api::stackexchange::{SearchQuery, SearchSort};
+    use crate::test_utils::TestDir;
+
+    const USERS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<users>
+  <row Id="1" Reputation="101" CreationDate="2008-07-31T14:22:31.287" DisplayName="Alice" Location="Earth" />
+</users>"#;
+
+    // The owner of the first answer isn't in the users, and the tag wiki isn't a question.
+    const POSTS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<posts>
+  <row Id="10" PostTypeId="1" AcceptedAnswerId="11" CreationDate="2008-07-31T21:42:52.667" Score="5" ViewCount="40" Body="&lt;p&gt;Question &lt;em&gt;html&lt;/em&gt;&lt;/p&gt;" OwnerUserId="1" LastActivityDate="2008-08-01T10:00:00.000" Title="How to parse XML?" Tags="|rust|xml|" AnswerCount="2" CommentCount="2" />
+  <row Id="11" PostTypeId="2" ParentId="10" CreationDate="2008-08-01T09:00:00.000" Score="3" Body="&lt;p&gt;Answer html&lt;/p&gt;" OwnerUserId="2" LastActivityDate="2008-08-01T09:00:00.000" CommentCount="0" />
+  <row Id="12" PostTypeId="2" ParentId="10" CreationDate="2008-08-01T10:00:00.000" Score="1" Body="&lt;p&gt;Plain &lt;b&gt;HTML&lt;/b&gt; answer&lt;/p&gt;" OwnerDisplayName="Bob" LastActivityDate="2008-08-01T10:00:00.000" CommentCount="0" />
+  <row Id="13" PostTypeId="4" CreationDate="2008-08-01T10:00:00.000" Score="0" Body="&lt;p&gt;Tag wiki&lt;/p&gt;" LastActivityDate="2008-08-01T10:00:00.000" />
+  <row Id="14" PostTypeId="1" CreationDate="2008-08-02T10:00:00.000" Score="9" Body="&lt;p&gt;XML everywhere, all XML, only XML&lt;/p&gt;" LastActivityDate="2008-08-02T10:00:00.000" Title="Parse 100% of XML_files" Tags="|xml|" />
+</posts>"#;
+
+    const COMMENTS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<comments>
+  <row Id="101" PostId="10" Score="2" Text="Second comment" CreationDate="2008-08-01T12:00:00.000" UserDisplayName="Carol" />
+  <row Id="100" PostId="10" Score="0" Text="First comment" CreationDate="2008-08-01T11:00:00.000" UserId="1" />
+</comments>"#;
+
+    // Edits (5) and rollbacks (8) replace the initial body (2), title edits (4) are ignored.
+    const POST_HISTORY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<posthistory>
+  <row Id="1" PostHistoryTypeId="2" PostId="10" CreationDate="2008-07-31T21:42:52.667" Text="Initial **markdown**" />
+  <row Id="2" PostHistoryTypeId="5" PostId="10" CreationDate="2008-08-01T08:00:00.000" Text="Edited **markdown**" />
+  <row Id="3" PostHistoryTypeId="4" PostId="10" CreationDate="2008-08-01T08:00:00.000" Text="New title" />
+  <row Id="4" PostHistoryTypeId="2" PostId="11" CreationDate="2008-08-01T09:00:00.000" Text="Answer" />
+  <row Id="5" PostHistoryTypeId="5" PostId="11" CreationDate="2008-08-01T09:30:00.000" Text="Broken answer" />
+  <row Id="6" PostHistoryTypeId="8" PostId="11" CreationDate="2008-08-01T09:40:00.000" Text="Rolled back answer" />
+</posthistory>"#;
+
+    const TAGS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<tags>
+  <row Id="1" TagName="rust" Count="2" />
+</tags>"#;
+
+    fn import_fixture(dir: &TestDir) -> DumpStore {
+        let dump_dir = dir.0.join("dump");
+        std::fs::create_dir(&dump_dir).unwrap();
+
+        for (file_name, content) in [
+            ("Users.xml", USERS),
+            ("Posts.xml", POSTS),
+            ("Comments.xml", COMMENTS),
+            ("PostHistory.xml", POST_HISTORY),
+            ("Tags.xml", TAGS),
+        ] {
+            std::fs::write(dump_dir.join(file_name), content).unwrap();
+        }
+
+        let store_path = dir.0.join("site.sqlite3");
+        import_dump_to(&store_path, &dump_dir, |_| {}).unwrap();
+
+        DumpStore::open_path(&store_path).unwrap()
+    }
+
+    #[test]
+    fn imports_posts_with_their_latest_markdown() {
+        let dir = TestDir::new("import-posts");
+        let store = import_fixture(&dir);
+
+        // Missing questions are skipped, like deleted ones in the API.
+        let questions = store.get_questions(&[99, 10, 13]).unwrap();
+        assert_eq!(questions.len(), 1);
+
+        let question = &questions[0];
+        assert_eq!(question.question_id, 10);
+        assert_eq!(question.title, "How to parse XML?");
+        assert_eq!(question.body_markdown, "Edited **markdown**");
+        assert_eq!(question.tags, ["rust", "xml"]);
+        assert_eq!(question.score, 5);
+        assert_eq!(question.view_count, 40);
+        assert_eq!(question.creation_date, 1_217_540_572);
+        assert!(question.is_answered);
+        assert_eq!(question.owner.user_id, Some(1));
+        assert_eq!(question.owner.display_name, "Alice");
+        assert_eq!(question.owner.reputation, Some(101));
+
+        let answers = question.answers.as_deref().unwrap();
+        assert_eq!(question.answer_count, 2);
+        assert_eq!(
+            answers
+                .iter()
+                .map(|answer| (answer.answer_id, answer.body_markdown.as_str()))
+                .collect::<Vec<_>>(),
+            [(11, "Rolled back answer"), (12, "Plain **HTML** answer")]
+        );
+        assert!(answers[0].is_accepted);
+        assert!(!answers[1].is_accepted);
+
+        // Owners missing from the users keep their id, others keep their display name.
+        assert_eq!(answers[0].owner.user_id, Some(2));
+        assert_eq!(answers[0].owner.display_name, "");
+        assert_eq!(answers[0].owner.reputation, None);
+        assert_eq!(answers[1].owner.user_id, None);
+        assert_eq!(answers[1].owner.display_name, "Bob");
+        assert_eq!(answers[1].owner.reputation, None);
+    }
+
+    #[test]
+    fn imports_comments_users_and_tags() {
+        let dir = TestDir::new("import-others");
+        let store = import_fixture(&dir);
+
+        let comments = store.get_post_comments(10).unwrap();
+        assert_eq!(
+            comments
+                .iter()
+                .map(|comment| (
+                    comment.comment_id,
+                    comment.body_markdown.as_deref().unwrap_or_default(),
+                    comment.owner.display_name.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (100, "First comment", "Alice"),
+                (101, "Second comment", "Carol")
+            ]
+        );
+        assert!(store.get_post_comments(11).unwrap().is_empty());
+
+        let user = store.get_user(1).unwrap().unwrap();
+        assert_eq!(user.display_name, "Alice");
+        assert_eq!(user.reputation, 101);
+        assert_eq!(user.location.as_deref(), Some("Earth"));
+        assert!(store.get_user(2).unwrap().is_none());
+
+        assert_eq!(store.get_tag("Rust").unwrap().map(|tag| tag.count), Some(2));
+        assert_eq!(store.get_answer_question_id(12).unwrap(), Some(10));
+        assert_eq!(store.get_answer_question_id(13).unwrap(), None);
+    }
+
+    #[test]
+    fn searches_imported_questions() {
+        let dir = TestDir::new("import-search");
+        let store = import_fixture(&dir);
+
+        let search = |query: SearchQuery| {
+            store
+                .search_questions(&query, 1)
+                .unwrap()
+                .items
+                .iter()
+                .map(|question| question.question_id)
+                .collect::<Vec<_>>()
+        };
+
+        // More matches rank first, even with a lower score.
+        assert_eq!(
+            search(SearchQuery {
+                q: "xml".to_owned(),
+                ..Default::default()
+            }),
+            [14, 10]
+        );
+        assert_eq!(
+            search(SearchQuery {
+                q: "markdown".to_owned(),
+                sort: SearchSort::Votes,
+                ..Default::default()
+            }),
+            [10]
+        );
+
+        // Wildcards of `LIKE` are matched literally.
+        for (title, expected) in [("100%", vec![14]), ("%", vec![14]), ("_", vec![14])] {
+            assert_eq!(
+                search(SearchQuery {
+                    title: title.to_owned(),
+                    ..Default::default()
+                }),
+                expected,
+                "{title}"
+            );
+        }
+        assert!(search(SearchQuery {
+            tagged: vec!["x_l".to_owned()],
+            ..Default::default()
+        })
+        .is_empty());
+        assert_eq!(
+            search(SearchQuery {
+                tagged: vec!["XML".to_owned()],
+                sort: SearchSort::Votes,
+                ..Default::default()
+            }),
+            [14, 10]
+        );
+    }
+
+    #[test]
+    fn requires_posts() {
+        let dir = TestDir::new("import-missing");
+
+        assert!(import_dump_to(&dir.0.join("site.sqlite3"), &dir.0, |_| {}).is_err());
+        assert!(!dir.0.join("site.sqlite3").exists());
+    }
+
+    #[test]
+    fn parses_dump_dates() {
+        assert_eq!(parse_date("1970-01-01T00:00:00.000"), Some(0));
+        assert_eq!(parse_date("2008-07-31T21:42:52.667"), Some(1_217_540_572));
+        assert_eq!(parse_date("2024-02-29T23:59:59"), Some(1_709_251_199));
+    }
+
+    #[test]
+    fn rejects_malformed_dates() {
+        for date in [
+            "",
+            "2008-07-31",
+            "2008-07-31T21:42",
+            "2008-07T21:42:52",
+            "year-07-31T21:42:52",
+            "2008-13-31T21:42:52",
+            "2008-07-32T21:42:52",
+            "2008-07-31T24:00:00",
+        ] {
+            assert_eq!(parse_date(date), None, "{date}");
+        }
+    }
+}
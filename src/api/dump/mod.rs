@@ -0,0 +1,672 @@
+//! Local copies of sites imported from the official StackExchange data dumps.
+//!
+//! Docs: https://meta.stackexchange.com/questions/2677
+
+mod html;
+pub mod import;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension, Row};
+
+use super::backend::{Backend, QaBackend};
+use super::error::ApiError;
+use super::offline::{OfflinePack, PackProgress};
+use super::scheduler::Quota;
+use super::sites::SiteCatalog;
+use super::stackexchange::{
+    Answer, BadgeCounts, Comment, Id, Page, Question, QuestionSummary, SearchQuery, SearchSort,
+    Site, Tag, User, UserProfile,
+};
+use import::ImportProgress;
+
+const SEARCH_PAGESIZE: usize = 30;
+
+// Columns of `OWNER_JOIN`, read with `owner_from_row`.
+const OWNER_COLUMNS: &str =
+    "owner_user_id, coalesce(users.display_name, owner_display_name, ''), users.reputation";
+const OWNER_JOIN: &str = "LEFT JOIN users ON users.id = owner_user_id";
+
+// Same meaning as `is_answered` of the API.
+const IS_ANSWERED: &str = "(posts.accepted_answer_id IS NOT NULL OR EXISTS (
+    SELECT 1 FROM posts AS answers WHERE answers.parent_id = posts.id AND answers.score > 0
+))";
+
+/// Imported dumps of every site, each one is opened when it's first used.
+#[derive(Default)]
+pub struct DumpLibrary {
+    // `None` for sites that were never imported.
+    stores: Mutex<HashMap<String, Option<Arc<DumpStore>>>>,
+}
+
+impl DumpLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Imported dump of a site, `None` when it was never imported.
+    pub fn store(&self, site: &str) -> Option<Arc<DumpStore>> {
+        self.stores
+            .lock()
+            .unwrap()
+            .entry(site.to_owned())
+            .or_insert_with(|| DumpStore::open(site).map(Arc::new))
+            .clone()
+    }
+
+    /// Import the data dump of a site extracted in `dir`, see `import::import_dump`.
+    pub fn import(
+        &self,
+        site: &str,
+        dir: &Path,
+        on_progress: impl FnMut(ImportProgress),
+    ) -> Result<(), ApiError> {
+        let result = import::import_dump(site, dir, on_progress);
+
+        // Open the new import next time, or an older one that was kept after a failure.
+        self.stores.lock().unwrap().remove(site);
+
+        result
+    }
+
+    fn imported_store(&self, site: &str) -> Result<Arc<DumpStore>, ApiError> {
+        self.store(site)
+            .ok_or_else(|| ApiError::Dump(format!("{site} wasn't imported")))
+    }
+}
+
+#[async_trait]
+impl QaBackend for DumpLibrary {
+    async fn get_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+        let ids = ids.to_vec();
+        self.imported_store(site)?
+            .run(move |dump| dump.get_questions(&ids))
+            .await
+    }
+
+    // Questions of dumps are always complete.
+    async fn complete_question(
+        &self,
+        _site: &str,
+        question: Question,
+    ) -> Result<Question, ApiError> {
+        Ok(question)
+    }
+
+    async fn search_questions(
+        &self,
+        site: &str,
+        query: &SearchQuery,
+        page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError> {
+        let query = query.clone();
+        self.imported_store(site)?
+            .run(move |dump| dump.search_questions(&query, page))
+            .await
+    }
+
+    async fn get_post_comments(&self, site: &str, post_id: Id) -> Result<Vec<Comment>, ApiError> {
+        self.imported_store(site)?
+            .run(move |dump| dump.get_post_comments(post_id))
+            .await
+    }
+
+    async fn get_user(&self, site: &str, user_id: Id) -> Result<Option<UserProfile>, ApiError> {
+        self.imported_store(site)?
+            .run(move |dump| dump.get_user(user_id))
+            .await
+    }
+
+    async fn get_answer_question_id(
+        &self,
+        site: &str,
+        answer_id: Id,
+    ) -> Result<Option<Id>, ApiError> {
+        self.imported_store(site)?
+            .run(move |dump| dump.get_answer_question_id(answer_id))
+            .await
+    }
+
+    async fn get_tag(&self, site: &str, name: &str) -> Result<Option<Tag>, ApiError> {
+        let name = name.to_owned();
+        self.imported_store(site)?
+            .run(move |dump| dump.get_tag(&name))
+            .await
+    }
+
+    /// Imported sites, described by the local catalog when it knows them.
+    async fn get_sites(&self) -> Result<Vec<Site>, ApiError> {
+        let catalog = SiteCatalog::load_local();
+
+        let entries = match std::fs::read_dir(crate::utils::data_dir().join("dumps")) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        Ok(entries
+            .filter_map(|entry| {
+                let file_name = entry.ok()?.file_name();
+                let site = file_name.to_str()?.strip_suffix(".sqlite3")?;
+
+                Some(
+                    catalog
+                        .sites()
+                        .iter()
+                        .find(|known| known.api_site_parameter == site)
+                        .cloned()
+                        .unwrap_or_else(|| Site {
+                            api_site_parameter: site.to_owned(),
+                            audience: String::new(),
+                            icon_url: String::new(),
+                            name: site.to_owned(),
+                            site_type: "main_site".to_owned(),
+                            site_url: String::new(),
+                        }),
+                )
+            })
+            .collect())
+    }
+
+    fn import_dump(
+        &self,
+        site: &str,
+        dir: &Path,
+        on_progress: &mut dyn FnMut(ImportProgress),
+    ) -> Result<(), ApiError> {
+        self.import(site, dir, on_progress)
+    }
+}
+
+/// Serves the sites with an imported dump from it, and every other site from another backend.
+pub struct DumpRouter {
+    dumps: DumpLibrary,
+    inner: Backend,
+}
+
+impl DumpRouter {
+    pub fn new(inner: Backend) -> Self {
+        Self {
+            dumps: DumpLibrary::new(),
+            inner,
+        }
+    }
+}
+
+#[async_trait]
+impl QaBackend for DumpRouter {
+    async fn get_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+        match self.dumps.store(site) {
+            Some(dump) => {
+                let ids = ids.to_vec();
+                dump.run(move |dump| dump.get_questions(&ids)).await
+            }
+            None => self.inner.get_questions(site, ids).await,
+        }
+    }
+
+    async fn complete_question(
+        &self,
+        site: &str,
+        question: Question,
+    ) -> Result<Question, ApiError> {
+        match self.dumps.store(site) {
+            Some(_) => Ok(question),
+            None => self.inner.complete_question(site, question).await,
+        }
+    }
+
+    async fn search_questions(
+        &self,
+        site: &str,
+        query: &SearchQuery,
+        page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError> {
+        match self.dumps.store(site) {
+            Some(dump) => {
+                let query = query.clone();
+                dump.run(move |dump| dump.search_questions(&query, page))
+                    .await
+            }
+            None => self.inner.search_questions(site, query, page).await,
+        }
+    }
+
+    async fn get_post_comments(&self, site: &str, post_id: Id) -> Result<Vec<Comment>, ApiError> {
+        match self.dumps.store(site) {
+            Some(dump) => dump.run(move |dump| dump.get_post_comments(post_id)).await,
+            None => self.inner.get_post_comments(site, post_id).await,
+        }
+    }
+
+    async fn get_user(&self, site: &str, user_id: Id) -> Result<Option<UserProfile>, ApiError> {
+        match self.dumps.store(site) {
+            Some(dump) => dump.run(move |dump| dump.get_user(user_id)).await,
+            None => self.inner.get_user(site, user_id).await,
+        }
+    }
+
+    async fn get_answer_question_id(
+        &self,
+        site: &str,
+        answer_id: Id,
+    ) -> Result<Option<Id>, ApiError> {
+        match self.dumps.store(site) {
+            Some(dump) => {
+                dump.run(move |dump| dump.get_answer_question_id(answer_id))
+                    .await
+            }
+            None => self.inner.get_answer_question_id(site, answer_id).await,
+        }
+    }
+
+    async fn get_tag(&self, site: &str, name: &str) -> Result<Option<Tag>, ApiError> {
+        match self.dumps.store(site) {
+            Some(dump) => {
+                let name = name.to_owned();
+                dump.run(move |dump| dump.get_tag(&name)).await
+            }
+            None => self.inner.get_tag(site, name).await,
+        }
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>, ApiError> {
+        self.inner.get_sites().await
+    }
+
+    async fn get_image(&self, url: &str) -> Result<Vec<u8>, ApiError> {
+        self.inner.get_image(url).await
+    }
+
+    fn subscribe_quota(&self) -> Option<tokio::sync::watch::Receiver<Option<Quota>>> {
+        self.inner.subscribe_quota()
+    }
+
+    fn lists_every_site(&self) -> bool {
+        self.inner.lists_every_site()
+    }
+
+    async fn list_tag_questions(
+        &self,
+        site: &str,
+        tag: &str,
+        limit: u32,
+    ) -> Result<Vec<Id>, ApiError> {
+        self.inner.list_tag_questions(site, tag, limit).await
+    }
+
+    async fn download_pack(
+        &self,
+        pack: &mut OfflinePack,
+        on_progress: &mut (dyn FnMut(PackProgress) + Send),
+    ) -> Result<(), ApiError> {
+        self.inner.download_pack(pack, on_progress).await
+    }
+
+    /// Import the data dump of a site extracted in `dir`, it's used instead of `inner` afterwards.
+    fn import_dump(
+        &self,
+        site: &str,
+        dir: &Path,
+        on_progress: &mut dyn FnMut(ImportProgress),
+    ) -> Result<(), ApiError> {
+        self.dumps.import(site, dir, on_progress)
+    }
+}
+
+/// Questions, answers, comments and users of a single site, indexed for lookups and search.
+pub struct DumpStore {
+    connection: Mutex<Connection>,
+}
+
+impl DumpStore {
+    /// Open the imported dump of a site, `None` when it was never imported.
+    pub fn open(site: &str) -> Option<Self> {
+        Self::open_path(&Self::path(site))
+    }
+
+    fn open_path(path: &Path) -> Option<Self> {
+        if !path.is_file() {
+            return None;
+        }
+
+        Connection::open(path).ok().map(|connection| Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    pub fn path(site: &str) -> PathBuf {
+        crate::utils::data_dir()
+            .join("dumps")
+            .join(format!("{site}.sqlite3"))
+    }
+
+    /// Run queries on a thread where blocking is fine, SQLite would hold up the async runtime.
+    pub async fn run<T: Send + 'static>(
+        self: Arc<Self>,
+        query: impl FnOnce(&Self) -> Result<T, ApiError> + Send + 'static,
+    ) -> Result<T, ApiError> {
+        tokio::task::spawn_blocking(move || query(&self))
+            .await
+            .map_err(|error| ApiError::Dump(error.to_string()))?
+    }
+
+    /// Questions with every answer and comment, in the same order of `ids`.
+    pub fn get_questions(&self, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+        let connection = self.connection.lock().unwrap();
+        let mut questions = Vec::with_capacity(ids.len());
+
+        let mut question_statement = connection.prepare_cached(&format!(
+            "SELECT posts.id, title, tags, body, score, view_count, posts.creation_date,
+                last_activity_date, {IS_ANSWERED}, {OWNER_COLUMNS}
+            FROM posts {OWNER_JOIN} WHERE posts.id = ?1 AND post_type = 1"
+        ))?;
+        let mut answers_statement = connection.prepare_cached(&format!(
+            "SELECT posts.id, body, score, posts.creation_date, last_activity_date,
+                posts.id = (SELECT accepted_answer_id FROM posts AS question WHERE question.id = ?1),
+                {OWNER_COLUMNS}
+            FROM posts {OWNER_JOIN} WHERE parent_id = ?1 AND post_type = 2
+            ORDER BY score DESC"
+        ))?;
+
+        for id in ids {
+            let question = question_statement
+                .query_row(params![id], |row| {
+                    Ok(Question {
+                        question_id: row.get(0)?,
+                        title: row.get(1)?,
+                        tags: split_tags(&row.get::<_, String>(2)?),
+                        body_markdown: row.get(3)?,
+                        score: row.get(4)?,
+                        view_count: row.get::<_, Option<u32>>(5)?.unwrap_or_default(),
+                        creation_date: row.get(6)?,
+                        last_activity_date: row.get(7)?,
+                        is_answered: row.get(8)?,
+                        owner: owner_from_row(row, 9)?,
+                        answer_count: 0,
+                        answers: None,
+                        comment_count: 0,
+                        comments: None,
+                        cached_at: None,
+                    })
+                })
+                .optional()?;
+
+            // Questions missing from the dump were deleted, like in the API.
+            let Some(mut question) = question else {
+                continue;
+            };
+
+            let answers = answers_statement
+                .query_map(params![id], |row| {
+                    Ok(Answer {
+                        answer_id: row.get(0)?,
+                        body_markdown: row.get(1)?,
+                        score: row.get(2)?,
+                        creation_date: row.get(3)?,
+                        last_activity_date: row.get(4)?,
+                        is_accepted: row.get(5)?,
+                        owner: owner_from_row(row, 6)?,
+                        comment_count: 0,
+                        comments: None,
+                    })
+                })?
+                .collect::<Result<Vec<Answer>, _>>()?;
+
+            // Counts of the dump include deleted posts, so count what is actually there.
+            question.answers = Some(
+                answers
+                    .into_iter()
+                    .map(|mut answer| {
+                        let comments = post_comments(&connection, answer.answer_id)?;
+                        answer.comment_count = comments.len() as u32;
+                        answer.comments = Some(comments);
+                        Ok(answer)
+                    })
+                    .collect::<Result<Vec<Answer>, ApiError>>()?,
+            );
+            question.answer_count = question.answers.as_ref().map_or(0, Vec::len) as u32;
+
+            let comments = post_comments(&connection, question.question_id)?;
+            question.comment_count = comments.len() as u32;
+            question.comments = Some(comments);
+
+            questions.push(question);
+        }
+
+        Ok(questions)
+    }
+
+    /// Search questions like the advanced search API, free text is matched by relevance.
+    pub fn search_questions(
+        &self,
+        query: &SearchQuery,
+        page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut joins = OWNER_JOIN.to_owned();
+        let mut conditions = vec!["post_type = 1".to_owned()];
+        let mut values = Vec::new();
+
+        let full_text = full_text_query(&query.q);
+        if let Some(full_text) = &full_text {
+            joins.push_str(" JOIN questions_search ON questions_search.rowid = posts.id");
+            conditions.push("questions_search MATCH ?".to_owned());
+            values.push(Value::Text(full_text.clone()));
+        }
+        for (column, text) in [("posts.title", &query.title), ("posts.body", &query.body)] {
+            if !text.is_empty() {
+                conditions.push(format!("{column} LIKE ? ESCAPE '\\'"));
+                values.push(Value::Text(format!("%{}%", escape_like(text))));
+            }
+        }
+        for tag in &query.tagged {
+            // Tags are stored surrounded by spaces, so a tag doesn't match a part of another.
+            conditions.push("tags LIKE ? ESCAPE '\\'".to_owned());
+            values.push(Value::Text(format!(
+                "% {} %",
+                escape_like(&tag.to_lowercase())
+            )));
+        }
+        if let Some(accepted) = query.accepted {
+            conditions.push(format!(
+                "accepted_answer_id IS {} NULL",
+                if accepted { "NOT" } else { "" }
+            ));
+        }
+        if let Some(answers) = query.answers {
+            conditions.push(
+                "(SELECT count(*) FROM posts AS answers WHERE answers.parent_id = posts.id) >= ?"
+                    .to_owned(),
+            );
+            values.push(Value::Integer(answers.into()));
+        }
+        if let Some(closed) = query.closed {
+            conditions.push(format!(
+                "closed_date IS {} NULL",
+                if closed { "NOT" } else { "" }
+            ));
+        }
+        if let Some(user) = query.user {
+            conditions.push("owner_user_id = ?".to_owned());
+            values.push(Value::Integer(user.into()));
+        }
+
+        let order = match query.sort {
+            SearchSort::Activity => "last_activity_date DESC",
+            SearchSort::Creation => "posts.creation_date DESC",
+            SearchSort::Votes => "score DESC",
+            SearchSort::Relevance if full_text.is_some() => "questions_search.rank",
+            SearchSort::Relevance => "score DESC",
+        };
+
+        // One more than a page, to know if there are more of them.
+        values.push(Value::Integer(SEARCH_PAGESIZE as i64 + 1));
+        values.push(Value::Integer(
+            (page.saturating_sub(1) as usize * SEARCH_PAGESIZE) as i64,
+        ));
+
+        let mut statement = connection.prepare(&format!(
+            "SELECT posts.id, posts.title, tags, score, view_count, posts.creation_date,
+                last_activity_date,
+                (SELECT count(*) FROM posts AS answers WHERE answers.parent_id = posts.id),
+                {IS_ANSWERED}, {OWNER_COLUMNS}
+            FROM posts {joins}
+            WHERE {}
+            ORDER BY {order}
+            LIMIT ? OFFSET ?",
+            conditions.join(" AND ")
+        ))?;
+
+        let mut items = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok(QuestionSummary {
+                    question_id: row.get(0)?,
+                    title: row.get(1)?,
+                    tags: split_tags(&row.get::<_, String>(2)?),
+                    score: row.get(3)?,
+                    view_count: row.get::<_, Option<u32>>(4)?.unwrap_or_default(),
+                    creation_date: row.get(5)?,
+                    last_activity_date: row.get(6)?,
+                    answer_count: row.get(7)?,
+                    is_answered: row.get(8)?,
+                    owner: owner_from_row(row, 9)?,
+                })
+            })?
+            .collect::<Result<Vec<QuestionSummary>, _>>()?;
+
+        let has_more = items.len() > SEARCH_PAGESIZE;
+        items.truncate(SEARCH_PAGESIZE);
+
+        Ok(Page {
+            items,
+            has_more,
+            backoff: None,
+            quota_max: None,
+            quota_remaining: None,
+        })
+    }
+
+    /// Comments of a post (question or answer), from oldest to newest.
+    pub fn get_post_comments(&self, post_id: Id) -> Result<Vec<Comment>, ApiError> {
+        post_comments(&self.connection.lock().unwrap(), post_id)
+    }
+
+    /// Get a user profile, `None` when the user isn't in the dump.
+    pub fn get_user(&self, user_id: Id) -> Result<Option<UserProfile>, ApiError> {
+        let connection = self.connection.lock().unwrap();
+
+        Ok(connection
+            .query_row(
+                "SELECT id, display_name, reputation, creation_date, location, website_url
+                FROM users WHERE id = ?1",
+                params![user_id],
+                |row| {
+                    Ok(UserProfile {
+                        user_id: row.get(0)?,
+                        display_name: row.get(1)?,
+                        reputation: row.get(2)?,
+                        creation_date: row.get(3)?,
+                        location: row.get(4)?,
+                        website_url: row.get(5)?,
+                        // Badges are not imported.
+                        badge_counts: BadgeCounts {
+                            bronze: 0,
+                            silver: 0,
+                            gold: 0,
+                        },
+                        link: String::new(),
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    pub fn get_tag(&self, name: &str) -> Result<Option<Tag>, ApiError> {
+        let connection = self.connection.lock().unwrap();
+
+        Ok(connection
+            .query_row(
+                "SELECT name, count FROM tags WHERE name = ?1",
+                params![name.to_lowercase()],
+                |row| {
+                    Ok(Tag {
+                        name: row.get(0)?,
+                        count: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Get the id of the question an answer belongs to, `None` when the answer doesn't exist.
+    pub fn get_answer_question_id(&self, answer_id: Id) -> Result<Option<Id>, ApiError> {
+        let connection = self.connection.lock().unwrap();
+
+        Ok(connection
+            .query_row(
+                "SELECT parent_id FROM posts WHERE id = ?1 AND post_type = 2",
+                params![answer_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+}
+
+fn post_comments(connection: &Connection, post_id: Id) -> Result<Vec<Comment>, ApiError> {
+    let mut statement = connection.prepare_cached(
+        "SELECT comments.id, post_id, text, score, comments.creation_date,
+            user_id, coalesce(users.display_name, user_display_name, ''), users.reputation
+        FROM comments LEFT JOIN users ON users.id = user_id
+        WHERE post_id = ?1 ORDER BY comments.creation_date",
+    )?;
+
+    let comments = statement
+        .query_map(params![post_id], |row| {
+            Ok(Comment {
+                comment_id: row.get(0)?,
+                post_id: row.get(1)?,
+                body_markdown: row.get(2)?,
+                score: row.get(3)?,
+                creation_date: row.get(4)?,
+                owner: owner_from_row(row, 5)?,
+            })
+        })?
+        .collect::<Result<Vec<Comment>, _>>()?;
+
+    Ok(comments)
+}
+
+// Read the user id, display name and reputation columns starting from `start`.
+fn owner_from_row(row: &Row, start: usize) -> rusqlite::Result<User> {
+    Ok(User {
+        user_id: row.get(start)?,
+        display_name: row.get(start + 1)?,
+        reputation: row.get(start + 2)?,
+        link: None,
+    })
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split_whitespace().map(str::to_owned).collect()
+}
+
+// Match the text literally in `LIKE` patterns escaped with `\`.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Match every word, quoted so the query syntax of FTS5 doesn't apply to user input.
+fn full_text_query(text: &str) -> Option<String> {
+    let words = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<String>>();
+
+    (!words.is_empty()).then(|| words.join(" "))
+}
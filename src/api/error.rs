@@ -0,0 +1,196 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Error names reported by the API in `error_name`.
+///
+/// Docs: https://api.stackexchange.com/docs/error-handling
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiErrorName {
+    BadParameter,
+    AccessTokenRequired,
+    InvalidAccessToken,
+    AccessDenied,
+    NoMethod,
+    KeyRequired,
+    AccessTokenCompromised,
+    WriteFailed,
+    DuplicateRequest,
+    InternalError,
+    ThrottleViolation,
+    TemporarilyUnavailable,
+    Other(String),
+}
+
+impl From<&str> for ApiErrorName {
+    fn from(error_name: &str) -> Self {
+        match error_name {
+            "bad_parameter" => Self::BadParameter,
+            "access_token_required" => Self::AccessTokenRequired,
+            "invalid_access_token" => Self::InvalidAccessToken,
+            "access_denied" => Self::AccessDenied,
+            "no_method" => Self::NoMethod,
+            "key_required" => Self::KeyRequired,
+            "access_token_compromised" => Self::AccessTokenCompromised,
+            "write_failed" => Self::WriteFailed,
+            "duplicate_request" => Self::DuplicateRequest,
+            "internal_error" => Self::InternalError,
+            "throttle_violation" => Self::ThrottleViolation,
+            "temporarily_unavailable" => Self::TemporarilyUnavailable,
+            other => Self::Other(other.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for ApiErrorName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BadParameter => "bad_parameter",
+            Self::AccessTokenRequired => "access_token_required",
+            Self::InvalidAccessToken => "invalid_access_token",
+            Self::AccessDenied => "access_denied",
+            Self::NoMethod => "no_method",
+            Self::KeyRequired => "key_required",
+            Self::AccessTokenCompromised => "access_token_compromised",
+            Self::WriteFailed => "write_failed",
+            Self::DuplicateRequest => "duplicate_request",
+            Self::InternalError => "internal_error",
+            Self::ThrottleViolation => "throttle_violation",
+            Self::TemporarilyUnavailable => "temporarily_unavailable",
+            Self::Other(name) => name,
+        })
+    }
+}
+
+/// Why a uri can't be opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UriError {
+    /// Not a uri at all.
+    Malformed,
+    UnsupportedScheme(String),
+    UnknownSite(String),
+    /// Path doesn't match any route.
+    UnknownRoute(String),
+    InvalidId(String),
+    MissingParameter(&'static str),
+}
+
+impl fmt::Display for UriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed URI"),
+            Self::UnsupportedScheme(scheme) => write!(f, "unsupported scheme `{scheme}`"),
+            Self::UnknownSite(site) => write!(f, "unknown site `{site}`"),
+            Self::UnknownRoute(path) => write!(f, "unknown route `{path}`"),
+            Self::InvalidId(id) => write!(f, "invalid id `{id}`"),
+            Self::MissingParameter(parameter) => write!(f, "missing parameter `{parameter}`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    /// Request couldn't be sent or its response couldn't be received (offline, timeout, ...).
+    Transport(String),
+    /// Non-success HTTP status without an API error in the body.
+    HttpStatus(u16),
+    /// Error reported by the API itself.
+    Api {
+        id: u32,
+        name: ApiErrorName,
+        message: String,
+    },
+    /// Response doesn't have the expected structure.
+    Decode(String),
+    /// Uri can't be mapped to an API request.
+    InvalidUri { uri: String, reason: UriError },
+    /// Daily requests quota is used up, no requests are sent until it's reset.
+    QuotaExhausted,
+    /// A backoff asked by the API is too long to wait for, so the request isn't sent.
+    Throttled { retry_after: Duration },
+    /// Imported data dump couldn't be read or written.
+    Dump(String),
+    /// Operation isn't available with the selected backend.
+    Unsupported(&'static str),
+    /// Network settings are invalid, so no requests are sent.
+    Config(String),
+}
+
+impl ApiError {
+    pub fn invalid_uri(uri: &str, reason: UriError) -> Self {
+        Self::InvalidUri {
+            uri: uri.to_owned(),
+            reason,
+        }
+    }
+
+    /// Whether the API couldn't be reached at all, so cached responses could be used instead.
+    pub fn is_offline(&self) -> bool {
+        matches!(self, Self::Transport(_))
+    }
+
+    /// Whether the same request might succeed if it's sent again later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Transport(_) => true,
+            Self::HttpStatus(status) => *status >= 500 || *status == 429,
+            Self::Api { name, .. } => matches!(
+                name,
+                ApiErrorName::InternalError
+                    | ApiErrorName::ThrottleViolation
+                    | ApiErrorName::TemporarilyUnavailable
+            ),
+            Self::Decode(_)
+            | Self::InvalidUri { .. }
+            | Self::QuotaExhausted
+            | Self::Throttled { .. }
+            | Self::Dump(_)
+            | Self::Unsupported(_)
+            | Self::Config(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(error) => write!(f, "transport error: {error}"),
+            Self::HttpStatus(status) => write!(f, "unexpected HTTP status {status}"),
+            Self::Api { id, name, message } => write!(f, "API error {id} ({name}): {message}"),
+            Self::Decode(error) => write!(f, "failed to decode response: {error}"),
+            Self::InvalidUri { uri, reason } => write!(f, "invalid URI {uri}: {reason}"),
+            Self::QuotaExhausted => write!(f, "daily requests quota is exhausted"),
+            Self::Throttled { retry_after } => write!(
+                f,
+                "throttled, more requests available in {} seconds",
+                retry_after.as_secs()
+            ),
+            Self::Dump(error) => write!(f, "data dump error: {error}"),
+            Self::Unsupported(operation) => write!(f, "{operation} are not supported"),
+            Self::Config(error) => write!(f, "invalid network settings: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_decode() {
+            Self::Decode(error.to_string())
+        } else {
+            Self::Transport(error.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Decode(error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Dump(error.to_string())
+    }
+}
@@ -0,0 +1,473 @@
+use std::hash::Hasher;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use fnv::FnvHasher;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json as json;
+
+use super::backend::{Backend, QaBackend};
+use super::error::ApiError;
+use super::scheduler::Quota;
+use super::stackexchange::{
+    Comment, Id, Page, Question, QuestionSummary, SearchQuery, Site, Tag, UserProfile,
+};
+
+// Longest readable part of fixture names, file names are limited to 255 bytes on most file
+// systems and long ones get a hash and the extension appended.
+const MAX_NAME_LEN: usize = 200;
+
+/// Responses stored as JSON files, for running the app with known data and without network.
+///
+/// Every response has its own file, named after the method, site and every parameter, so fixtures
+/// can be edited by hand. Responses are recorded from another backend while they are used.
+pub struct FixtureBackend {
+    dir: PathBuf,
+    // Backend to record responses from, only fixtures are used when it's `None`.
+    recording: Option<Backend>,
+}
+
+impl FixtureBackend {
+    /// Serve responses recorded in `dir`, requests without a recorded response fail.
+    pub fn replay(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            recording: None,
+        }
+    }
+
+    /// Forward requests to `inner`, recording its successful responses in `dir`.
+    pub fn record(dir: PathBuf, inner: Backend) -> Self {
+        Self {
+            dir,
+            recording: Some(inner),
+        }
+    }
+
+    // Parameters are escaped then separated with `-`, so different ones never share a file.
+    fn fixture_file(&self, method: &str, site: &str, params: &[String]) -> PathBuf {
+        let name = std::iter::once(site)
+            .chain(params.iter().map(String::as_str))
+            .map(file_key)
+            .collect::<Vec<String>>()
+            .join("-");
+
+        // The hash of the whole name keeps truncated names apart.
+        let name = if name.len() > MAX_NAME_LEN {
+            let mut hasher = FnvHasher::default();
+            hasher.write(name.as_bytes());
+            format!("{}-{:016x}", &name[..MAX_NAME_LEN], hasher.finish())
+        } else {
+            name
+        };
+
+        self.dir.join(method).join(format!("{name}.json"))
+    }
+
+    fn save<T: Serialize>(
+        &self,
+        path: PathBuf,
+        result: Result<T, ApiError>,
+    ) -> Result<T, ApiError> {
+        if let Ok(value) = &result {
+            // Failing to record is not fatal, the response is still used.
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            if let Ok(content) = json::to_vec_pretty(value) {
+                let _ = std::fs::write(path, content);
+            }
+        }
+
+        result
+    }
+
+    fn load<T: DeserializeOwned>(&self, path: PathBuf) -> Result<T, ApiError> {
+        // Missing fixtures are like unreachable servers, so cached responses could be used.
+        let content = std::fs::read(&path).map_err(|error| {
+            ApiError::Transport(format!("no fixture at {}: {error}", path.display()))
+        })?;
+
+        Ok(json::from_slice(&content)?)
+    }
+}
+
+#[async_trait]
+impl QaBackend for FixtureBackend {
+    async fn get_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+        let params = ids.iter().map(|id| id.to_string()).collect::<Vec<String>>();
+        let path = self.fixture_file("questions", site, &params);
+
+        match &self.recording {
+            Some(inner) => self.save(path, inner.get_questions(site, ids).await),
+            None => self.load(path),
+        }
+    }
+
+    async fn complete_question(
+        &self,
+        site: &str,
+        question: Question,
+    ) -> Result<Question, ApiError> {
+        if question.is_complete() {
+            return Ok(question);
+        }
+
+        let path = self.fixture_file(
+            "complete_question",
+            site,
+            &[question.question_id.to_string()],
+        );
+
+        match &self.recording {
+            Some(inner) => self.save(path, inner.complete_question(site, question).await),
+            None => self.load(path),
+        }
+    }
+
+    async fn search_questions(
+        &self,
+        site: &str,
+        query: &SearchQuery,
+        page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError> {
+        let option = |value: Option<String>| value.unwrap_or_default();
+        let path = self.fixture_file(
+            "search",
+            site,
+            &[
+                page.to_string(),
+                query.q.clone(),
+                query.title.clone(),
+                query.body.clone(),
+                query.tagged.join(" "),
+                option(query.accepted.map(|accepted| accepted.to_string())),
+                option(query.answers.map(|answers| answers.to_string())),
+                option(query.closed.map(|closed| closed.to_string())),
+                option(query.user.map(|user| user.to_string())),
+                query.sort.as_str().to_owned(),
+            ],
+        );
+
+        match &self.recording {
+            Some(inner) => self.save(path, inner.search_questions(site, query, page).await),
+            None => self.load(path),
+        }
+    }
+
+    async fn get_post_comments(&self, site: &str, post_id: Id) -> Result<Vec<Comment>, ApiError> {
+        let path = self.fixture_file("comments", site, &[post_id.to_string()]);
+
+        match &self.recording {
+            Some(inner) => self.save(path, inner.get_post_comments(site, post_id).await),
+            None => self.load(path),
+        }
+    }
+
+    async fn get_user(&self, site: &str, user_id: Id) -> Result<Option<UserProfile>, ApiError> {
+        let path = self.fixture_file("users", site, &[user_id.to_string()]);
+
+        match &self.recording {
+            Some(inner) => self.save(path, inner.get_user(site, user_id).await),
+            None => self.load(path),
+        }
+    }
+
+    async fn get_answer_question_id(
+        &self,
+        site: &str,
+        answer_id: Id,
+    ) -> Result<Option<Id>, ApiError> {
+        let path = self.fixture_file("answers", site, &[answer_id.to_string()]);
+
+        match &self.recording {
+            Some(inner) => self.save(path, inner.get_answer_question_id(site, answer_id).await),
+            None => self.load(path),
+        }
+    }
+
+    async fn get_tag(&self, site: &str, name: &str) -> Result<Option<Tag>, ApiError> {
+        let path = self.fixture_file("tags", site, &[name.to_owned()]);
+
+        match &self.recording {
+            Some(inner) => self.save(path, inner.get_tag(site, name).await),
+            None => self.load(path),
+        }
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>, ApiError> {
+        let path = self.dir.join("sites.json");
+
+        match &self.recording {
+            Some(inner) => self.save(path, inner.get_sites().await),
+            None => self.load(path),
+        }
+    }
+
+    // Images are not JSON, so they are not recorded.
+    async fn get_image(&self, url: &str) -> Result<Vec<u8>, ApiError> {
+        match &self.recording {
+            Some(inner) => inner.get_image(url).await,
+            None => Err(ApiError::Unsupported("images")),
+        }
+    }
+
+    fn subscribe_quota(&self) -> Option<tokio::sync::watch::Receiver<Option<Quota>>> {
+        self.recording.as_ref()?.subscribe_quota()
+    }
+
+    fn lists_every_site(&self) -> bool {
+        self.recording
+            .as_ref()
+            .is_some_and(|inner| inner.lists_every_site())
+    }
+}
+
+// Queries and tags might have any character, keep ASCII letters and digits and escape the rest as
+// `_` followed by the hex of their UTF-8 bytes.
+fn file_key(text: &str) -> String {
+    text.bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() {
+                char::from(byte).to_string()
+            } else {
+                format!("_{byte:02x}")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use serde_json as json;
+
+    use super::FixtureBackend;
+    use crate::api::backend::{Backend, QaBackend};
+    use crate::api::error::ApiError;
+    use crate::api::stackexchange::{
+        Comment, Id, Page, Question, QuestionSummary, SearchQuery, Site, Tag, UserProfile,
+    };
+    use crate::test_utils::{block_on, question, TestDir};
+
+    // Answers with the parameters it got, so tests can tell which response was replayed.
+    struct EchoBackend;
+
+    #[async_trait]
+    impl QaBackend for EchoBackend {
+        async fn get_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+            Ok(ids
+                .iter()
+                .map(|id| question(format!("{site} {ids:?}"), *id))
+                .collect())
+        }
+
+        async fn complete_question(
+            &self,
+            _site: &str,
+            question: Question,
+        ) -> Result<Question, ApiError> {
+            Ok(question)
+        }
+
+        async fn search_questions(
+            &self,
+            site: &str,
+            query: &SearchQuery,
+            page: u32,
+        ) -> Result<Page<QuestionSummary>, ApiError> {
+            let summary = json::to_value(question(format!("{site} {page} {query:?}"), 1)).unwrap();
+
+            Ok(Page {
+                items: vec![json::from_value(summary).unwrap()],
+                has_more: false,
+                backoff: None,
+                quota_max: None,
+                quota_remaining: None,
+            })
+        }
+
+        async fn get_post_comments(
+            &self,
+            _site: &str,
+            _post_id: Id,
+        ) -> Result<Vec<Comment>, ApiError> {
+            Ok(Vec::new())
+        }
+
+        async fn get_user(
+            &self,
+            _site: &str,
+            _user_id: Id,
+        ) -> Result<Option<UserProfile>, ApiError> {
+            Ok(None)
+        }
+
+        async fn get_answer_question_id(
+            &self,
+            _site: &str,
+            answer_id: Id,
+        ) -> Result<Option<Id>, ApiError> {
+            Ok(Some(answer_id + 1))
+        }
+
+        async fn get_tag(&self, site: &str, name: &str) -> Result<Option<Tag>, ApiError> {
+            Ok(Some(Tag {
+                name: format!("{site} {name}"),
+                count: 1,
+            }))
+        }
+
+        async fn get_sites(&self) -> Result<Vec<Site>, ApiError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn search_titles(backend: &Backend, site: &str, query: &SearchQuery) -> Vec<String> {
+        block_on(backend.search_questions(site, query, 2))
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|summary| summary.title)
+            .collect()
+    }
+
+    fn tag_name(backend: &Backend, site: &str, name: &str) -> Option<String> {
+        block_on(backend.get_tag(site, name))
+            .unwrap()
+            .map(|tag| tag.name)
+    }
+
+    #[test]
+    fn replays_recorded_responses() {
+        let dir = TestDir::new("fixtures-replay");
+        let recording: Backend =
+            Arc::new(FixtureBackend::record(dir.0.clone(), Arc::new(EchoBackend)));
+        let replay: Backend = Arc::new(FixtureBackend::replay(dir.0.clone()));
+
+        let query = SearchQuery::from("[rust] title:tabs answers:2");
+        let recorded_titles = search_titles(&recording, "stackoverflow", &query);
+        let recorded_questions = block_on(recording.get_questions("superuser", &[3, 1])).unwrap();
+        block_on(recording.get_answer_question_id("stackoverflow", 7)).unwrap();
+
+        assert_eq!(
+            search_titles(&replay, "stackoverflow", &query),
+            recorded_titles
+        );
+        assert_eq!(
+            block_on(replay.get_questions("superuser", &[3, 1]))
+                .unwrap()
+                .iter()
+                .map(|question| (question.question_id, question.title.as_str()))
+                .collect::<Vec<_>>(),
+            recorded_questions
+                .iter()
+                .map(|question| (question.question_id, question.title.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            block_on(replay.get_answer_question_id("stackoverflow", 7)).unwrap(),
+            Some(8)
+        );
+
+        // Requests that were never recorded fail like an unreachable server.
+        let error = block_on(replay.get_questions("superuser", &[1, 3])).unwrap_err();
+        assert!(error.is_offline(), "{error}");
+    }
+
+    #[test]
+    fn keeps_responses_of_different_parameters_apart() {
+        let dir = TestDir::new("fixtures-parameters");
+        let recording: Backend =
+            Arc::new(FixtureBackend::record(dir.0.clone(), Arc::new(EchoBackend)));
+        let replay: Backend = Arc::new(FixtureBackend::replay(dir.0.clone()));
+
+        // Pairs that used to share a fixture file.
+        let tags = [
+            ("a_b", "c"),
+            ("a", "b_c"),
+            ("stackoverflow", "c++"),
+            ("stackoverflow", "c__"),
+            ("stackoverflow", "c#"),
+        ];
+        let queries = [
+            SearchQuery {
+                q: "[rust]".to_owned(),
+                ..Default::default()
+            },
+            SearchQuery {
+                tagged: vec!["rust".to_owned()],
+                ..Default::default()
+            },
+            SearchQuery {
+                title: "a b".to_owned(),
+                ..Default::default()
+            },
+            SearchQuery {
+                title: "a_b".to_owned(),
+                ..Default::default()
+            },
+        ];
+
+        for (site, name) in tags {
+            tag_name(&recording, site, name);
+        }
+        for query in &queries {
+            search_titles(&recording, "stackoverflow", query);
+        }
+
+        for (site, name) in tags {
+            assert_eq!(
+                tag_name(&replay, site, name),
+                Some(format!("{site} {name}"))
+            );
+        }
+        for query in &queries {
+            assert_eq!(
+                search_titles(&replay, "stackoverflow", query),
+                [format!("stackoverflow 2 {query:?}")]
+            );
+        }
+    }
+
+    #[test]
+    fn shortens_long_fixture_names() {
+        let dir = TestDir::new("fixtures-long-names");
+        let recording: Backend =
+            Arc::new(FixtureBackend::record(dir.0.clone(), Arc::new(EchoBackend)));
+        let replay: Backend = Arc::new(FixtureBackend::replay(dir.0.clone()));
+
+        // Names of these only differ after the readable part.
+        let ids = (1..=100).collect::<Vec<Id>>();
+        let queries = ["long", "longer"].map(|end| SearchQuery {
+            q: format!("{} {end}", "é".repeat(100)),
+            ..Default::default()
+        });
+
+        block_on(recording.get_questions("stackoverflow", &ids)).unwrap();
+        for query in &queries {
+            search_titles(&recording, "stackoverflow", query);
+        }
+
+        assert_eq!(
+            block_on(replay.get_questions("stackoverflow", &ids))
+                .unwrap()
+                .len(),
+            ids.len()
+        );
+        for query in &queries {
+            assert_eq!(
+                search_titles(&replay, "stackoverflow", query),
+                [format!("stackoverflow 2 {query:?}")]
+            );
+        }
+
+        for method in ["questions", "search"] {
+            for entry in std::fs::read_dir(dir.0.join(method)).unwrap() {
+                assert!(entry.unwrap().file_name().len() <= 255);
+            }
+        }
+    }
+}
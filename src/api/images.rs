@@ -0,0 +1,89 @@
+use std::hash::Hasher;
+use std::path::PathBuf;
+
+use fnv::FnvHasher;
+
+use super::error::ApiError;
+
+/// Largest image that is downloaded, posts images are screenshots that are much smaller.
+pub const MAX_IMAGE_SIZE: usize = 20 * 1024 * 1024;
+
+/// Disk cache of images shown in posts, keyed by their urls.
+///
+/// Uploaded images never change at the same url, so entries are used without expiring.
+pub struct ImageCache {
+    dir: PathBuf,
+}
+
+impl ImageCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn get(&self, url: &str) -> Option<Vec<u8>> {
+        std::fs::read(self.entry_file(url)).ok()
+    }
+
+    pub fn put(&self, url: &str, image: &[u8]) {
+        // Failing to write the cache is not fatal, it will be downloaded again next time.
+        let _ = std::fs::create_dir_all(&self.dir);
+        let _ = std::fs::write(self.entry_file(url), image);
+    }
+
+    // Urls could be longer than file names and have any character, so their hashes are used.
+    //
+    // FNV hashes are the same across builds and Rust versions, unlike `DefaultHasher`.
+    fn entry_file(&self, url: &str) -> PathBuf {
+        let mut hasher = FnvHasher::default();
+        hasher.write(url.as_bytes());
+
+        self.dir.join(format!("{:016x}", hasher.finish()))
+    }
+}
+
+/// Download an image, images are not API requests, so they are not throttled.
+pub async fn download(reqwest_client: &reqwest::Client, url: &str) -> Result<Vec<u8>, ApiError> {
+    let too_large = || ApiError::Decode(format!("image is larger than {MAX_IMAGE_SIZE} bytes"));
+
+    let mut res = reqwest_client.get(url).send().await?;
+
+    let status = res.status();
+    if !status.is_success() {
+        return Err(ApiError::HttpStatus(status.as_u16()));
+    }
+
+    if res
+        .content_length()
+        .is_some_and(|length| length > MAX_IMAGE_SIZE as u64)
+    {
+        return Err(too_large());
+    }
+
+    // The length might be missing or wrong, so the download stops as soon as it's too large.
+    let mut image = Vec::new();
+
+    while let Some(chunk) = res.chunk().await? {
+        if image.len() + chunk.len() > MAX_IMAGE_SIZE {
+            return Err(too_large());
+        }
+
+        image.extend_from_slice(&chunk);
+    }
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_entries_the_same_across_builds() {
+        let cache = ImageCache::new(PathBuf::from("images"));
+
+        assert_eq!(
+            cache.entry_file("https://i.sstatic.net/a.png"),
+            PathBuf::from("images/31f0cf8c9b438454")
+        );
+    }
+}
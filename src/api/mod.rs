@@ -1 +1,14 @@
+pub mod backend;
+pub mod batcher;
+pub mod cache;
+pub mod config;
+pub mod dump;
+pub mod error;
+pub mod fixtures;
+pub mod images;
+pub mod offline;
+pub mod router;
+pub mod scheduler;
+pub mod sites;
 pub mod stackexchange;
+pub mod web_url;
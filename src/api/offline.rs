@@ -0,0 +1,436 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::backend::{Backend, QaBackend};
+use super::batcher::MAX_IDS_PER_REQUEST;
+use super::cache::ResponseCache;
+use super::dump::import::ImportProgress;
+use super::error::ApiError;
+use super::scheduler::Quota;
+use super::stackexchange::{
+    Comment, Id, Page, Question, QuestionSummary, SearchQuery, Site, Tag, UserProfile,
+};
+
+/// Questions to download for offline reading.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackSource {
+    /// The highest voted questions of a tag, up to `limit`.
+    Tag {
+        tag: String,
+        limit: u32,
+    },
+    Questions(Vec<Id>),
+}
+
+impl std::fmt::Display for PackSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tag { tag, limit } => write!(f, "[{tag}] ×{limit}"),
+            Self::Questions(ids) => write!(
+                f,
+                "{}",
+                ids.iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>()
+                    .join(";")
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackProgress {
+    pub done: usize,
+    /// Unknown until the questions of a tag are listed.
+    pub total: Option<usize>,
+}
+
+/// A download of questions for offline reading, with every answer and comment.
+///
+/// Its state is saved after each batch of questions, so an interrupted download could be resumed
+/// from where it stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflinePack {
+    pub site: String,
+    pub source: PackSource,
+    // Resolved once, so resuming doesn't list the questions of a tag again.
+    ids: Option<Vec<Id>>,
+    // Number of `ids` already downloaded, in order.
+    done: usize,
+}
+
+impl OfflinePack {
+    /// Continue an unfinished download of the same questions, or start a new one.
+    pub fn new(site: &str, source: PackSource) -> Self {
+        Self::load_all()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|pack| pack.site == site && pack.source == source && !pack.is_finished())
+            .unwrap_or_else(|| Self {
+                site: site.to_owned(),
+                source,
+                ids: None,
+                done: 0,
+            })
+    }
+
+    /// Downloads that were interrupted before they finished.
+    pub fn unfinished() -> Vec<Self> {
+        Self::load_all()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|pack| !pack.is_finished())
+            .collect()
+    }
+
+    pub fn progress(&self) -> PackProgress {
+        PackProgress {
+            done: self.done,
+            total: self.ids.as_ref().map(Vec::len),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.ids.as_ref().is_some_and(|ids| self.done >= ids.len())
+    }
+
+    // Every download is kept in one file, finished ones are replaced when downloaded again.
+    fn packs_file() -> PathBuf {
+        crate::utils::data_dir().join("offline").join("packs.json")
+    }
+
+    fn load_all() -> io::Result<Vec<Self>> {
+        Ok(crate::storage::read_json(&Self::packs_file())?.unwrap_or_default())
+    }
+
+    // Failing to save is not fatal, like the cache, the download just can't be resumed.
+    fn save(&self) {
+        // Other downloads would be lost if the file was replaced without them.
+        let Ok(mut packs) = Self::load_all() else {
+            return;
+        };
+        packs.retain(|pack| pack.site != self.site || pack.source != self.source);
+        packs.push(self.clone());
+
+        let _ = crate::storage::write_json(&Self::packs_file(), &packs);
+    }
+}
+
+/// Serves questions downloaded for offline reading when another backend can't reach the API.
+pub struct OfflineBackend {
+    inner: Backend,
+    // Kept with the user data, unlike the cache.
+    store: ResponseCache,
+}
+
+impl OfflineBackend {
+    pub fn new(inner: Backend) -> Self {
+        Self::with_dir(inner, crate::utils::data_dir().join("offline"))
+    }
+
+    fn with_dir(inner: Backend, dir: PathBuf) -> Self {
+        Self {
+            inner,
+            // Downloads are never fresh, so they don't hide newer responses of `inner`.
+            store: ResponseCache::new(dir, Duration::ZERO),
+        }
+    }
+
+    // Downloaded copy of a question, when it's newer than a stale copy from `inner`.
+    fn newer_download(&self, site: &str, question: Question) -> Question {
+        match (
+            question.cached_at,
+            self.store
+                .get::<Question>("questions", site, question.question_id),
+        ) {
+            (Some(cached_at), Some(hit)) if hit.stored > cached_at => Question {
+                cached_at: Some(hit.stored),
+                ..hit.value
+            },
+            _ => question,
+        }
+    }
+
+    fn downloaded_question(&self, site: &str, question_id: Id) -> Option<Question> {
+        self.store
+            .get::<Question>("questions", site, question_id)
+            .map(|hit| Question {
+                cached_at: Some(hit.stored),
+                ..hit.value
+            })
+    }
+
+    // Store a question with every answer and comment, and its comments on their own as well.
+    fn store_question(&self, site: &str, question: &Question) {
+        let comments = std::iter::once((question.question_id, &question.comments)).chain(
+            question
+                .answers
+                .iter()
+                .flatten()
+                .map(|answer| (answer.answer_id, &answer.comments)),
+        );
+        for (post_id, comments) in comments {
+            self.store.put(
+                "comments",
+                site,
+                post_id,
+                &comments.as_deref().unwrap_or_default(),
+            );
+        }
+
+        self.store
+            .put("questions", site, question.question_id, question);
+    }
+}
+
+#[async_trait]
+impl QaBackend for OfflineBackend {
+    async fn get_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+        match self.inner.get_questions(site, ids).await {
+            Ok(questions) => Ok(questions
+                .into_iter()
+                .map(|question| self.newer_download(site, question))
+                .collect()),
+            // Every question must have been downloaded to be shown offline.
+            Err(error) if error.is_offline() => ids
+                .iter()
+                .map(|id| self.downloaded_question(site, *id))
+                .collect::<Option<Vec<Question>>>()
+                .ok_or(error),
+            Err(error) => Err(error),
+        }
+    }
+
+    async fn complete_question(
+        &self,
+        site: &str,
+        question: Question,
+    ) -> Result<Question, ApiError> {
+        let question_id = question.question_id;
+
+        match self.inner.complete_question(site, question).await {
+            Ok(question) => Ok(self.newer_download(site, question)),
+            Err(error) if error.is_offline() => {
+                self.downloaded_question(site, question_id).ok_or(error)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    async fn search_questions(
+        &self,
+        site: &str,
+        query: &SearchQuery,
+        page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError> {
+        self.inner.search_questions(site, query, page).await
+    }
+
+    async fn get_post_comments(&self, site: &str, post_id: Id) -> Result<Vec<Comment>, ApiError> {
+        match self.inner.get_post_comments(site, post_id).await {
+            Err(error) if error.is_offline() => self
+                .store
+                .get::<Vec<Comment>>("comments", site, post_id)
+                .map(|hit| hit.value)
+                .ok_or(error),
+            result => result,
+        }
+    }
+
+    async fn get_user(&self, site: &str, user_id: Id) -> Result<Option<UserProfile>, ApiError> {
+        self.inner.get_user(site, user_id).await
+    }
+
+    async fn get_answer_question_id(
+        &self,
+        site: &str,
+        answer_id: Id,
+    ) -> Result<Option<Id>, ApiError> {
+        match self.inner.get_answer_question_id(site, answer_id).await {
+            // Look for a downloaded question with the answer.
+            Err(error) if error.is_offline() => self
+                .store
+                .ids("questions", site)
+                .into_iter()
+                .find(|question_id| {
+                    self.store
+                        .get::<Question>("questions", site, *question_id)
+                        .is_some_and(|hit| {
+                            hit.value
+                                .answers
+                                .iter()
+                                .flatten()
+                                .any(|answer| answer.answer_id == answer_id)
+                        })
+                })
+                .map(Some)
+                .ok_or(error),
+            result => result,
+        }
+    }
+
+    async fn get_tag(&self, site: &str, name: &str) -> Result<Option<Tag>, ApiError> {
+        self.inner.get_tag(site, name).await
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>, ApiError> {
+        self.inner.get_sites().await
+    }
+
+    async fn get_image(&self, url: &str) -> Result<Vec<u8>, ApiError> {
+        self.inner.get_image(url).await
+    }
+
+    fn subscribe_quota(&self) -> Option<tokio::sync::watch::Receiver<Option<Quota>>> {
+        self.inner.subscribe_quota()
+    }
+
+    fn lists_every_site(&self) -> bool {
+        self.inner.lists_every_site()
+    }
+
+    async fn list_tag_questions(
+        &self,
+        site: &str,
+        tag: &str,
+        limit: u32,
+    ) -> Result<Vec<Id>, ApiError> {
+        self.inner.list_tag_questions(site, tag, limit).await
+    }
+
+    /// Download the questions of a pack, reporting the progress after each batch of them.
+    ///
+    /// Downloaded questions are used by `get_questions` and the rest when the API can't be
+    /// reached.
+    async fn download_pack(
+        &self,
+        pack: &mut OfflinePack,
+        on_progress: &mut (dyn FnMut(PackProgress) + Send),
+    ) -> Result<(), ApiError> {
+        let ids = match &pack.ids {
+            Some(ids) => ids.clone(),
+            None => {
+                let ids = match &pack.source {
+                    PackSource::Tag { tag, limit } => {
+                        self.inner
+                            .list_tag_questions(&pack.site, tag, *limit)
+                            .await?
+                    }
+                    PackSource::Questions(ids) => ids.clone(),
+                };
+
+                pack.ids = Some(ids.clone());
+                pack.save();
+                ids
+            }
+        };
+
+        on_progress(pack.progress());
+
+        while let Some(chunk) = ids
+            .get(pack.done..)
+            .filter(|rest| !rest.is_empty())
+            .map(|rest| &rest[..rest.len().min(MAX_IDS_PER_REQUEST)])
+        {
+            for question in self.inner.get_questions(&pack.site, chunk).await? {
+                let question = self.inner.complete_question(&pack.site, question).await?;
+                self.store_question(&pack.site, &question);
+            }
+
+            pack.done += chunk.len();
+            pack.save();
+
+            on_progress(pack.progress());
+        }
+
+        Ok(())
+    }
+
+    fn import_dump(
+        &self,
+        site: &str,
+        dir: &Path,
+        on_progress: &mut dyn FnMut(ImportProgress),
+    ) -> Result<(), ApiError> {
+        self.inner.import_dump(site, dir, on_progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use serde_json::{self as json, json};
+
+    use super::OfflineBackend;
+    use crate::api::backend::QaBackend;
+    use crate::api::error::ApiError;
+    use crate::test_utils::{block_on, question, FakeBackend, TestDir};
+
+    #[test]
+    fn serves_downloaded_questions_when_offline() {
+        let dir = TestDir::new("offline-questions");
+        let inner = Arc::new(FakeBackend::default());
+        let backend = OfflineBackend::with_dir(inner.clone(), dir.0.clone());
+        backend.store_question("stackoverflow", &question("Downloaded".to_owned(), 1));
+
+        // Responses of the API are used whenever it's reachable.
+        let questions = block_on(backend.get_questions("stackoverflow", &[1])).unwrap();
+        assert_eq!(questions[0].title, "request 0");
+        assert_eq!(questions[0].cached_at, None);
+
+        inner.offline.store(true, Ordering::Relaxed);
+
+        let questions = block_on(backend.get_questions("stackoverflow", &[1])).unwrap();
+        assert_eq!(questions[0].title, "Downloaded");
+        assert!(questions[0].cached_at.is_some());
+
+        for (site, ids) in [("stackoverflow", [1, 2]), ("superuser", [1, 1])] {
+            assert!(matches!(
+                block_on(backend.get_questions(site, &ids)),
+                Err(ApiError::Transport(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn finds_questions_of_downloaded_answers_when_offline() {
+        let dir = TestDir::new("offline-answers");
+        let inner = Arc::new(FakeBackend::default());
+        let backend = OfflineBackend::with_dir(inner.clone(), dir.0.clone());
+
+        for question_id in [1, 2] {
+            let mut question = question(String::new(), question_id);
+            question.answers = Some(vec![json::from_value(json!({
+                "answer_id": question_id * 10,
+                "body_markdown": "",
+                "comment_count": 0,
+                "creation_date": 0,
+                "is_accepted": false,
+                "last_activity_date": 0,
+                "owner": { "display_name": "" },
+                "score": 0,
+            }))
+            .unwrap()]);
+            backend.store_question("stackoverflow", &question);
+        }
+
+        inner.offline.store(true, Ordering::Relaxed);
+
+        assert_eq!(
+            block_on(backend.get_answer_question_id("stackoverflow", 20)).unwrap(),
+            Some(2)
+        );
+        for (site, answer_id) in [("stackoverflow", 30), ("superuser", 20)] {
+            assert!(matches!(
+                block_on(backend.get_answer_question_id(site, answer_id)),
+                Err(ApiError::Transport(_))
+            ));
+        }
+    }
+}
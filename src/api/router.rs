@@ -0,0 +1,446 @@
+use std::fmt;
+
+use reqwest::Url;
+
+use super::error::{ApiError, UriError};
+use super::stackexchange::{Id, SearchQuery};
+use super::web_url::{self, WebLink, WebTarget};
+
+pub const URI_SCHEME: &str = "stackbloatless";
+
+/// Something that could be opened in a tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+    /// Questions, optionally with an answer or a comment of the first one to focus.
+    Questions {
+        ids: Vec<Id>,
+        answer_id: Option<Id>,
+        comment_id: Option<Id>,
+    },
+    /// An answer whose question isn't known yet, optionally with one of its comments to focus.
+    Answer {
+        answer_id: Id,
+        comment_id: Option<Id>,
+    },
+    Search(SearchQuery),
+    User(Id),
+    Tag(String),
+}
+
+/// A validated application uri, of a single StackExchange site.
+///
+/// Accepted forms:
+///
+/// - stackbloatless://{site}/questions/{ids}#answer-{id}
+/// - stackbloatless://{site}/questions/{ids}#comment{id}_{post id}
+/// - stackbloatless://{site}/{ids} (same as above, without an answer)
+/// - stackbloatless://{site}/answers/{id}
+/// - stackbloatless://{site}/answers/{id}#comment{id}_{answer id}
+/// - stackbloatless://{site}/search?q={search term}
+/// - stackbloatless://{site}/users/{id}
+/// - stackbloatless://{site}/tags/{tag}
+///
+/// Where `{ids}` are questions ids separated by `;`, and `{site}` is the `api_site_parameter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppUri {
+    pub site: String,
+    pub route: Route,
+}
+
+impl AppUri {
+    pub fn new(site: &str, route: Route) -> Self {
+        Self {
+            site: site.to_owned(),
+            route,
+        }
+    }
+
+    /// Parse an application uri, or a StackExchange web link.
+    pub fn parse(uri: &str) -> Result<Self, ApiError> {
+        if web_url::is_web_url(uri) {
+            return web_url::resolve(uri).map(Self::from);
+        }
+
+        let invalid_uri = |reason| ApiError::invalid_uri(uri, reason);
+
+        let url = Url::parse(uri).map_err(|_| invalid_uri(UriError::Malformed))?;
+
+        if url.scheme() != URI_SCHEME {
+            return Err(invalid_uri(UriError::UnsupportedScheme(
+                url.scheme().to_owned(),
+            )));
+        }
+
+        let site = url.host_str().unwrap_or_default();
+        if !is_valid_site(site) {
+            return Err(invalid_uri(UriError::UnknownSite(site.to_owned())));
+        }
+
+        let segments = url
+            .path_segments()
+            .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+            .unwrap_or_else(Vec::new);
+
+        let parse_id = |id: &str| {
+            id.parse::<Id>()
+                .map_err(|_| invalid_uri(UriError::InvalidId(id.to_owned())))
+        };
+        let parse_ids = |ids: &str| ids.split(';').map(parse_id).collect::<Result<Vec<_>, _>>();
+
+        let route = match segments.as_slice() {
+            ["questions", ids] => {
+                let ids = parse_ids(ids)?;
+                // Same anchors of web links, comments anchors point to the question or an answer.
+                let anchor = url
+                    .fragment()
+                    .map(web_url::parse_anchor)
+                    .unwrap_or_default();
+
+                Route::Questions {
+                    answer_id: anchor.post_id.filter(|post_id| *post_id != ids[0]),
+                    comment_id: anchor.comment_id,
+                    ids,
+                }
+            }
+            ["answers", id] => Route::Answer {
+                answer_id: parse_id(id)?,
+                comment_id: url
+                    .fragment()
+                    .and_then(|fragment| web_url::parse_anchor(fragment).comment_id),
+            },
+            ["users", id] => Route::User(parse_id(id)?),
+            ["tags", tag] if !tag.is_empty() => Route::Tag(percent_decode(tag)),
+            ["search"] => {
+                let search_term = url
+                    .query_pairs()
+                    .find_map(|(key, value)| (key == "q").then_some(value))
+                    .filter(|search_term| !search_term.trim().is_empty())
+                    .ok_or_else(|| invalid_uri(UriError::MissingParameter("q")))?;
+
+                Route::Search(SearchQuery::from(search_term.as_ref()))
+            }
+            // Older form with questions ids only.
+            [ids] if ids.starts_with(|c: char| c.is_ascii_digit()) => Route::Questions {
+                ids: parse_ids(ids)?,
+                answer_id: None,
+                comment_id: None,
+            },
+            _ => return Err(invalid_uri(UriError::UnknownRoute(url.path().to_owned()))),
+        };
+
+        Ok(Self::new(site, route))
+    }
+}
+
+impl From<WebLink> for AppUri {
+    fn from(link: WebLink) -> Self {
+        let route = match link.target {
+            WebTarget::Question {
+                question_id,
+                answer_id,
+                comment_id,
+            } => Route::Questions {
+                ids: vec![question_id],
+                answer_id,
+                comment_id,
+            },
+            WebTarget::Answer {
+                answer_id,
+                comment_id,
+            } => Route::Answer {
+                answer_id,
+                comment_id,
+            },
+        };
+
+        Self {
+            site: link.site,
+            route,
+        }
+    }
+}
+
+impl fmt::Display for AppUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut url =
+            Url::parse(&format!("{URI_SCHEME}://{}", self.site)).map_err(|_| fmt::Error)?;
+
+        match &self.route {
+            Route::Questions {
+                ids,
+                answer_id,
+                comment_id,
+            } => {
+                let post_id = answer_id.or(ids.first().copied());
+                let fragment = match (comment_id, answer_id) {
+                    (Some(comment_id), _) => {
+                        post_id.map(|post_id| format!("comment{comment_id}_{post_id}"))
+                    }
+                    (None, Some(answer_id)) => Some(format!("answer-{answer_id}")),
+                    (None, None) => None,
+                };
+                url.set_fragment(fragment.as_deref());
+
+                let ids = ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>()
+                    .join(";");
+
+                url.set_path(&format!("questions/{ids}"));
+            }
+            Route::Answer {
+                answer_id,
+                comment_id,
+            } => {
+                url.set_fragment(
+                    comment_id
+                        .map(|comment_id| format!("comment{comment_id}_{answer_id}"))
+                        .as_deref(),
+                );
+                url.set_path(&format!("answers/{answer_id}"));
+            }
+            Route::User(id) => url.set_path(&format!("users/{id}")),
+            Route::Tag(tag) => {
+                url.path_segments_mut()
+                    .map_err(|_| fmt::Error)?
+                    .clear()
+                    .extend(["tags", tag]);
+            }
+            Route::Search(query) => {
+                url.set_path("search");
+                url.query_pairs_mut().append_pair("q", &query.to_string());
+            }
+        }
+
+        write!(f, "{url}")
+    }
+}
+
+/// Whether the text should be opened as a uri, rather than searched for.
+pub fn is_uri(text: &str) -> bool {
+    web_url::is_web_url(text)
+        || text
+            .strip_prefix(URI_SCHEME)
+            .is_some_and(|rest| rest.starts_with("://"))
+}
+
+// Sites parameters are like `stackoverflow`, `meta.superuser`, and `mathoverflow.net`.
+fn is_valid_site(site: &str) -> bool {
+    !site.is_empty()
+        && site
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+// Tags could include characters that are escaped in paths, like `c%23` for `c#`.
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(escaped) => {
+                bytes.push(escaped);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid_site, percent_decode, AppUri, Route};
+    use crate::api::error::{ApiError, UriError};
+    use crate::api::stackexchange::{Id, SearchQuery};
+
+    fn questions(ids: &[Id], answer_id: Option<Id>, comment_id: Option<Id>) -> Route {
+        Route::Questions {
+            ids: ids.to_vec(),
+            answer_id,
+            comment_id,
+        }
+    }
+
+    fn rejection(uri: &str) -> UriError {
+        match AppUri::parse(uri) {
+            Err(ApiError::InvalidUri { reason, .. }) => reason,
+            other => panic!("{uri} wasn't rejected: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_every_route() {
+        for (uri, site, route) in [
+            (
+                "stackbloatless://stackoverflow/questions/1;2",
+                "stackoverflow",
+                questions(&[1, 2], None, None),
+            ),
+            (
+                "stackbloatless://stackoverflow/questions/1#answer-3",
+                "stackoverflow",
+                questions(&[1], Some(3), None),
+            ),
+            (
+                "stackbloatless://stackoverflow/questions/1#comment4_3",
+                "stackoverflow",
+                questions(&[1], Some(3), Some(4)),
+            ),
+            (
+                "stackbloatless://stackoverflow/questions/1#comment4_1",
+                "stackoverflow",
+                questions(&[1], None, Some(4)),
+            ),
+            (
+                "stackbloatless://meta.superuser/123;456",
+                "meta.superuser",
+                questions(&[123, 456], None, None),
+            ),
+            (
+                "stackbloatless://mathoverflow.net/answers/3",
+                "mathoverflow.net",
+                Route::Answer {
+                    answer_id: 3,
+                    comment_id: None,
+                },
+            ),
+            (
+                "stackbloatless://stackoverflow/users/42/",
+                "stackoverflow",
+                Route::User(42),
+            ),
+            (
+                "stackbloatless://stackoverflow/tags/c%23",
+                "stackoverflow",
+                Route::Tag("c#".to_owned()),
+            ),
+            (
+                "stackbloatless://stackoverflow/search?q=rust+%5Basync%5D+is%3Aquestion",
+                "stackoverflow",
+                Route::Search(SearchQuery::from("rust [async] is:question")),
+            ),
+        ] {
+            assert_eq!(
+                AppUri::parse(uri).unwrap(),
+                AppUri::new(site, route),
+                "{uri}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_uris() {
+        assert_eq!(rejection("stackbloatless"), UriError::Malformed);
+        assert_eq!(
+            rejection("other://stackoverflow/questions/1"),
+            UriError::UnsupportedScheme("other".to_owned())
+        );
+        assert_eq!(
+            rejection("stackbloatless:///questions/1"),
+            UriError::UnknownSite(String::new())
+        );
+        assert_eq!(
+            rejection("stackbloatless://stack_overflow/questions/1"),
+            UriError::UnknownSite("stack_overflow".to_owned())
+        );
+        assert_eq!(
+            rejection("stackbloatless://stackoverflow/questions/1;x"),
+            UriError::InvalidId("x".to_owned())
+        );
+        assert_eq!(
+            rejection("stackbloatless://stackoverflow/users/-1"),
+            UriError::InvalidId("-1".to_owned())
+        );
+        assert_eq!(
+            rejection("stackbloatless://stackoverflow"),
+            UriError::UnknownRoute(String::new())
+        );
+        assert_eq!(
+            rejection("stackbloatless://stackoverflow/questions"),
+            UriError::UnknownRoute("/questions".to_owned())
+        );
+        assert_eq!(
+            rejection("stackbloatless://stackoverflow/search?q=+"),
+            UriError::MissingParameter("q")
+        );
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        for route in [
+            questions(&[1, 2], None, None),
+            questions(&[1], Some(3), None),
+            questions(&[1], Some(3), Some(4)),
+            questions(&[1], None, Some(4)),
+            Route::Answer {
+                answer_id: 3,
+                comment_id: None,
+            },
+            Route::User(42),
+            Route::Tag("c#".to_owned()),
+            Route::Tag("c++".to_owned()),
+            Route::Search(SearchQuery::from("rust & [async] user:1 \"a b\"")),
+        ] {
+            let uri = AppUri::new("meta.stackoverflow", route);
+            assert_eq!(AppUri::parse(&uri.to_string()).unwrap(), uri, "{uri}");
+        }
+    }
+
+    #[test]
+    fn validates_sites() {
+        for site in [
+            "stackoverflow",
+            "meta.superuser",
+            "mathoverflow.net",
+            "ru-test",
+        ] {
+            assert!(is_valid_site(site), "{site}");
+        }
+        for site in ["", "stack overflow", "stack_overflow", "a/b", "ünicode"] {
+            assert!(!is_valid_site(site), "{site}");
+        }
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(percent_decode("c%23"), "c#");
+        assert_eq!(percent_decode("%E2%9C%93"), "✓");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%2"), "%zz%2");
+        assert_eq!(percent_decode("%FF"), "\u{FFFD}");
+    }
+
+    #[test]
+    fn keeps_comments_of_answer_links() {
+        let uri = AppUri::parse("https://unix.stackexchange.com/a/456/321#comment789_456").unwrap();
+        assert_eq!(
+            uri,
+            AppUri::new(
+                "unix",
+                Route::Answer {
+                    answer_id: 456,
+                    comment_id: Some(789),
+                },
+            )
+        );
+
+        assert_eq!(
+            uri.to_string(),
+            "stackbloatless://unix/answers/456#comment789_456"
+        );
+        assert_eq!(AppUri::parse(&uri.to_string()).unwrap(), uri);
+    }
+}
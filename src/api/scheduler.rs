@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::watch;
+
+use super::error::{ApiError, ApiErrorName};
+
+// The API refuses more than 30 requests per second from a single IP.
+// Docs: https://api.stackexchange.com/docs/throttle
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(34);
+
+const MAX_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+// Don't keep a request pending for longer than this, fail instead.
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Daily requests quota, it's reset at midnight UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub remaining: u32,
+    pub max: u32,
+}
+
+struct SchedulerState {
+    // Methods that must not be requested before the given instant.
+    backoffs: HashMap<String, Instant>,
+    // Instant reserved for the last scheduled request.
+    last_slot: Option<Instant>,
+    quota: Option<(Quota, SystemTime)>,
+}
+
+/// Makes sure every request respects the API throttling rules.
+pub struct RequestScheduler {
+    state: Mutex<SchedulerState>,
+    quota_sender: watch::Sender<Option<Quota>>,
+}
+
+impl RequestScheduler {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(SchedulerState {
+                backoffs: HashMap::new(),
+                last_slot: None,
+                quota: None,
+            }),
+            quota_sender: watch::Sender::new(None),
+        }
+    }
+
+    /// Wait until a request to `method` is allowed.
+    pub async fn wait_turn(&self, method: &str) -> Result<(), ApiError> {
+        let turn = self.reserve_turn(method)?;
+
+        tokio::time::sleep_until(turn.into()).await;
+
+        Ok(())
+    }
+
+    // Instant when a request to `method` can be sent.
+    //
+    // Requests are spaced out globally, but a backoff only delays requests to its own method.
+    fn reserve_turn(&self, method: &str) -> Result<Instant, ApiError> {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some((quota, updated)) = state.quota {
+            if quota.remaining == 0 && utc_day(updated) == utc_day(SystemTime::now()) {
+                return Err(ApiError::QuotaExhausted);
+            }
+        }
+
+        let now = Instant::now();
+        let slot = match state.last_slot {
+            Some(last_slot) => now.max(last_slot + MIN_REQUEST_INTERVAL),
+            None => now,
+        };
+        let turn = match state.backoffs.get(method) {
+            Some(backoff) => slot.max(*backoff),
+            None => slot,
+        };
+
+        if turn.duration_since(now) > MAX_RETRY_WAIT {
+            return Err(ApiError::Throttled {
+                retry_after: turn.duration_since(now),
+            });
+        }
+
+        state.last_slot = Some(slot);
+
+        Ok(turn)
+    }
+
+    /// Record throttling fields of a successful response.
+    pub fn record_response(&self, method: &str, backoff: Option<u64>, quota: Option<Quota>) {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(backoff) = backoff {
+            state.backoffs.insert(
+                method.to_owned(),
+                Instant::now() + Duration::from_secs(backoff),
+            );
+        }
+
+        if let Some(quota) = quota {
+            state.quota = Some((quota, SystemTime::now()));
+            self.quota_sender.send_replace(Some(quota));
+        }
+    }
+
+    /// Delay before retrying a failed request, or `None` when it shouldn't be retried.
+    pub fn retry_delay(&self, method: &str, error: &ApiError, attempt: u32) -> Option<Duration> {
+        if attempt >= MAX_RETRIES || !error.is_transient() {
+            return None;
+        }
+
+        // Throttle violations tell us how long to wait.
+        if let ApiError::Api {
+            name: ApiErrorName::ThrottleViolation,
+            message,
+            ..
+        } = error
+        {
+            if let Some(wait) = parse_throttle_wait(message) {
+                self.state
+                    .lock()
+                    .unwrap()
+                    .backoffs
+                    .insert(method.to_owned(), Instant::now() + wait);
+
+                return (wait <= MAX_RETRY_WAIT).then_some(wait);
+            }
+        }
+
+        // Exponential backoff with jitter, so retries don't happen all at once.
+        Some(RETRY_BASE_DELAY * 2_u32.pow(attempt) + jitter(RETRY_BASE_DELAY))
+    }
+
+    pub fn quota(&self) -> Option<Quota> {
+        *self.quota_sender.borrow()
+    }
+
+    /// Get notified whenever the quota is updated.
+    pub fn subscribe_quota(&self) -> watch::Receiver<Option<Quota>> {
+        self.quota_sender.subscribe()
+    }
+}
+
+// Extract the waiting time from messages like:
+// `too many requests from this IP, more requests available in 79470 seconds`
+fn parse_throttle_wait(message: &str) -> Option<Duration> {
+    message
+        .trim_end_matches('.')
+        .strip_suffix("seconds")?
+        .split_whitespace()
+        .last()?
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+fn utc_day(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / SECONDS_PER_DAY
+}
+
+// Random duration in `[0, max)` without depending on a random numbers generator crate.
+fn jitter(max: Duration) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+
+    max.mul_f64((random % 1000) as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle_violation(message: &str) -> ApiError {
+        ApiError::Api {
+            id: 502,
+            name: ApiErrorName::ThrottleViolation,
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn spaces_out_requests() {
+        let scheduler = RequestScheduler::new();
+
+        let first = scheduler.reserve_turn("questions").unwrap();
+        let second = scheduler.reserve_turn("sites").unwrap();
+
+        assert!(second >= first + MIN_REQUEST_INTERVAL);
+    }
+
+    #[test]
+    fn applies_backoffs_to_their_method_only() {
+        let scheduler = RequestScheduler::new();
+        let now = Instant::now();
+
+        scheduler.record_response("questions", Some(10), None);
+
+        assert!(scheduler.reserve_turn("questions").unwrap() >= now + Duration::from_secs(10));
+        assert!(scheduler.reserve_turn("search/advanced").unwrap() < now + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn refuses_long_backoffs() {
+        let scheduler = RequestScheduler::new();
+
+        scheduler.record_response("questions", Some(60), None);
+
+        assert!(matches!(
+            scheduler.reserve_turn("questions"),
+            Err(ApiError::Throttled { retry_after }) if retry_after > MAX_RETRY_WAIT
+        ));
+        assert!(scheduler.reserve_turn("sites").is_ok());
+    }
+
+    #[test]
+    fn refuses_requests_until_the_quota_is_reset() {
+        let scheduler = RequestScheduler::new();
+        let exhausted = Quota {
+            remaining: 0,
+            max: 300,
+        };
+
+        scheduler.record_response("questions", None, Some(exhausted));
+
+        assert!(matches!(
+            scheduler.reserve_turn("sites"),
+            Err(ApiError::QuotaExhausted)
+        ));
+        assert_eq!(scheduler.quota(), Some(exhausted));
+
+        // Quotas of previous days were reset since.
+        scheduler.state.lock().unwrap().quota = Some((
+            exhausted,
+            SystemTime::now() - Duration::from_secs(SECONDS_PER_DAY),
+        ));
+
+        assert!(scheduler.reserve_turn("sites").is_ok());
+    }
+
+    #[test]
+    fn parses_throttle_messages() {
+        for (message, wait) in [
+            (
+                "too many requests from this IP, more requests available in 79470 seconds",
+                Some(79470),
+            ),
+            ("more requests available in 5 seconds.", Some(5)),
+            ("too many requests from this IP", None),
+            ("more requests available in some seconds", None),
+        ] {
+            assert_eq!(
+                parse_throttle_wait(message),
+                wait.map(Duration::from_secs),
+                "{message}"
+            );
+        }
+    }
+
+    #[test]
+    fn waits_as_long_as_throttle_violations_ask() {
+        let scheduler = RequestScheduler::new();
+        let now = Instant::now();
+
+        assert_eq!(
+            scheduler.retry_delay(
+                "questions",
+                &throttle_violation("more requests available in 5 seconds"),
+                0
+            ),
+            Some(Duration::from_secs(5))
+        );
+        assert!(scheduler.reserve_turn("questions").unwrap() >= now + Duration::from_secs(5));
+
+        assert_eq!(
+            scheduler.retry_delay(
+                "questions",
+                &throttle_violation("more requests available in 79470 seconds"),
+                0
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn retries_transient_errors_with_growing_delays() {
+        let scheduler = RequestScheduler::new();
+        let error = ApiError::Transport("connection reset".to_owned());
+
+        for attempt in 0..MAX_RETRIES {
+            let delay = scheduler.retry_delay("questions", &error, attempt).unwrap();
+            let base = RETRY_BASE_DELAY * 2_u32.pow(attempt);
+
+            assert!(
+                delay >= base && delay < base + RETRY_BASE_DELAY,
+                "{delay:?}"
+            );
+        }
+
+        assert_eq!(
+            scheduler.retry_delay("questions", &error, MAX_RETRIES),
+            None
+        );
+        assert_eq!(
+            scheduler.retry_delay("questions", &ApiError::HttpStatus(404), 0),
+            None
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use serde_json as json;
+
+use super::backend::QaBackend;
+use super::error::ApiError;
+use super::stackexchange::Site;
+
+// Snapshot of the catalog to use before it's fetched for the first time or when offline.
+const BUNDLED_SITES: &str = include_str!("sites.json");
+
+// Sites are rarely added or renamed.
+const SITES_CACHE_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+pub const DEFAULT_SITE: &str = "stackoverflow";
+
+/// Catalog of StackExchange sites, cached on disk.
+#[derive(Debug, Clone)]
+pub struct SiteCatalog {
+    sites: Vec<Site>,
+}
+
+impl SiteCatalog {
+    /// Load the cached catalog, or the bundled snapshot when there is no valid cache.
+    ///
+    /// Doesn't touch the network, so it's safe to call it at startup.
+    pub fn load_local() -> Self {
+        let sites = std::fs::read_to_string(Self::cache_file())
+            .ok()
+            .and_then(|content| json::from_str::<Vec<Site>>(&content).ok())
+            .filter(|sites| !sites.is_empty())
+            .unwrap_or_else(|| json::from_str(BUNDLED_SITES).unwrap());
+
+        Self { sites }
+    }
+
+    /// Whether the cached catalog is missing or too old.
+    pub fn is_stale() -> bool {
+        let Some(age) = std::fs::metadata(Self::cache_file())
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        else {
+            return true;
+        };
+
+        age > SITES_CACHE_MAX_AGE
+    }
+
+    /// Fetch the catalog from the backend, and update the disk cache when it's the full one.
+    pub async fn refresh(client: &dyn QaBackend) -> Result<Self, ApiError> {
+        let sites = client.get_sites().await?;
+
+        // Sites of imported dumps or recorded responses would hide the others in the next runs.
+        if client.lists_every_site() {
+            // Failing to write the cache is not fatal, it will be fetched again next time.
+            let _ = crate::storage::write_json(&Self::cache_file(), &sites);
+        }
+
+        Ok(Self { sites })
+    }
+
+    pub fn sites(&self) -> &[Site] {
+        &self.sites
+    }
+
+    pub fn find(&self, api_site_parameter: &str) -> Option<&Site> {
+        self.sites
+            .iter()
+            .find(|site| site.api_site_parameter == api_site_parameter)
+    }
+
+    fn cache_file() -> PathBuf {
+        crate::utils::cache_dir().join("sites.json")
+    }
+}
@@ -1,19 +1,34 @@
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
 
+use async_trait::async_trait;
 use reqwest::Url;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json as json;
 
-const API_ENDPOINT: &str = "https://api.stackexchange.com/2.3";
+use super::backend::QaBackend;
+use super::batcher::{QuestionsBatcher, BATCH_WINDOW, MAX_IDS_PER_REQUEST};
+use super::config::ApiConfig;
+use super::error::{ApiError, ApiErrorName};
+use super::images::{self, ImageCache};
+use super::scheduler::{Quota, RequestScheduler};
 
 // API filters to just include fields we need.
 const API_QUESTIONS_FILTER: &str =
     "EElmT9iE*eL20pftmjJrJa1RzdE9QOwek0yS*Tk9VsC59YEekmluvpWi71mN)yEJu00ci5W";
-// const API_SEARCH_FILTER: &str = "";
+// The default filter already includes every field of `QuestionSummary`.
+const API_SEARCH_FILTER: &str = "default";
 
-// When it's not big enough some results might be missing.
+// Maximum allowed page size, more pages are requested when there are more results.
 const API_SITE_PAGESIZE: &str = "100";
 
+// Sites catalog is small, so use the largest allowed page size.
+const API_SITES_PAGESIZE: &str = "100";
+
+// Smaller pages for search results, since more pages can be loaded on demand.
+const API_SEARCH_PAGESIZE: &str = "30";
+
 pub type Id = u32; // Since all operations are in strings not integers.
 pub type Uri = String;
 type Date = i64;
@@ -21,10 +36,12 @@ type Date = i64;
 pub trait DateExt: Sized {
     fn formate_date_time_string(self) -> String;
 
+    fn formate_date_string(self) -> String;
+
     // TODO: Create another method to provide `before 2 years` like format.
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct User {
     pub display_name: String,
     pub link: Option<String>, // Url
@@ -32,7 +49,7 @@ pub struct User {
     pub user_id: Option<Id>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Comment {
     pub body_markdown: Option<String>,
     pub comment_id: Id,
@@ -42,7 +59,7 @@ pub struct Comment {
     pub score: i32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Answer {
     pub answer_id: Id,
     pub body_markdown: String,
@@ -55,7 +72,7 @@ pub struct Answer {
     pub score: i32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Question {
     pub answer_count: u32,
     pub answers: Option<Vec<Answer>>,
@@ -69,66 +86,807 @@ pub struct Question {
     pub owner: User,
     pub question_id: Id,
     pub score: i32,
-    // tags: Vec<String>,
+    // Not included by every filter.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub title: String,
+    pub view_count: u32,
+    /// When the question was cached, if it's a stale copy used because the API is unreachable.
+    #[serde(skip)]
+    pub cached_at: Option<Date>,
+}
+
+impl Question {
+    /// Whether every answer and comment is included, since the first page might be cut off.
+    pub fn is_complete(&self) -> bool {
+        self.has_all_answers()
+            && has_all_comments(&self.comments, self.comment_count)
+            && self
+                .answers
+                .iter()
+                .flatten()
+                .all(|answer| has_all_comments(&answer.comments, answer.comment_count))
+    }
+
+    fn has_all_answers(&self) -> bool {
+        self.answers.as_ref().map_or(0, Vec::len) as u32 >= self.answer_count
+    }
+}
+
+fn has_all_comments(comments: &Option<Vec<Comment>>, comment_count: u32) -> bool {
+    comments.as_ref().map_or(0, Vec::len) as u32 >= comment_count
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BadgeCounts {
+    pub bronze: u32,
+    pub silver: u32,
+    pub gold: u32,
+}
+
+/// Public profile of a user in a single site.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UserProfile {
+    pub badge_counts: BadgeCounts,
+    pub creation_date: Date,
+    pub display_name: String,
+    pub link: String, // Url
+    pub location: Option<String>,
+    pub reputation: u32,
+    pub user_id: Id,
+    pub website_url: Option<String>, // Url
+}
+
+/// A site in the StackExchange network.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Site {
+    pub api_site_parameter: String,
+    pub audience: String,
+    pub icon_url: String,
+    pub name: String,
+    pub site_type: String, // main_site or meta_site
+    pub site_url: String,  // Url
+}
+
+/// A tag of a site with the number of questions tagged with it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Tag {
+    pub name: String,
+    pub count: u32,
+}
+
+/// Short form of a question as returned by the search API.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QuestionSummary {
+    pub answer_count: u32,
+    pub creation_date: Date,
+    pub is_answered: bool,
+    pub last_activity_date: Date,
+    pub owner: User,
+    pub question_id: Id,
+    pub score: i32,
+    pub tags: Vec<String>,
     pub title: String,
     pub view_count: u32,
 }
 
+/// Single page of API results, `has_more` tells if the next page is worth requesting.
+///
+/// Docs: https://api.stackexchange.com/docs/wrapper
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub has_more: bool,
+    // Seconds to wait before requesting the same method again.
+    #[serde(default)]
+    pub backoff: Option<u64>,
+    #[serde(default)]
+    pub quota_max: Option<u32>,
+    #[serde(default)]
+    pub quota_remaining: Option<u32>,
+}
+
+impl<T> Page<T> {
+    fn quota(&self) -> Option<Quota> {
+        Some(Quota {
+            remaining: self.quota_remaining?,
+            max: self.quota_max?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchSort {
+    Activity,
+    Creation,
+    Votes,
+    #[default]
+    Relevance,
+}
+
+impl SearchSort {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Activity => "activity",
+            Self::Creation => "creation",
+            Self::Votes => "votes",
+            Self::Relevance => "relevance",
+        }
+    }
+}
+
+impl FromStr for SearchSort {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "activity" => Ok(Self::Activity),
+            "creation" | "newest" => Ok(Self::Creation),
+            "votes" | "score" => Ok(Self::Votes),
+            "relevance" => Ok(Self::Relevance),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Parameters of the advanced search, empty fields are not sent to the API.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Free text matched against titles and bodies.
+    pub q: String,
+    pub title: String,
+    pub body: String,
+    pub tagged: Vec<String>,
+    /// Only questions with (or without) an accepted answer.
+    pub accepted: Option<bool>,
+    /// Minimum number of answers.
+    pub answers: Option<u32>,
+    pub closed: Option<bool>,
+    /// Only questions owned by this user.
+    pub user: Option<Id>,
+    pub sort: SearchSort,
+}
+
+impl From<&str> for SearchQuery {
+    /// Parse a search term written with a subset of the web search syntax.
+    ///
+    /// Ref: https://stackoverflow.com/help/searching
+    ///
+    /// For example: `[rust] [gtk] title:tabs answers:2 hasaccepted:yes closed:no sort:votes`
+    fn from(search_term: &str) -> Self {
+        fn push_word(field: &mut String, word: &str) {
+            if !field.is_empty() {
+                field.push(' ');
+            }
+            field.push_str(word);
+        }
+
+        fn parse_bool(value: &str) -> Option<bool> {
+            match value {
+                "yes" | "true" | "1" => Some(true),
+                "no" | "false" | "0" => Some(false),
+                _ => None,
+            }
+        }
+
+        let mut query = Self::default();
+
+        for word in search_term.split_whitespace() {
+            if let Some(tag) = word
+                .strip_prefix('[')
+                .and_then(|w| w.strip_suffix(']'))
+                .filter(|tag| !tag.is_empty())
+            {
+                query.tagged.push(tag.to_owned());
+                continue;
+            }
+
+            // Unknown operators and invalid values are kept as free text.
+            let handled = match word.split_once(':') {
+                Some(("title", value)) if !value.is_empty() => {
+                    push_word(&mut query.title, value);
+                    true
+                }
+                Some(("body", value)) if !value.is_empty() => {
+                    push_word(&mut query.body, value);
+                    true
+                }
+                Some(("hasaccepted" | "accepted", value)) => parse_bool(value)
+                    .map(|value| query.accepted = Some(value))
+                    .is_some(),
+                Some(("closed", value)) => parse_bool(value)
+                    .map(|value| query.closed = Some(value))
+                    .is_some(),
+                Some(("answers", value)) => value
+                    .parse()
+                    .map(|value| query.answers = Some(value))
+                    .is_ok(),
+                Some(("user", value)) => {
+                    value.parse().map(|value| query.user = Some(value)).is_ok()
+                }
+                Some(("sort", value)) => value.parse().map(|value| query.sort = value).is_ok(),
+                _ => false,
+            };
+
+            if !handled {
+                push_word(&mut query.q, word);
+            }
+        }
+
+        query
+    }
+}
+
+impl std::fmt::Display for SearchQuery {
+    /// Human readable form, used as a title for search pages.
+    ///
+    /// It's written with the same syntax, so it could be parsed back.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = self
+            .tagged
+            .iter()
+            .map(|tag| format!("[{tag}]"))
+            .collect::<Vec<String>>();
+
+        parts.extend(
+            self.title
+                .split_whitespace()
+                .map(|word| format!("title:{word}")),
+        );
+        parts.extend(
+            self.body
+                .split_whitespace()
+                .map(|word| format!("body:{word}")),
+        );
+
+        if let Some(accepted) = self.accepted {
+            parts.push(format!(
+                "hasaccepted:{}",
+                if accepted { "yes" } else { "no" }
+            ));
+        }
+        if let Some(answers) = self.answers {
+            parts.push(format!("answers:{answers}"));
+        }
+        if let Some(closed) = self.closed {
+            parts.push(format!("closed:{}", if closed { "yes" } else { "no" }));
+        }
+        if let Some(user) = self.user {
+            parts.push(format!("user:{user}"));
+        }
+        if self.sort != SearchSort::default() {
+            parts.push(format!("sort:{}", self.sort.as_str()));
+        }
+        if !self.q.is_empty() {
+            parts.push(self.q.clone());
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[derive(Clone)]
 pub struct StackExchange {
-    reqwest_client: reqwest::Client,
+    // Every request fails with the error when the settings are invalid.
+    reqwest_client: Result<reqwest::Client, ApiError>,
+    config: Arc<ApiConfig>,
+    // Shared between all clones, since throttling is applied per IP.
+    scheduler: Arc<RequestScheduler>,
+    batcher: Arc<QuestionsBatcher>,
+    images: Arc<ImageCache>,
 }
 
 impl StackExchange {
     pub fn new() -> Self {
+        Self::with_config(ApiConfig::default())
+    }
+
+    /// Every request goes to the API, see `CachingBackend` to reuse responses for the config's
+    /// `cache_ttl`.
+    pub fn with_config(config: ApiConfig) -> Self {
         Self {
-            reqwest_client: reqwest::Client::builder().gzip(true).build().unwrap(),
+            reqwest_client: config.http_client(),
+            config: Arc::new(config),
+            scheduler: Arc::new(RequestScheduler::new()),
+            batcher: Arc::new(QuestionsBatcher::new()),
+            images: Arc::new(ImageCache::new(crate::utils::cache_dir().join("images"))),
         }
     }
 
-    pub async fn get_questions_from_uri(&self, uri: &str) -> Result<Vec<Question>, String> {
-        // Accept uris of form: stackbloatless://{site}/{questions ids}
-        // For example: stackbloatless://stackoverflow/123456;7891011;121314
-        let uri = Url::parse(uri).unwrap();
+    /// Last known daily requests quota.
+    pub fn quota(&self) -> Option<Quota> {
+        self.scheduler.quota()
+    }
+
+    // Lookups of the same site that happen within `BATCH_WINDOW` are merged together, to save
+    // quota when many questions are opened at once.
+    async fn get_batched_questions(
+        &self,
+        site: &str,
+        ids: &[Id],
+    ) -> Result<Vec<Question>, ApiError> {
+        let (receiver, is_leader) = self.batcher.enqueue(site, ids);
+
+        // The batch is sent from its own task, since the leader could be cancelled (e.g. when its
+        // tab is closed) while other requesters are still waiting for it.
+        if is_leader {
+            let client = self.clone();
+            let site = site.to_owned();
+
+            tokio::spawn(async move {
+                tokio::time::sleep(BATCH_WINDOW).await;
 
-        // TODO: Check if shame is stackbloatless or not.
-        // TODO: Check if questions ids are valid.
+                let batch = client.batcher.take(&site);
+                let result = client.fetch_questions(&site, &batch.ids).await;
+                batch.dispatch(result);
+            });
+        }
 
-        self.get_questions(uri.domain().unwrap(), uri.path()).await
+        receiver
+            .await
+            .unwrap_or_else(|_| Err(ApiError::Transport("request was cancelled".to_owned())))
     }
 
-    async fn get_questions(&self, site: &str, ids: &str) -> Result<Vec<Question>, String> {
+    async fn fetch_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
         // Docs: https://api.stackexchange.com/docs/questions-by-ids
-        //
-        // `ids` are in form of a path with multiple ids separated by ;
-        // For example: /123456;78910;111213
-        let mut url = Url::parse(API_ENDPOINT)
-            .unwrap()
-            .join(&format!("questions{}", ids))
-            .unwrap();
+        let mut questions = Vec::with_capacity(ids.len());
+
+        for chunk in ids.chunks(MAX_IDS_PER_REQUEST) {
+            let ids = chunk
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(";");
+
+            let mut url = self.method_url(&format!("questions/{ids}"))?;
+
+            url.set_query(Some(&format!(
+                "site={site}&filter={API_QUESTIONS_FILTER}&pagesize={API_SITE_PAGESIZE}"
+            )));
+
+            questions.extend(self.get_page::<Question>("questions", url).await?.items);
+        }
+
+        Ok(questions)
+    }
+
+    // Get all answers of a question, sorted by votes.
+    async fn fetch_answers(&self, site: &str, question_id: Id) -> Result<Vec<Answer>, ApiError> {
+        // Docs: https://api.stackexchange.com/docs/answers-on-questions
+        let mut url = self.method_url(&format!("questions/{question_id}/answers"))?;
 
         url.set_query(Some(&format!(
-            "site={site}&filter={API_QUESTIONS_FILTER}&pagesize={API_SITE_PAGESIZE}"
+            "site={site}&filter={API_QUESTIONS_FILTER}&pagesize={API_SITE_PAGESIZE}&sort=votes&order=desc"
         )));
 
-        let res = self.reqwest_client.get(url).send().await.unwrap();
+        self.get_all_pages("questions/answers", url).await
+    }
+
+    // Get all comments of some posts (questions or answers), from oldest to newest.
+    async fn fetch_posts_comments(
+        &self,
+        site: &str,
+        posts_ids: &[Id],
+    ) -> Result<Vec<Comment>, ApiError> {
+        // Docs: https://api.stackexchange.com/docs/comments-on-posts
+        let mut comments = Vec::new();
+
+        for chunk in posts_ids.chunks(MAX_IDS_PER_REQUEST) {
+            let ids = chunk
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<String>>()
+                .join(";");
+
+            let mut url = self.method_url(&format!("posts/{ids}/comments"))?;
+
+            url.set_query(Some(&format!(
+                "site={site}&filter={API_QUESTIONS_FILTER}&pagesize={API_SITE_PAGESIZE}&sort=creation&order=asc"
+            )));
+
+            comments.extend(self.get_all_pages("posts/comments", url).await?);
+        }
+
+        Ok(comments)
+    }
+
+    // Fetch comments of the question and its answers that didn't fit in their first page, with
+    // the same requests for all of them.
+    async fn complete_comments(&self, site: &str, question: &mut Question) -> Result<(), ApiError> {
+        let mut posts_ids = Vec::new();
+        if !has_all_comments(&question.comments, question.comment_count) {
+            posts_ids.push(question.question_id);
+        }
+        for answer in question.answers.iter().flatten() {
+            if !has_all_comments(&answer.comments, answer.comment_count) {
+                posts_ids.push(answer.answer_id);
+            }
+        }
 
-        let value: json::Value = res.json().await.unwrap();
+        if posts_ids.is_empty() {
+            return Ok(());
+        }
+
+        // Posts whose comments were all deleted meanwhile have none.
+        let mut comments = posts_ids
+            .iter()
+            .map(|post_id| (*post_id, Vec::new()))
+            .collect::<HashMap<Id, Vec<Comment>>>();
+
+        for comment in self.fetch_posts_comments(site, &posts_ids).await? {
+            if let Some(post_comments) = comments.get_mut(&comment.post_id) {
+                post_comments.push(comment);
+            }
+        }
+
+        if let Some(question_comments) = comments.remove(&question.question_id) {
+            question.comments = Some(question_comments);
+        }
+        for answer in question.answers.iter_mut().flatten() {
+            if let Some(answer_comments) = comments.remove(&answer.answer_id) {
+                answer.comments = Some(answer_comments);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Follow `has_more` to collect items from every page.
+    async fn get_all_pages<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        url: Url,
+    ) -> Result<Vec<T>, ApiError> {
+        let mut items = Vec::new();
+
+        for page in 1.. {
+            let mut page_url = url.clone();
+            page_url
+                .query_pairs_mut()
+                .append_pair("page", &page.to_string());
+
+            let page = self.get_page::<T>(method, page_url).await?;
+            items.extend(page.items);
+
+            if !page.has_more {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    fn method_url(&self, method: &str) -> Result<Url, ApiError> {
+        self.config.method_url(method.split('/'))
+    }
+
+    /// Send a request through the scheduler, retrying it when it fails for a transient reason.
+    ///
+    /// `method` identifies the API method for applying the `backoff` it might return.
+    async fn get_page<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        url: Url,
+    ) -> Result<Page<T>, ApiError> {
+        let mut attempt = 0;
+
+        loop {
+            self.scheduler.wait_turn(method).await?;
+
+            match self.send_request::<T>(url.clone()).await {
+                Ok(page) => {
+                    self.scheduler
+                        .record_response(method, page.backoff, page.quota());
+
+                    return Ok(page);
+                }
+                Err(error) => match self.scheduler.retry_delay(method, &error, attempt) {
+                    Some(delay) => {
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(error),
+                },
+            }
+        }
+    }
+
+    async fn send_request<T: DeserializeOwned>(&self, mut url: Url) -> Result<Page<T>, ApiError> {
+        if let Some(key) = &self.config.key {
+            url.query_pairs_mut().append_pair("key", key);
+        }
+
+        let reqwest_client = self.reqwest_client.as_ref().map_err(Clone::clone)?;
+
+        let res = reqwest_client.get(url).send().await?;
+
+        let status = res.status();
+        let body = res.bytes().await?;
+
+        // Errors are reported in the body along with a non-success status.
+        // Docs: https://api.stackexchange.com/docs/error-handling
+        let value = match json::from_slice::<json::Value>(&body) {
+            Ok(value) => value,
+            Err(_) if !status.is_success() => return Err(ApiError::HttpStatus(status.as_u16())),
+            Err(error) => return Err(error.into()),
+        };
 
-        // TODO: Handle backend errors
         if let Some(error_id) = value.get("error_id") {
-            // value.get("error_name");
-            // value.get("error_message");
-            return Err(error_id.to_string());
+            return Err(ApiError::Api {
+                id: error_id.as_u64().unwrap_or_default() as u32,
+                name: ApiErrorName::from(
+                    value
+                        .get("error_name")
+                        .and_then(|name| name.as_str())
+                        .unwrap_or_default(),
+                ),
+                message: value
+                    .get("error_message")
+                    .and_then(|message| message.as_str())
+                    .unwrap_or_default()
+                    .to_owned(),
+            });
+        }
+
+        if !status.is_success() {
+            return Err(ApiError::HttpStatus(status.as_u16()));
+        }
+
+        Ok(json::from_value::<Page<T>>(value)?)
+    }
+}
+
+#[async_trait]
+impl QaBackend for StackExchange {
+    async fn get_questions(&self, site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+        self.get_batched_questions(site, ids).await
+    }
+
+    /// Fetch answers and comments that didn't fit in the question's first page.
+    async fn complete_question(
+        &self,
+        site: &str,
+        mut question: Question,
+    ) -> Result<Question, ApiError> {
+        if !question.has_all_answers() {
+            question.answers = Some(self.fetch_answers(site, question.question_id).await?);
+        }
+
+        self.complete_comments(site, &mut question).await?;
+
+        Ok(question)
+    }
+
+    async fn search_questions(
+        &self,
+        site: &str,
+        query: &SearchQuery,
+        page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError> {
+        // Docs: https://api.stackexchange.com/docs/advanced-search
+        let mut url = self.method_url("search/advanced")?;
+
+        {
+            let mut pairs = url.query_pairs_mut();
+
+            pairs
+                .append_pair("site", site)
+                .append_pair("filter", API_SEARCH_FILTER)
+                .append_pair("pagesize", API_SEARCH_PAGESIZE)
+                .append_pair("page", &page.to_string())
+                .append_pair("sort", query.sort.as_str())
+                .append_pair("order", "desc");
+
+            for (key, value) in [
+                ("q", &query.q),
+                ("title", &query.title),
+                ("body", &query.body),
+            ] {
+                if !value.is_empty() {
+                    pairs.append_pair(key, value);
+                }
+            }
+
+            if !query.tagged.is_empty() {
+                pairs.append_pair("tagged", &query.tagged.join(";"));
+            }
+            if let Some(accepted) = query.accepted {
+                pairs.append_pair("accepted", &accepted.to_string());
+            }
+            if let Some(answers) = query.answers {
+                pairs.append_pair("answers", &answers.to_string());
+            }
+            if let Some(closed) = query.closed {
+                pairs.append_pair("closed", &closed.to_string());
+            }
+            if let Some(user) = query.user {
+                pairs.append_pair("user", &user.to_string());
+            }
         }
 
-        Ok(json::from_value::<Vec<Question>>(value.get("items").unwrap().to_owned()).unwrap())
+        self.get_page("search/advanced", url).await
+    }
+
+    /// Get all comments of a post (question or answer), from oldest to newest.
+    async fn get_post_comments(&self, site: &str, post_id: Id) -> Result<Vec<Comment>, ApiError> {
+        self.fetch_posts_comments(site, &[post_id]).await
     }
 
-    // TODO: Search function
-    // fn search_questions() {
-    //     // Docs: https://api.stackexchange.com/docs/search
-    //     todo!();
-    // }
+    /// Get a user profile, `None` when the user doesn't exist.
+    async fn get_user(&self, site: &str, user_id: Id) -> Result<Option<UserProfile>, ApiError> {
+        // Docs: https://api.stackexchange.com/docs/users-by-ids
+        let mut url = self.method_url(&format!("users/{user_id}"))?;
+
+        url.set_query(Some(&format!("site={site}&filter=default")));
+
+        Ok(self
+            .get_page::<UserProfile>("users", url)
+            .await?
+            .items
+            .pop()
+            .map(|mut user| {
+                user.display_name = decode_html_entities(&user.display_name);
+                user.location = user.location.as_deref().map(decode_html_entities);
+                user
+            }))
+    }
+
+    /// Get the id of the question an answer belongs to, `None` when the answer doesn't exist.
+    async fn get_answer_question_id(
+        &self,
+        site: &str,
+        answer_id: Id,
+    ) -> Result<Option<Id>, ApiError> {
+        // Docs: https://api.stackexchange.com/docs/answers-by-ids
+        #[derive(Deserialize)]
+        struct AnswerParent {
+            question_id: Id,
+        }
+
+        let mut url = self.method_url(&format!("answers/{answer_id}"))?;
+
+        url.set_query(Some(&format!("site={site}&filter=default")));
+
+        Ok(self
+            .get_page::<AnswerParent>("answers", url)
+            .await?
+            .items
+            .first()
+            .map(|answer| answer.question_id))
+    }
+
+    async fn get_tag(&self, site: &str, name: &str) -> Result<Option<Tag>, ApiError> {
+        // Docs: https://api.stackexchange.com/docs/tags-by-name
+        let mut url = self.config.method_url(["tags", name, "info"])?;
+
+        url.set_query(Some(&format!("site={site}&filter=default")));
+
+        Ok(self.get_page::<Tag>("tags/info", url).await?.items.pop())
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>, ApiError> {
+        // Docs: https://api.stackexchange.com/docs/sites
+        let mut url = self.method_url("sites")?;
+
+        url.set_query(Some(&format!(
+            "filter=default&pagesize={API_SITES_PAGESIZE}"
+        )));
+
+        let sites = self.get_all_pages::<Site>("sites", url).await?;
+
+        Ok(sites
+            .into_iter()
+            .map(|mut site| {
+                // Names might contain html entities, e.g. `Stack Overflow en espa&#241;ol`.
+                site.name = decode_html_entities(&site.name);
+                site.audience = decode_html_entities(&site.audience);
+                site
+            })
+            .collect())
+    }
+
+    async fn get_image(&self, url: &str) -> Result<Vec<u8>, ApiError> {
+        // Cached images are shown even when the network settings are invalid.
+        if let Some(image) = self.images.get(url) {
+            return Ok(image);
+        }
+
+        let reqwest_client = self.reqwest_client.as_ref().map_err(Clone::clone)?;
+
+        let image = images::download(reqwest_client, url).await?;
+        self.images.put(url, &image);
+
+        Ok(image)
+    }
+
+    fn subscribe_quota(&self) -> Option<tokio::sync::watch::Receiver<Option<Quota>>> {
+        Some(self.scheduler.subscribe_quota())
+    }
+
+    fn lists_every_site(&self) -> bool {
+        true
+    }
+
+    async fn list_tag_questions(
+        &self,
+        site: &str,
+        tag: &str,
+        limit: u32,
+    ) -> Result<Vec<Id>, ApiError> {
+        // Docs: https://api.stackexchange.com/docs/questions
+        #[derive(Deserialize)]
+        struct QuestionId {
+            question_id: Id,
+        }
+
+        let mut ids = Vec::new();
+
+        for page in 1.. {
+            let mut url = self.method_url("questions")?;
+            url.query_pairs_mut()
+                .append_pair("site", site)
+                .append_pair("tagged", tag)
+                .append_pair("filter", "default")
+                .append_pair("pagesize", &MAX_IDS_PER_REQUEST.to_string())
+                .append_pair("page", &page.to_string())
+                .append_pair("sort", "votes")
+                .append_pair("order", "desc");
+
+            let page = self.get_page::<QuestionId>("questions", url).await?;
+            ids.extend(page.items.iter().map(|question| question.question_id));
+
+            if !page.has_more || ids.len() >= limit as usize {
+                break;
+            }
+        }
+
+        ids.truncate(limit as usize);
+
+        Ok(ids)
+    }
+}
+
+/// Decode the html entities that the API uses to escape plain text fields.
+pub fn decode_html_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest
+            .find(';')
+            .map(|end| &rest[1..end])
+            .and_then(|entity| match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                    .and_then(|code| code.ok())
+                    .and_then(char::from_u32),
+            });
+
+        match entity {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[rest.find(';').unwrap() + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
 }
 
 impl DateExt for Date {
@@ -155,4 +913,103 @@ impl DateExt for Date {
             )
             .unwrap()
     }
+
+    fn formate_date_string(self) -> String {
+        use icu::calendar::DateTime;
+        use icu::datetime::{options::length, DateFormatter};
+        use icu::locid::Locale;
+
+        let locale = Locale::from_str(&crate::utils::SYSTEM_TIME_LOCALE)
+            .unwrap()
+            .into();
+
+        let formatter = DateFormatter::try_new_with_length(&locale, length::Date::Full).unwrap();
+
+        formatter
+            .format_to_string(
+                &DateTime::from_minutes_since_local_unix_epoch((self / 60) as i32)
+                    .date
+                    .to_any(),
+            )
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_search_operators() {
+        assert_eq!(
+            SearchQuery::from(
+                "[rust] title:tabs gtk [gtk-rs] body:relm4 answers:2 hasaccepted:yes closed:no \
+                 user:42 sort:newest"
+            ),
+            SearchQuery {
+                q: "gtk".to_owned(),
+                title: "tabs".to_owned(),
+                body: "relm4".to_owned(),
+                tagged: vec!["rust".to_owned(), "gtk-rs".to_owned()],
+                accepted: Some(true),
+                answers: Some(2),
+                closed: Some(false),
+                user: Some(42),
+                sort: SearchSort::Creation,
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_operators_and_invalid_values_as_text() {
+        assert_eq!(
+            SearchQuery::from("is:answer score:5 answers:many closed:maybe title: [] sort:best"),
+            SearchQuery {
+                q: "is:answer score:5 answers:many closed:maybe title: [] sort:best".to_owned(),
+                ..SearchQuery::default()
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_quotes_as_text() {
+        for (search_term, q) in [
+            ("\"exact  phrase\" [rust]", "\"exact phrase\""),
+            ("\"unterminated quote", "\"unterminated quote"),
+            ("title:\"tabs", ""),
+        ] {
+            assert_eq!(SearchQuery::from(search_term).q, q, "{search_term}");
+        }
+    }
+
+    #[test]
+    fn parses_empty_input() {
+        for search_term in ["", "   \t\n"] {
+            assert_eq!(SearchQuery::from(search_term), SearchQuery::default());
+        }
+
+        assert_eq!(SearchQuery::default().to_string(), "");
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        let query = SearchQuery {
+            q: "free text".to_owned(),
+            title: "two words".to_owned(),
+            body: "body".to_owned(),
+            tagged: vec!["c#".to_owned(), "rust".to_owned()],
+            accepted: Some(false),
+            answers: Some(0),
+            closed: Some(true),
+            user: Some(7),
+            sort: SearchSort::Votes,
+        };
+
+        assert_eq!(
+            query.to_string(),
+            "[c#] [rust] title:two title:words body:body hasaccepted:no answers:0 closed:yes \
+             user:7 sort:votes free text"
+        );
+        assert_eq!(SearchQuery::from(query.to_string().as_str()), query);
+    }
 }
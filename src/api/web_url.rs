@@ -0,0 +1,309 @@
+use reqwest::Url;
+
+use super::error::{ApiError, UriError};
+use super::stackexchange::Id;
+
+// Hosts of sites that don't live under `stackexchange.com`, with their meta and localized
+// subdomains (ru.stackoverflow.com, meta.superuser.com, ...).
+const STANDALONE_HOSTS: [&str; 6] = [
+    "stackoverflow.com",
+    "superuser.com",
+    "serverfault.com",
+    "askubuntu.com",
+    "stackapps.com",
+    "mathoverflow.net",
+];
+
+const NETWORK_HOST: &str = "stackexchange.com";
+
+/// Post that a web link points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebTarget {
+    Question {
+        question_id: Id,
+        answer_id: Option<Id>,
+        comment_id: Option<Id>,
+    },
+    /// Short answer links (/a/{id}) don't include the question id.
+    Answer {
+        answer_id: Id,
+        comment_id: Option<Id>,
+    },
+}
+
+/// A StackExchange web link resolved to its API site parameter and post.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebLink {
+    pub site: String,
+    pub target: WebTarget,
+}
+
+/// Whether the uri should be resolved as a web link, instead of the custom scheme.
+pub fn is_web_url(uri: &str) -> bool {
+    uri.starts_with("https://") || uri.starts_with("http://")
+}
+
+/// Resolve links like:
+///
+/// - https://stackoverflow.com/questions/123/slug
+/// - https://stackoverflow.com/questions/123/slug/456#456
+/// - https://superuser.com/questions/123/slug#comment789_456
+/// - https://askubuntu.com/q/123/321
+/// - https://unix.stackexchange.com/a/456/321
+pub fn resolve(uri: &str) -> Result<WebLink, ApiError> {
+    let invalid_uri = |reason| ApiError::invalid_uri(uri, reason);
+
+    let url = Url::parse(uri).map_err(|_| invalid_uri(UriError::Malformed))?;
+
+    if !matches!(url.scheme(), "https" | "http") {
+        return Err(invalid_uri(UriError::UnsupportedScheme(
+            url.scheme().to_owned(),
+        )));
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    let site =
+        site_from_host(host).ok_or_else(|| invalid_uri(UriError::UnknownSite(host.to_owned())))?;
+
+    let unknown_route = || invalid_uri(UriError::UnknownRoute(url.path().to_owned()));
+
+    let mut segments = url.path_segments().ok_or_else(unknown_route)?;
+    let kind = segments.next().unwrap_or_default();
+    let post_id = segments.next().ok_or_else(unknown_route)?;
+    let post_id = post_id
+        .parse::<Id>()
+        .map_err(|_| invalid_uri(UriError::InvalidId(post_id.to_owned())))?;
+
+    let anchor = url.fragment().map(parse_anchor).unwrap_or_default();
+
+    let target = match kind {
+        "questions" | "q" => {
+            // Answers links have the answer id after the slug.
+            let path_answer_id = match kind {
+                "questions" => segments.nth(1).and_then(|id| id.parse::<Id>().ok()),
+                _ => None,
+            };
+
+            WebTarget::Question {
+                question_id: post_id,
+                answer_id: anchor
+                    .post_id
+                    .filter(|anchor_post_id| *anchor_post_id != post_id)
+                    .or(path_answer_id),
+                comment_id: anchor.comment_id,
+            }
+        }
+        "a" => WebTarget::Answer {
+            answer_id: post_id,
+            comment_id: anchor.comment_id,
+        },
+        _ => return Err(unknown_route()),
+    };
+
+    Ok(WebLink { site, target })
+}
+
+/// Map a site host to its API site parameter.
+///
+/// For example: stackoverflow.com -> stackoverflow, meta.superuser.com -> meta.superuser,
+/// unix.stackexchange.com -> unix, ru.stackoverflow.com -> ru.stackoverflow,
+/// mathoverflow.net -> mathoverflow.net
+pub fn site_from_host(host: &str) -> Option<String> {
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    if host == NETWORK_HOST {
+        return None;
+    }
+
+    if let Some(site) = host.strip_suffix(&format!(".{NETWORK_HOST}")) {
+        // meta.stackexchange.com is `meta`, the same as other sites.
+        return Some(site.to_owned());
+    }
+
+    let standalone_host = STANDALONE_HOSTS.iter().find(|standalone_host| {
+        host == **standalone_host || host.ends_with(&format!(".{standalone_host}"))
+    })?;
+
+    // The API uses the full domain of sites outside `.com`.
+    if standalone_host.ends_with(".com") {
+        host.strip_suffix(".com").map(str::to_owned)
+    } else {
+        Some(host.to_owned())
+    }
+}
+
+#[derive(Default)]
+pub(super) struct Anchor {
+    pub post_id: Option<Id>,
+    pub comment_id: Option<Id>,
+}
+
+// Answers anchors are `#{answer id}` or `#answer-{answer id}`,
+// and comments anchors are `#comment{comment id}_{post id}`.
+pub(super) fn parse_anchor(fragment: &str) -> Anchor {
+    if let Some(comment) = fragment.strip_prefix("comment") {
+        let (comment_id, post_id) = comment.split_once('_').unwrap_or((comment, ""));
+
+        Anchor {
+            post_id: post_id.parse().ok(),
+            comment_id: comment_id.parse().ok(),
+        }
+    } else {
+        Anchor {
+            post_id: fragment
+                .strip_prefix("answer-")
+                .unwrap_or(fragment)
+                .parse()
+                .ok(),
+            comment_id: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve, site_from_host, WebLink, WebTarget};
+    use crate::api::error::{ApiError, UriError};
+    use crate::api::stackexchange::Id;
+
+    fn question(
+        site: &str,
+        question_id: Id,
+        answer_id: Option<Id>,
+        comment_id: Option<Id>,
+    ) -> WebLink {
+        WebLink {
+            site: site.to_owned(),
+            target: WebTarget::Question {
+                question_id,
+                answer_id,
+                comment_id,
+            },
+        }
+    }
+
+    fn rejection(uri: &str) -> UriError {
+        match resolve(uri) {
+            Err(ApiError::InvalidUri { reason, .. }) => reason,
+            other => panic!("{uri} wasn't rejected: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn maps_hosts_to_sites() {
+        for (host, site) in [
+            ("stackoverflow.com", "stackoverflow"),
+            ("www.stackoverflow.com", "stackoverflow"),
+            ("unix.stackexchange.com", "unix"),
+            ("Unix.StackExchange.com", "unix"),
+            ("meta.stackexchange.com", "meta"),
+            ("meta.superuser.com", "meta.superuser"),
+            ("ru.stackoverflow.com", "ru.stackoverflow"),
+            ("askubuntu.com", "askubuntu"),
+            ("mathoverflow.net", "mathoverflow.net"),
+            ("meta.mathoverflow.net", "meta.mathoverflow.net"),
+        ] {
+            assert_eq!(site_from_host(host).as_deref(), Some(site), "{host}");
+        }
+
+        for host in [
+            "",
+            "stackexchange.com",
+            "example.com",
+            "notstackoverflow.com",
+            "stackoverflow.com.example.com",
+        ] {
+            assert_eq!(site_from_host(host), None, "{host}");
+        }
+    }
+
+    #[test]
+    fn resolves_question_links() {
+        for (uri, link) in [
+            (
+                "https://stackoverflow.com/questions/123/slug",
+                question("stackoverflow", 123, None, None),
+            ),
+            (
+                "http://stackoverflow.com/questions/123",
+                question("stackoverflow", 123, None, None),
+            ),
+            (
+                "https://stackoverflow.com/questions/123/slug/456#456",
+                question("stackoverflow", 123, Some(456), None),
+            ),
+            (
+                "https://stackoverflow.com/questions/123/slug#answer-456",
+                question("stackoverflow", 123, Some(456), None),
+            ),
+            (
+                "https://superuser.com/questions/123/slug#comment789_456",
+                question("superuser", 123, Some(456), Some(789)),
+            ),
+            (
+                "https://superuser.com/questions/123/slug#comment789_123",
+                question("superuser", 123, None, Some(789)),
+            ),
+            (
+                "https://askubuntu.com/q/123/321",
+                question("askubuntu", 123, None, None),
+            ),
+            (
+                "https://ru.stackoverflow.com/q/123",
+                question("ru.stackoverflow", 123, None, None),
+            ),
+        ] {
+            assert_eq!(resolve(uri).unwrap(), link, "{uri}");
+        }
+    }
+
+    #[test]
+    fn resolves_answer_links() {
+        assert_eq!(
+            resolve("https://unix.stackexchange.com/a/456/321").unwrap(),
+            WebLink {
+                site: "unix".to_owned(),
+                target: WebTarget::Answer {
+                    answer_id: 456,
+                    comment_id: None,
+                },
+            }
+        );
+        assert_eq!(
+            resolve("https://mathoverflow.net/a/456#comment789_456").unwrap(),
+            WebLink {
+                site: "mathoverflow.net".to_owned(),
+                target: WebTarget::Answer {
+                    answer_id: 456,
+                    comment_id: Some(789),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_other_links() {
+        assert_eq!(rejection("https://"), UriError::Malformed);
+        assert_eq!(
+            rejection("ftp://stackoverflow.com/q/123"),
+            UriError::UnsupportedScheme("ftp".to_owned())
+        );
+        assert_eq!(
+            rejection("https://example.com/questions/123"),
+            UriError::UnknownSite("example.com".to_owned())
+        );
+        assert_eq!(
+            rejection("https://stackoverflow.com/"),
+            UriError::UnknownRoute("/".to_owned())
+        );
+        assert_eq!(
+            rejection("https://stackoverflow.com/users/123"),
+            UriError::UnknownRoute("/users/123".to_owned())
+        );
+        assert_eq!(
+            rejection("https://stackoverflow.com/questions/tagged/rust"),
+            UriError::InvalidId("tagged".to_owned())
+        );
+    }
+}
@@ -0,0 +1,510 @@
+use relm4::{
+    adw::{self, prelude::*},
+    prelude::*,
+};
+use relm4_icons::icon_names;
+
+use super::main_window::BOOKMARKS;
+use super::markdown2gtk::MarkdownView;
+use crate::api::router::{AppUri, Route};
+use crate::api::stackexchange::{DateExt, Id, Uri};
+use crate::fl;
+use crate::storage::bookmarks::{Bookmark, BookmarkStore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BookmarksSort {
+    Newest,
+    Title,
+    Score,
+}
+
+// Same order of the sort drop down items.
+const SORTS: [BookmarksSort; 3] = [
+    BookmarksSort::Newest,
+    BookmarksSort::Title,
+    BookmarksSort::Score,
+];
+
+/// Searchable list of bookmarks in the sidebar.
+pub struct BookmarksPageModel {
+    bookmarks: Vec<Bookmark>,
+    folders: Vec<String>,
+    filter_text: String,
+    // `None` to show bookmarks of all folders.
+    folder_filter: Option<String>,
+    sort: BookmarksSort,
+    // Bookmarks in the same order of the list rows.
+    shown: Vec<Bookmark>,
+}
+
+pub struct BookmarksPageWidgets {
+    folder_drop_down: gtk::DropDown,
+    folders_list: gtk::StringList,
+    folder_handler: gtk::glib::SignalHandlerId,
+    bookmarks_list: gtk::ListBox,
+    content_stack: gtk::Stack,
+}
+
+#[derive(Debug)]
+pub enum BookmarksPageInput {
+    BookmarksChanged(Vec<Bookmark>, Vec<String>),
+    Filter(String),
+    FilterFolder(u32),
+    Sort(u32),
+    Open(usize),
+    Edit(Bookmark),
+    Remove(usize),
+}
+
+#[derive(Debug)]
+pub enum BookmarksPageOutput {
+    RequestPagesByUri(Uri),
+    UpdateBookmark(Bookmark),
+    RemoveBookmark(String, Id),
+}
+
+impl Component for BookmarksPageModel {
+    type CommandOutput = ();
+    type Input = BookmarksPageInput;
+    type Output = BookmarksPageOutput;
+    type Init = ();
+    type Root = gtk::Box;
+    type Widgets = BookmarksPageWidgets;
+
+    fn init_root() -> Self::Root {
+        gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(5)
+            .build()
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let (bookmarks, folders) = {
+            let store = BOOKMARKS.read();
+            (store.bookmarks().to_vec(), store.folders())
+        };
+
+        let mut model = BookmarksPageModel {
+            bookmarks,
+            folders,
+            filter_text: String::new(),
+            folder_filter: None,
+            sort: BookmarksSort::Newest,
+            shown: Vec::new(),
+        };
+
+        // Filter and sort controls
+        let (folder_drop_down, folders_list, folder_handler) = {
+            let controls_layout = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(5)
+                .margin_top(5)
+                .margin_start(5)
+                .margin_end(5)
+                .build();
+            root.append(&controls_layout);
+
+            let filter_entry = gtk::SearchEntry::builder()
+                .placeholder_text(fl!("bookmarks-filter-placeholder"))
+                .hexpand(true)
+                .build();
+            controls_layout.append(&filter_entry);
+
+            filter_entry.connect_search_changed(gtk::glib::clone!(@strong sender => move |entry| {
+                sender.input(BookmarksPageInput::Filter(entry.text().to_string()));
+            }));
+
+            let sort_drop_down = gtk::DropDown::from_strings(&[
+                &fl!("bookmarks-sort-newest"),
+                &fl!("bookmarks-sort-title"),
+                &fl!("bookmarks-sort-score"),
+            ]);
+            sort_drop_down.set_tooltip_text(Some(&fl!("bookmarks-sort-tooltip")));
+            controls_layout.append(&sort_drop_down);
+
+            sort_drop_down.connect_selected_notify(
+                gtk::glib::clone!(@strong sender => move |drop_down| {
+                    sender.input(BookmarksPageInput::Sort(drop_down.selected()));
+                }),
+            );
+
+            // The first item is for all folders.
+            let folders_list = gtk::StringList::new(&[&fl!("bookmarks-all-folders")]);
+            for folder in &model.folders {
+                folders_list.append(folder);
+            }
+
+            let folder_drop_down = gtk::DropDown::builder()
+                .model(&folders_list)
+                .tooltip_text(fl!("bookmarks-folder-tooltip"))
+                .margin_start(5)
+                .margin_end(5)
+                .build();
+            root.append(&folder_drop_down);
+
+            let folder_handler = folder_drop_down.connect_selected_notify(
+                gtk::glib::clone!(@strong sender => move |drop_down| {
+                    sender.input(BookmarksPageInput::FilterFolder(drop_down.selected()));
+                }),
+            );
+
+            (folder_drop_down, folders_list, folder_handler)
+        };
+
+        let content_stack = gtk::Stack::builder().vexpand(true).build();
+        root.append(&content_stack);
+
+        content_stack.add_named(
+            &adw::StatusPage::builder()
+                .title(fl!("bookmarks"))
+                .description(fl!("bookmarks-empty"))
+                .icon_name(icon_names::LIBRARY)
+                .build(),
+            Some("empty"),
+        );
+
+        let bookmarks_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["navigation-sidebar"])
+            .build();
+
+        content_stack.add_named(
+            &gtk::ScrolledWindow::builder()
+                .child(&bookmarks_list)
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .build(),
+            Some("list"),
+        );
+
+        BOOKMARKS.subscribe(sender.input_sender(), |bookmarks: &BookmarkStore| {
+            BookmarksPageInput::BookmarksChanged(
+                bookmarks.bookmarks().to_vec(),
+                bookmarks.folders(),
+            )
+        });
+
+        let widgets = BookmarksPageWidgets {
+            folder_drop_down,
+            folders_list,
+            folder_handler,
+            bookmarks_list,
+            content_stack,
+        };
+
+        model.fill_list(&widgets, &sender);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            BookmarksPageInput::BookmarksChanged(bookmarks, folders) => {
+                self.bookmarks = bookmarks;
+
+                if folders != self.folders {
+                    self.folders = folders;
+                    self.update_folders(widgets);
+                }
+            }
+            BookmarksPageInput::Filter(filter_text) => {
+                self.filter_text = filter_text.to_lowercase();
+            }
+            BookmarksPageInput::FilterFolder(index) => {
+                self.folder_filter = (index as usize)
+                    .checked_sub(1)
+                    .and_then(|index| self.folders.get(index))
+                    .cloned();
+            }
+            BookmarksPageInput::Sort(index) => {
+                self.sort = SORTS[index as usize % SORTS.len()];
+            }
+            BookmarksPageInput::Open(index) => {
+                if let Some(bookmark) = self.shown.get(index) {
+                    sender
+                        .output(BookmarksPageOutput::RequestPagesByUri(
+                            AppUri::new(
+                                &bookmark.site,
+                                Route::Questions {
+                                    ids: vec![bookmark.question_id],
+                                    answer_id: None,
+                                    comment_id: None,
+                                },
+                            )
+                            .to_string(),
+                        ))
+                        .unwrap();
+                }
+                return;
+            }
+            BookmarksPageInput::Edit(bookmark) => {
+                sender
+                    .output(BookmarksPageOutput::UpdateBookmark(bookmark))
+                    .unwrap();
+                return;
+            }
+            BookmarksPageInput::Remove(index) => {
+                if let Some(bookmark) = self.shown.get(index) {
+                    sender
+                        .output(BookmarksPageOutput::RemoveBookmark(
+                            bookmark.site.clone(),
+                            bookmark.question_id,
+                        ))
+                        .unwrap();
+                }
+                return;
+            }
+        }
+
+        self.fill_list(widgets, &sender);
+    }
+}
+
+impl BookmarksPageModel {
+    fn fill_list(&mut self, widgets: &BookmarksPageWidgets, sender: &ComponentSender<Self>) {
+        self.shown = self
+            .bookmarks
+            .iter()
+            .filter(|bookmark| self.matches_filter(bookmark))
+            .cloned()
+            .collect();
+
+        match self.sort {
+            BookmarksSort::Newest => self.shown.sort_by(|a, b| b.added.cmp(&a.added)),
+            BookmarksSort::Title => self
+                .shown
+                .sort_by_key(|bookmark| bookmark.title.to_lowercase()),
+            BookmarksSort::Score => self.shown.sort_by(|a, b| b.score.cmp(&a.score)),
+        }
+
+        widgets.bookmarks_list.remove_all();
+
+        for (index, bookmark) in self.shown.iter().enumerate() {
+            widgets.bookmarks_list.append(&Self::st_bookmark(
+                bookmark,
+                index,
+                self.folder_filter.is_none(),
+                sender,
+            ));
+
+            if !bookmark.note.trim().is_empty() {
+                let md_view = MarkdownView::render(&bookmark.note);
+                md_view.text_view.set_margin_bottom(10);
+
+                widgets.bookmarks_list.append(
+                    &gtk::ListBoxRow::builder()
+                        .child(&md_view.text_view)
+                        .activatable(false)
+                        .selectable(false)
+                        .build(),
+                );
+            }
+        }
+
+        widgets
+            .content_stack
+            .set_visible_child_name(if self.bookmarks.is_empty() {
+                "empty"
+            } else {
+                "list"
+            });
+    }
+
+    // Keep the selected folder if it still exists, otherwise show all folders.
+    fn update_folders(&mut self, widgets: &BookmarksPageWidgets) {
+        let selected = self
+            .folder_filter
+            .as_ref()
+            .and_then(|folder| self.folders.iter().position(|f| f == folder));
+
+        if selected.is_none() {
+            self.folder_filter = None;
+        }
+
+        widgets
+            .folder_drop_down
+            .block_signal(&widgets.folder_handler);
+
+        let folders: Vec<&str> = self.folders.iter().map(String::as_str).collect();
+        widgets
+            .folders_list
+            .splice(1, widgets.folders_list.n_items() - 1, &folders);
+        widgets
+            .folder_drop_down
+            .set_selected(selected.map_or(0, |index| index as u32 + 1));
+
+        widgets
+            .folder_drop_down
+            .unblock_signal(&widgets.folder_handler);
+    }
+
+    fn matches_filter(&self, bookmark: &Bookmark) -> bool {
+        if self
+            .folder_filter
+            .as_ref()
+            .is_some_and(|folder| bookmark.folder.as_ref() != Some(folder))
+        {
+            return false;
+        }
+
+        let matches = |text: &str| text.to_lowercase().contains(&self.filter_text);
+
+        self.filter_text.is_empty()
+            || matches(&bookmark.title)
+            || matches(&bookmark.site)
+            || matches(&bookmark.note)
+            || bookmark.folder.as_deref().is_some_and(matches)
+            || bookmark.tags.iter().any(|tag| matches(tag))
+            || bookmark.local_tags.iter().any(|tag| matches(tag))
+    }
+
+    fn st_bookmark(
+        bookmark: &Bookmark,
+        index: usize,
+        show_folder: bool,
+        sender: &ComponentSender<Self>,
+    ) -> adw::ActionRow {
+        let mut subtitle = Vec::new();
+        if let Some(folder) = bookmark.folder.as_ref().filter(|_| show_folder) {
+            subtitle.push(format!("📁 {folder}"));
+        }
+        subtitle.push(bookmark.site.clone());
+        subtitle.push(bookmark.score.to_string());
+        subtitle.extend(bookmark.tags.iter().map(|tag| format!("[{tag}]")));
+        subtitle.extend(bookmark.local_tags.iter().map(|tag| format!("#{tag}")));
+
+        let row = adw::ActionRow::builder()
+            .title(&bookmark.title)
+            .subtitle(subtitle.join(" · "))
+            .subtitle_lines(1)
+            .use_markup(false)
+            .activatable(true)
+            .tooltip_markup(fl!(
+                "bookmark-tooltip",
+                time = bookmark.added.formate_date_time_string()
+            ))
+            .build();
+
+        row.connect_activated(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(BookmarksPageInput::Open(index));
+        }));
+
+        let edit_button = gtk::MenuButton::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text(fl!("bookmark-edit"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        row.add_suffix(&edit_button);
+
+        // Only build the editor when it's needed.
+        edit_button.set_create_popup_func(
+            gtk::glib::clone!(@strong sender, @strong bookmark => move |button| {
+                if button.popover().is_none() {
+                    button.set_popover(Some(&Self::st_editor(&bookmark, &sender)));
+                }
+            }),
+        );
+
+        let remove_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(fl!("bookmark-remove"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        row.add_suffix(&remove_button);
+
+        remove_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(BookmarksPageInput::Remove(index));
+        }));
+
+        row
+    }
+
+    fn st_editor(bookmark: &Bookmark, sender: &ComponentSender<Self>) -> gtk::Popover {
+        let editor_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(5)
+            .width_request(300)
+            .build();
+
+        let folder_entry = gtk::Entry::builder()
+            .text(bookmark.folder.as_deref().unwrap_or_default())
+            .placeholder_text(fl!("bookmark-folder-placeholder"))
+            .build();
+        editor_layout.append(&folder_entry);
+
+        let tags_entry = gtk::Entry::builder()
+            .text(bookmark.local_tags.join(" "))
+            .placeholder_text(fl!("bookmark-tags-placeholder"))
+            .build();
+        editor_layout.append(&tags_entry);
+
+        let note_view = gtk::TextView::builder()
+            .wrap_mode(gtk::WrapMode::WordChar)
+            .accepts_tab(false)
+            .top_margin(5)
+            .bottom_margin(5)
+            .left_margin(5)
+            .right_margin(5)
+            .tooltip_text(fl!("bookmark-note-tooltip"))
+            .build();
+        note_view.buffer().set_text(&bookmark.note);
+
+        editor_layout.append(
+            &gtk::Frame::builder()
+                .child(
+                    &gtk::ScrolledWindow::builder()
+                        .child(&note_view)
+                        .min_content_height(150)
+                        .hscrollbar_policy(gtk::PolicyType::Never)
+                        .build(),
+                )
+                .build(),
+        );
+
+        let save_button = gtk::Button::builder()
+            .label(fl!("bookmark-save"))
+            .halign(gtk::Align::End)
+            .css_classes(["suggested-action"])
+            .build();
+        editor_layout.append(&save_button);
+
+        let popover = gtk::Popover::builder().child(&editor_layout).build();
+
+        save_button.connect_clicked(gtk::glib::clone!(
+            @strong sender, @strong bookmark, @weak popover => move |_| {
+                let folder = folder_entry.text().trim().to_owned();
+                let buffer = note_view.buffer();
+
+                let mut local_tags: Vec<String> =
+                    tags_entry.text().split_whitespace().map(str::to_owned).collect();
+                local_tags.sort_unstable();
+                local_tags.dedup();
+
+                sender.input(BookmarksPageInput::Edit(Bookmark {
+                    folder: (!folder.is_empty()).then_some(folder),
+                    local_tags,
+                    note: buffer
+                        .text(&buffer.start_iter(), &buffer.end_iter(), false)
+                        .trim_end()
+                        .to_owned(),
+                    ..bookmark.clone()
+                }));
+
+                popover.popdown();
+            }
+        ));
+
+        popover
+    }
+}
@@ -1,67 +1,578 @@
-use std::str::FromStr;
-
-use relm4::{adw, gtk::prelude::*, prelude::*};
-
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+use relm4::{
+    adw,
+    gtk::{glib, prelude::*},
+    prelude::*,
+};
+
+use super::main_window::{api_error_message, BOOKMARKS};
 use super::markdown2gtk::MarkdownView;
-use crate::api::stackexchange::{Answer, Comment, DateExt, Question, User};
+use crate::api::backend::Backend;
+use crate::api::error::ApiError;
+use crate::api::stackexchange::{Answer, Comment, DateExt, Id, Question, User};
 use crate::fl;
+use crate::storage::bookmarks::Bookmark;
+
+// Number of comments shown for each post before expanding them.
+const COLLAPSED_COMMENTS_COUNT: usize = 5;
+
+// How long a focused post stays highlighted.
+const HIGHLIGHT_DURATION: Duration = Duration::from_secs(2);
+
+// Minimum time between reports of the scroll position.
+const SCROLL_REPORT_DELAY: Duration = Duration::from_secs(1);
+
+// Widgets of each post, to update or scroll to them after the page is built.
+#[derive(Default)]
+struct PostsWidgets {
+    // Layouts holding comments of each post, to append comments loaded later.
+    comment_sections: HashMap<Id, gtk::Box>,
+    answers: HashMap<Id, gtk::Frame>,
+    comments: HashMap<Id, gtk::Frame>,
+}
+
+/// Post to scroll to and highlight once the question page is shown.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostFocus {
+    pub answer_id: Option<Id>,
+    /// Comment of the focused answer, or of the question when there is no answer.
+    pub comment_id: Option<Id>,
+}
 
 pub struct QuestionPageModel {
     question: Question,
+    site: String,
+    client: Backend,
+    focus: Option<PostFocus>,
 }
 
 pub struct QuestionPageInit {
     pub question: Question,
+    pub site: String,
+    pub client: Backend,
+    pub focus: Option<PostFocus>,
 }
 
-pub struct QuestionPageWidgets;
+pub struct QuestionPageWidgets {
+    page_layout: gtk::Box,
+    cached_banner: adw::Banner,
+    bookmark_button: gtk::ToggleButton,
+    question_layout: gtk::Box,
+    posts: PostsWidgets,
+    loading_layout: gtk::Box,
+    loading_spinner: gtk::Spinner,
+    loading_label: gtk::Label,
+}
+
+#[derive(Debug)]
+pub enum QuestionPageInput {
+    ShowAllComments(Id),
+    ScrollToFocus,
+    SetBookmarked(bool),
+    BookmarkStateChanged(bool),
+    ReportScroll,
+}
+
+#[derive(Debug)]
+pub enum QuestionPageOutput {
+    AddBookmark(Bookmark),
+    RemoveBookmark(String, Id),
+    /// How far the question was read, from 0 to 1.
+    Scrolled(String, Id, f64),
+}
 
-// TODO: Make the question page async.
-impl FactoryComponent for QuestionPageModel {
-    type ParentWidget = adw::TabView;
-    type CommandOutput = ();
-    type Input = ();
-    type Output = ();
+#[derive(Debug)]
+pub enum QuestionPageCommand {
+    QuestionCompleted(Result<Question, ApiError>),
+    CommentsLoaded(Id, Result<Vec<Comment>, ApiError>),
+}
+
+impl Component for QuestionPageModel {
+    type CommandOutput = QuestionPageCommand;
+    type Input = QuestionPageInput;
+    type Output = QuestionPageOutput;
     type Init = QuestionPageInit;
     type Root = gtk::ScrolledWindow;
     type Widgets = QuestionPageWidgets;
-    type Index = DynamicIndex;
 
-    fn init_model(init: Self::Init, _index: &Self::Index, _sender: FactorySender<Self>) -> Self {
-        QuestionPageModel {
-            question: init.question,
-        }
-    }
-
-    fn init_root(&self) -> Self::Root {
+    fn init_root() -> Self::Root {
         gtk::ScrolledWindow::builder()
             .vexpand(true)
             .hexpand(true)
             .build()
     }
 
-    fn init_widgets(
-        &mut self,
-        _index: &Self::Index,
+    fn init(
+        init: Self::Init,
         root: Self::Root,
-        retured_widget: &adw::TabPage,
-        _sender: FactorySender<Self>,
-    ) -> Self::Widgets {
-        root.set_child(Some(&Self::st_question(&self.question)));
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = QuestionPageModel {
+            question: init.question,
+            site: init.site,
+            client: init.client,
+            focus: init.focus,
+        };
+
+        let page_layout = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        root.set_child(Some(&page_layout));
+
+        // Tell when the question is an old copy, since the API couldn't be reached.
+        let cached_banner = adw::Banner::new("");
+        page_layout.append(&cached_banner);
+
+        Self::update_cached_banner(&cached_banner, &model.question);
+
+        // Actions bar
+        let actions_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .halign(gtk::Align::End)
+            .margin_top(5)
+            .margin_end(10)
+            .build();
+        page_layout.append(&actions_layout);
+
+        let bookmark_button = gtk::ToggleButton::builder().css_classes(["flat"]).build();
+        actions_layout.append(&bookmark_button);
+
+        Self::update_bookmark_button(
+            &bookmark_button,
+            BOOKMARKS
+                .read()
+                .contains(&model.site, model.question.question_id),
+        );
+
+        bookmark_button.connect_toggled(glib::clone!(@strong sender => move |button| {
+            sender.input(QuestionPageInput::SetBookmarked(button.is_active()));
+        }));
+
+        // Keep the button in sync when bookmarks are changed from other places.
+        {
+            let site = model.site.clone();
+            let question_id = model.question.question_id;
+
+            BOOKMARKS.subscribe(sender.input_sender(), move |bookmarks| {
+                QuestionPageInput::BookmarkStateChanged(bookmarks.contains(&site, question_id))
+            });
+        }
+
+        let mut posts = PostsWidgets::default();
+        let question_layout = Self::st_question(&model.question, &model.client, &mut posts);
+        page_layout.append(&question_layout);
+
+        // Posts can only be scrolled to after the page is shown.
+        if model.focus.is_some() {
+            root.connect_map(glib::clone!(@strong sender => move |_| {
+                sender.input(QuestionPageInput::ScrollToFocus);
+            }));
+        }
+
+        // Report the position while scrolling, but not on every step.
+        {
+            let report_pending = Rc::new(Cell::new(false));
+
+            root.vadjustment()
+                .connect_value_changed(glib::clone!(@strong sender => move |_| {
+                    if report_pending.replace(true) {
+                        return;
+                    }
+
+                    glib::timeout_add_local_once(
+                        SCROLL_REPORT_DELAY,
+                        glib::clone!(@strong sender, @strong report_pending => move || {
+                            report_pending.set(false);
+                            sender.input(QuestionPageInput::ReportScroll);
+                        }),
+                    );
+                }));
+        }
 
-        retured_widget.set_title(&self.question.title);
+        // Show more comments buttons activate this action with the post id.
+        {
+            let show_comments_action =
+                gtk::gio::SimpleAction::new("show-comments", Some(glib::VariantTy::UINT32));
+
+            show_comments_action.connect_activate(
+                glib::clone!(@strong sender => move |_, parameter| {
+                    if let Some(post_id) = parameter.and_then(|parameter| parameter.get::<Id>()) {
+                        sender.input(QuestionPageInput::ShowAllComments(post_id));
+                    }
+                }),
+            );
 
-        // TODO: Pass question tags as keywords.
-        // retured_widget.set_keyword(keyword);
+            let group = gtk::gio::SimpleActionGroup::new();
+            group.add_action(&show_comments_action);
+            root.insert_action_group("question", Some(&group));
+        }
 
-        QuestionPageWidgets {}
+        // Indicator for loading answers that are not in the first page.
+        let loading_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .halign(gtk::Align::Center)
+            .spacing(10)
+            .margin_top(15)
+            .margin_bottom(15)
+            .visible(false)
+            .build();
+        page_layout.append(&loading_layout);
+
+        let loading_spinner = gtk::Spinner::new();
+        loading_layout.append(&loading_spinner);
+
+        let loading_label = gtk::Label::builder()
+            .label(fl!("question-loading-more"))
+            .css_classes(["dim-label"])
+            .wrap(true)
+            .build();
+        loading_layout.append(&loading_label);
+
+        if !model.question.is_complete() {
+            loading_layout.set_visible(true);
+            loading_spinner.start();
+
+            let client = model.client.clone();
+            let site = model.site.clone();
+            let question = model.question.clone();
+
+            sender.oneshot_command(async move {
+                QuestionPageCommand::QuestionCompleted(
+                    client.complete_question(&site, question).await,
+                )
+            });
+        }
+
+        let widgets = QuestionPageWidgets {
+            page_layout,
+            cached_banner,
+            bookmark_button,
+            question_layout,
+            posts,
+            loading_layout,
+            loading_spinner,
+            loading_label,
+        };
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            QuestionPageInput::ShowAllComments(post_id) => {
+                self.show_all_comments(&mut widgets.posts, post_id, &sender);
+            }
+            QuestionPageInput::SetBookmarked(bookmarked) => {
+                // Ignore changes that came from the store itself.
+                if bookmarked
+                    == BOOKMARKS
+                        .read()
+                        .contains(&self.site, self.question.question_id)
+                {
+                    return;
+                }
+
+                sender
+                    .output(if bookmarked {
+                        QuestionPageOutput::AddBookmark(Bookmark::new(&self.site, &self.question))
+                    } else {
+                        QuestionPageOutput::RemoveBookmark(
+                            self.site.clone(),
+                            self.question.question_id,
+                        )
+                    })
+                    .unwrap();
+            }
+            QuestionPageInput::BookmarkStateChanged(bookmarked) => {
+                Self::update_bookmark_button(&widgets.bookmark_button, bookmarked);
+            }
+            QuestionPageInput::ReportScroll => {
+                let adjustment = root.vadjustment();
+                let scrollable = adjustment.upper() - adjustment.page_size();
+
+                if scrollable > 0.0 {
+                    sender
+                        .output(QuestionPageOutput::Scrolled(
+                            self.site.clone(),
+                            self.question.question_id,
+                            adjustment.value() / scrollable,
+                        ))
+                        .unwrap();
+                }
+            }
+            QuestionPageInput::ScrollToFocus => {
+                let Some(focus) = self.focus else {
+                    return;
+                };
+
+                if !root.is_mapped() {
+                    return;
+                }
+
+                // Wait for answers that are not in the first page.
+                if focus
+                    .answer_id
+                    .is_some_and(|answer_id| !widgets.posts.answers.contains_key(&answer_id))
+                    && !self.question.is_complete()
+                {
+                    return;
+                }
+
+                // Hidden comments are shown first, or loaded then focused.
+                if let Some(comment_id) = focus.comment_id {
+                    if !widgets.posts.comments.contains_key(&comment_id) {
+                        let post_id = focus.answer_id.unwrap_or(self.question.question_id);
+
+                        if !self.show_all_comments(&mut widgets.posts, post_id, &sender) {
+                            return;
+                        }
+                    }
+                }
+
+                self.focus = None;
+
+                let target = match (focus.comment_id, focus.answer_id) {
+                    (Some(comment_id), _) if widgets.posts.comments.contains_key(&comment_id) => {
+                        widgets.posts.comments[&comment_id]
+                            .clone()
+                            .upcast::<gtk::Widget>()
+                    }
+                    (_, Some(answer_id)) if widgets.posts.answers.contains_key(&answer_id) => {
+                        widgets.posts.answers[&answer_id].clone().upcast()
+                    }
+                    _ => return,
+                };
+
+                Self::scroll_to(root, &widgets.page_layout, target);
+            }
+        }
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            QuestionPageCommand::QuestionCompleted(result) => {
+                widgets.loading_spinner.stop();
+
+                match result {
+                    Ok(question) => {
+                        self.question = question;
+
+                        // Replace the page content in one go, so the scroll position is kept.
+                        widgets.posts = PostsWidgets::default();
+                        let question_layout =
+                            Self::st_question(&self.question, &self.client, &mut widgets.posts);
+                        widgets
+                            .page_layout
+                            .insert_child_after(&question_layout, Some(&widgets.question_layout));
+                        widgets.page_layout.remove(&widgets.question_layout);
+                        widgets.question_layout = question_layout;
+
+                        Self::update_cached_banner(&widgets.cached_banner, &self.question);
+
+                        widgets.loading_layout.set_visible(false);
+
+                        sender.input(QuestionPageInput::ScrollToFocus);
+                    }
+                    Err(error) => {
+                        // The focused answer might not be loaded.
+                        self.focus = None;
+
+                        widgets.loading_label.set_label(&fl!(
+                            "question-loading-more-failed",
+                            error = api_error_message(&error)
+                        ));
+                    }
+                }
+            }
+            QuestionPageCommand::CommentsLoaded(post_id, result) => {
+                let Some(comments_layout) = widgets.posts.comment_sections.get(&post_id) else {
+                    return;
+                };
+
+                match result {
+                    Ok(comments) => {
+                        Self::expand_comments(
+                            comments_layout,
+                            &comments,
+                            &mut widgets.posts.comments,
+                        );
+
+                        // Comments count includes deleted ones, don't try loading them again.
+                        let comment_count = comments.len() as u32;
+
+                        if post_id == self.question.question_id {
+                            self.question.comments = Some(comments);
+                            self.question.comment_count = comment_count;
+                        } else if let Some(answer) = self
+                            .question
+                            .answers
+                            .iter_mut()
+                            .flatten()
+                            .find(|answer| answer.answer_id == post_id)
+                        {
+                            answer.comments = Some(comments);
+                            answer.comment_count = comment_count;
+                        }
+
+                        sender.input(QuestionPageInput::ScrollToFocus);
+                    }
+                    Err(error) => {
+                        // Focus the post instead of its comment.
+                        if let Some(focus) = &mut self.focus {
+                            focus.comment_id = None;
+                            sender.input(QuestionPageInput::ScrollToFocus);
+                        }
+
+                        if let Some(button) =
+                            comments_layout.last_child().and_downcast::<gtk::Button>()
+                        {
+                            button.set_label(&fl!(
+                                "comments-loading-failed",
+                                error = api_error_message(&error)
+                            ));
+                            button.set_sensitive(true);
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
 // TODO: Use grid layout for some cases.
 
 impl QuestionPageModel {
-    fn st_question(question: &Question) -> gtk::Box {
+    // Loaded comments of a post and its total comments count.
+    fn post_comments(&self, post_id: Id) -> (Option<&Vec<Comment>>, u32) {
+        if post_id == self.question.question_id {
+            return (self.question.comments.as_ref(), self.question.comment_count);
+        }
+
+        self.question
+            .answers
+            .iter()
+            .flatten()
+            .find(|answer| answer.answer_id == post_id)
+            .map_or((None, 0), |answer| {
+                (answer.comments.as_ref(), answer.comment_count)
+            })
+    }
+
+    // Show every comment of a post, returns `false` when they have to be loaded first.
+    fn show_all_comments(
+        &self,
+        posts: &mut PostsWidgets,
+        post_id: Id,
+        sender: &ComponentSender<Self>,
+    ) -> bool {
+        let Some(comments_layout) = posts.comment_sections.get(&post_id) else {
+            return true;
+        };
+
+        let (comments, comment_count) = self.post_comments(post_id);
+
+        if comments.map_or(0, Vec::len) as u32 >= comment_count {
+            // Every comment is already loaded but hidden.
+            Self::expand_comments(
+                comments_layout,
+                comments.map(Vec::as_slice).unwrap_or_default(),
+                &mut posts.comments,
+            );
+
+            return true;
+        }
+
+        // Disable the show more button while loading.
+        if let Some(button) = comments_layout.last_child() {
+            button.set_sensitive(false);
+        }
+
+        let client = self.client.clone();
+        let site = self.site.clone();
+
+        sender.oneshot_command(async move {
+            QuestionPageCommand::CommentsLoaded(
+                post_id,
+                client.get_post_comments(&site, post_id).await,
+            )
+        });
+
+        false
+    }
+
+    // Show all comments in a section, in place of the collapsed ones.
+    fn expand_comments(
+        comments_layout: &gtk::Box,
+        comments: &[Comment],
+        comment_frames: &mut HashMap<Id, gtk::Frame>,
+    ) {
+        while let Some(child) = comments_layout.first_child() {
+            comments_layout.remove(&child);
+        }
+
+        for comment in comments {
+            let comment_frame = Self::st_comment(comment);
+            comments_layout.append(&comment_frame);
+            comment_frames.insert(comment.comment_id, comment_frame);
+        }
+    }
+
+    fn update_bookmark_button(bookmark_button: &gtk::ToggleButton, bookmarked: bool) {
+        bookmark_button.set_active(bookmarked);
+
+        if bookmarked {
+            bookmark_button.set_icon_name("starred-symbolic");
+            bookmark_button.set_tooltip_text(Some(&fl!("bookmark-remove")));
+        } else {
+            bookmark_button.set_icon_name("non-starred-symbolic");
+            bookmark_button.set_tooltip_text(Some(&fl!("bookmark-add")));
+        }
+    }
+
+    fn update_cached_banner(cached_banner: &adw::Banner, question: &Question) {
+        if let Some(cached_at) = question.cached_at {
+            cached_banner.set_title(&fl!(
+                "question-cached-copy",
+                time = cached_at.formate_date_time_string()
+            ));
+        }
+
+        cached_banner.set_revealed(question.cached_at.is_some());
+    }
+
+    // Scroll the page to a post and highlight it for a moment.
+    fn scroll_to(root: &gtk::ScrolledWindow, page_layout: &gtk::Box, target: gtk::Widget) {
+        // Positions are only known after the layout is allocated.
+        glib::idle_add_local_once(glib::clone!(@strong root, @strong page_layout => move || {
+            if let Some(point) =
+                target.compute_point(&page_layout, &gtk::graphene::Point::zero())
+            {
+                root.vadjustment().set_value(f64::from(point.y()) - 10.0);
+            }
+
+            target.add_css_class("highlighted");
+
+            glib::timeout_add_local_once(HIGHLIGHT_DURATION, move || {
+                target.remove_css_class("highlighted");
+            });
+        }));
+    }
+
+    fn st_question(question: &Question, client: &Backend, posts: &mut PostsWidgets) -> gtk::Box {
         let main_layout = gtk::Box::new(gtk::Orientation::Vertical, 0);
 
         // Question title
@@ -191,13 +702,13 @@ impl QuestionPageModel {
 
         // Body
         {
-            let md_view = MarkdownView::from_str(&question.body_markdown).unwrap();
+            let md_view = MarkdownView::render_with_images(&question.body_markdown, client.clone());
 
             question_layout.append(&md_view.text_view);
         }
 
         // Comments
-        if let Some(comments) = &question.comments {
+        if question.comment_count > 0 {
             main_layout.append(
                 &gtk::Label::builder()
                     .label(fl!("comments-count", count = question.comment_count))
@@ -205,10 +716,17 @@ impl QuestionPageModel {
                     .halign(gtk::Align::Start)
                     .build(),
             );
-            // TODO: Implement show-more button for comments.
-            for comment in comments {
-                main_layout.append(&Self::st_comment(comment));
-            }
+
+            let comments_layout = Self::st_comments(
+                question.question_id,
+                question.comments.as_deref().unwrap_or_default(),
+                question.comment_count,
+                &mut posts.comments,
+            );
+            main_layout.append(&comments_layout);
+            posts
+                .comment_sections
+                .insert(question.question_id, comments_layout);
         }
 
         // Separator between question area and answers
@@ -229,14 +747,16 @@ impl QuestionPageModel {
             );
 
             for answer in answers {
-                main_layout.append(&Self::st_answer(answer));
+                let answer_frame = Self::st_answer(answer, client, posts);
+                main_layout.append(&answer_frame);
+                posts.answers.insert(answer.answer_id, answer_frame);
             }
         }
 
         main_layout
     }
 
-    fn st_answer(answer: &Answer) -> gtk::Frame {
+    fn st_answer(answer: &Answer, client: &Backend, posts: &mut PostsWidgets) -> gtk::Frame {
         // Main layout for answer area
         let main_layout = gtk::Box::builder()
             .orientation(gtk::Orientation::Vertical)
@@ -327,13 +847,13 @@ impl QuestionPageModel {
 
         // Body
         {
-            let md_view = MarkdownView::from_str(&answer.body_markdown).unwrap();
+            let md_view = MarkdownView::render_with_images(&answer.body_markdown, client.clone());
 
             answer_layout.append(&md_view.text_view);
         }
 
         // Comments
-        if let Some(comments) = &answer.comments {
+        if answer.comment_count > 0 {
             main_layout.append(
                 &gtk::Label::builder()
                     .label(fl!("comments-count", count = answer.comment_count))
@@ -341,9 +861,17 @@ impl QuestionPageModel {
                     .halign(gtk::Align::Start)
                     .build(),
             );
-            for comment in comments {
-                main_layout.append(&Self::st_comment(comment));
-            }
+
+            let comments_layout = Self::st_comments(
+                answer.answer_id,
+                answer.comments.as_deref().unwrap_or_default(),
+                answer.comment_count,
+                &mut posts.comments,
+            );
+            main_layout.append(&comments_layout);
+            posts
+                .comment_sections
+                .insert(answer.answer_id, comments_layout);
         }
 
         gtk::Frame::builder()
@@ -355,6 +883,42 @@ impl QuestionPageModel {
             .build()
     }
 
+    // Top comments of a post, with a button to show the rest of them.
+    fn st_comments(
+        post_id: Id,
+        comments: &[Comment],
+        comment_count: u32,
+        comment_frames: &mut HashMap<Id, gtk::Frame>,
+    ) -> gtk::Box {
+        let main_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+
+        for comment in comments.iter().take(COLLAPSED_COMMENTS_COUNT) {
+            let comment_frame = Self::st_comment(comment);
+            main_layout.append(&comment_frame);
+            comment_frames.insert(comment.comment_id, comment_frame);
+        }
+
+        let hidden_count =
+            (comment_count as usize).saturating_sub(comments.len().min(COLLAPSED_COMMENTS_COUNT));
+
+        if hidden_count > 0 {
+            main_layout.append(
+                &gtk::Button::builder()
+                    .label(fl!("comments-show-more", count = hidden_count))
+                    .action_name("question.show-comments")
+                    .action_target(&post_id.to_variant())
+                    .css_classes(["flat"])
+                    .halign(gtk::Align::Start)
+                    .margin_start(5)
+                    .build(),
+            );
+        }
+
+        main_layout
+    }
+
     fn st_comment(comment: &Comment) -> gtk::Frame {
         let main_layout = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
@@ -398,7 +962,7 @@ impl QuestionPageModel {
 
         match &comment.body_markdown {
             Some(body_markdown) => {
-                let md_view = MarkdownView::from_str(body_markdown).unwrap();
+                let md_view = MarkdownView::render(body_markdown);
 
                 comment_layout.append(&md_view.text_view);
             }
@@ -475,7 +1039,11 @@ impl QuestionPageModel {
                     Some(user_id) => user_id.to_string(),
                     None => fl!("not-available"),
                 },
-                reputation = user.reputation.unwrap()
+                // Deleted users, and owners missing from imported dumps, have no reputation.
+                reputation = match user.reputation {
+                    Some(reputation) => reputation.to_string(),
+                    None => fl!("not-available"),
+                }
             ))
             .build()
     }
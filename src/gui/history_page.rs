@@ -0,0 +1,374 @@
+use relm4::{
+    adw::{self, prelude::*},
+    prelude::*,
+};
+use relm4_icons::icon_names;
+
+use super::main_window::HISTORY;
+use crate::api::router::{AppUri, Route};
+use crate::api::stackexchange::{DateExt, Id, Uri};
+use crate::fl;
+use crate::storage::history::{HistoryEntry, HistoryStore};
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// Ranges of the clear history menu, all of them until now.
+#[derive(Debug, Clone, Copy)]
+pub enum ClearRange {
+    LastHour,
+    Today,
+    LastWeek,
+    Everything,
+}
+
+/// Visited questions in the sidebar, grouped by day.
+pub struct HistoryPageModel {
+    entries: Vec<HistoryEntry>,
+    filter_text: String,
+    // Entries in the same order they are shown, newest first.
+    shown: Vec<HistoryEntry>,
+}
+
+pub struct HistoryPageWidgets {
+    history_list: gtk::ListBox,
+    content_stack: gtk::Stack,
+}
+
+#[derive(Debug)]
+pub enum HistoryPageInput {
+    HistoryChanged(Vec<HistoryEntry>),
+    Filter(String),
+    Open(usize),
+    Remove(usize),
+    ClearDay(i64),
+    Clear(ClearRange),
+}
+
+#[derive(Debug)]
+pub enum HistoryPageOutput {
+    RequestPagesByUri(Uri),
+    RemoveEntry(String, Id, i64),
+    /// Remove entries visited from the first time until before the second one.
+    Clear(i64, i64),
+}
+
+impl Component for HistoryPageModel {
+    type CommandOutput = ();
+    type Input = HistoryPageInput;
+    type Output = HistoryPageOutput;
+    type Init = ();
+    type Root = gtk::Box;
+    type Widgets = HistoryPageWidgets;
+
+    fn init_root() -> Self::Root {
+        gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(5)
+            .build()
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let mut model = HistoryPageModel {
+            entries: HISTORY.read().entries().to_vec(),
+            filter_text: String::new(),
+            shown: Vec::new(),
+        };
+
+        // Search and clear controls
+        {
+            let controls_layout = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(5)
+                .margin_top(5)
+                .margin_start(5)
+                .margin_end(5)
+                .build();
+            root.append(&controls_layout);
+
+            let filter_entry = gtk::SearchEntry::builder()
+                .placeholder_text(fl!("history-filter-placeholder"))
+                .hexpand(true)
+                .build();
+            controls_layout.append(&filter_entry);
+
+            filter_entry.connect_search_changed(gtk::glib::clone!(@strong sender => move |entry| {
+                sender.input(HistoryPageInput::Filter(entry.text().to_string()));
+            }));
+
+            let clear_layout = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .build();
+
+            for (range, label) in [
+                (ClearRange::LastHour, fl!("history-clear-last-hour")),
+                (ClearRange::Today, fl!("history-clear-today")),
+                (ClearRange::LastWeek, fl!("history-clear-last-week")),
+                (ClearRange::Everything, fl!("history-clear-everything")),
+            ] {
+                let button = gtk::Button::builder()
+                    .label(label)
+                    .css_classes(["flat"])
+                    .build();
+                clear_layout.append(&button);
+
+                button.connect_clicked(gtk::glib::clone!(@strong sender => move |button| {
+                    if let Some(popover) = button
+                        .ancestor(gtk::Popover::static_type())
+                        .and_downcast::<gtk::Popover>()
+                    {
+                        popover.popdown();
+                    }
+
+                    sender.input(HistoryPageInput::Clear(range));
+                }));
+            }
+
+            controls_layout.append(
+                &gtk::MenuButton::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(fl!("history-clear"))
+                    .popover(&gtk::Popover::builder().child(&clear_layout).build())
+                    .build(),
+            );
+        }
+
+        let content_stack = gtk::Stack::builder().vexpand(true).build();
+        root.append(&content_stack);
+
+        content_stack.add_named(
+            &adw::StatusPage::builder()
+                .title(fl!("history"))
+                .description(fl!("history-empty"))
+                .icon_name(icon_names::HISTORY_UNDO)
+                .build(),
+            Some("empty"),
+        );
+
+        let history_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["navigation-sidebar"])
+            .build();
+
+        content_stack.add_named(
+            &gtk::ScrolledWindow::builder()
+                .child(&history_list)
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .build(),
+            Some("list"),
+        );
+
+        HISTORY.subscribe(sender.input_sender(), |history: &HistoryStore| {
+            HistoryPageInput::HistoryChanged(history.entries().to_vec())
+        });
+
+        let widgets = HistoryPageWidgets {
+            history_list,
+            content_stack,
+        };
+
+        model.fill_list(&widgets, &sender);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            HistoryPageInput::HistoryChanged(entries) => {
+                self.entries = entries;
+            }
+            HistoryPageInput::Filter(filter_text) => {
+                self.filter_text = filter_text.to_lowercase();
+            }
+            HistoryPageInput::Open(index) => {
+                if let Some(entry) = self.shown.get(index) {
+                    sender
+                        .output(HistoryPageOutput::RequestPagesByUri(
+                            AppUri::new(
+                                &entry.site,
+                                Route::Questions {
+                                    ids: vec![entry.question_id],
+                                    answer_id: None,
+                                    comment_id: None,
+                                },
+                            )
+                            .to_string(),
+                        ))
+                        .unwrap();
+                }
+                return;
+            }
+            HistoryPageInput::Remove(index) => {
+                if let Some(entry) = self.shown.get(index) {
+                    sender
+                        .output(HistoryPageOutput::RemoveEntry(
+                            entry.site.clone(),
+                            entry.question_id,
+                            entry.visited,
+                        ))
+                        .unwrap();
+                }
+                return;
+            }
+            HistoryPageInput::ClearDay(day) => {
+                let from = day * DAY - local_offset();
+                sender
+                    .output(HistoryPageOutput::Clear(from, from + DAY))
+                    .unwrap();
+                return;
+            }
+            HistoryPageInput::Clear(range) => {
+                let now = crate::storage::now();
+                let from = match range {
+                    ClearRange::LastHour => now - 60 * 60,
+                    ClearRange::Today => local_day(now) * DAY - local_offset(),
+                    ClearRange::LastWeek => now - 7 * DAY,
+                    ClearRange::Everything => i64::MIN,
+                };
+
+                sender
+                    .output(HistoryPageOutput::Clear(from, i64::MAX))
+                    .unwrap();
+                return;
+            }
+        }
+
+        self.fill_list(widgets, &sender);
+    }
+}
+
+impl HistoryPageModel {
+    fn fill_list(&mut self, widgets: &HistoryPageWidgets, sender: &ComponentSender<Self>) {
+        self.shown = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| {
+                self.filter_text.is_empty()
+                    || entry.title.to_lowercase().contains(&self.filter_text)
+                    || entry.site.contains(&self.filter_text)
+            })
+            .cloned()
+            .collect();
+
+        widgets.history_list.remove_all();
+
+        let mut current_day = None;
+
+        for (index, entry) in self.shown.iter().enumerate() {
+            let day = local_day(entry.visited);
+
+            if current_day != Some(day) {
+                current_day = Some(day);
+                widgets
+                    .history_list
+                    .append(&Self::st_day_header(day, sender));
+            }
+
+            widgets
+                .history_list
+                .append(&Self::st_entry(entry, index, sender));
+        }
+
+        widgets
+            .content_stack
+            .set_visible_child_name(if self.entries.is_empty() {
+                "empty"
+            } else {
+                "list"
+            });
+    }
+
+    fn st_day_header(day: i64, sender: &ComponentSender<Self>) -> gtk::ListBoxRow {
+        let header_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .margin_top(10)
+            .build();
+
+        header_layout.append(
+            &gtk::Label::builder()
+                // Days are already shifted to the local time.
+                .label((day * DAY).formate_date_string())
+                .css_classes(["heading"])
+                .halign(gtk::Align::Start)
+                .hexpand(true)
+                .build(),
+        );
+
+        let clear_button = gtk::Button::builder()
+            .icon_name("edit-clear-all-symbolic")
+            .tooltip_text(fl!("history-clear-day"))
+            .css_classes(["flat"])
+            .build();
+        header_layout.append(&clear_button);
+
+        clear_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(HistoryPageInput::ClearDay(day));
+        }));
+
+        gtk::ListBoxRow::builder()
+            .child(&header_layout)
+            .activatable(false)
+            .selectable(false)
+            .build()
+    }
+
+    fn st_entry(
+        entry: &HistoryEntry,
+        index: usize,
+        sender: &ComponentSender<Self>,
+    ) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(&entry.title)
+            .subtitle(fl!(
+                "history-entry-subtitle",
+                site = entry.site.as_str(),
+                read = (entry.scroll * 100.0).round() as u32
+            ))
+            .subtitle_lines(1)
+            .use_markup(false)
+            .activatable(true)
+            .tooltip_markup(fl!(
+                "history-entry-tooltip",
+                time = entry.visited.formate_date_time_string()
+            ))
+            .build();
+
+        row.connect_activated(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(HistoryPageInput::Open(index));
+        }));
+
+        let remove_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(fl!("history-remove"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        row.add_suffix(&remove_button);
+
+        remove_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(HistoryPageInput::Remove(index));
+        }));
+
+        row
+    }
+}
+
+// Offset of the local time zone from UTC in seconds.
+fn local_offset() -> i64 {
+    gtk::glib::DateTime::now_local().map_or(0, |now| now.utc_offset().as_seconds())
+}
+
+// Days since the unix epoch in the local time zone.
+fn local_day(time: i64) -> i64 {
+    (time + local_offset()).div_euclid(DAY)
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use relm4::{
     actions::AccelsPlus,
     adw::{self, prelude::*},
@@ -13,36 +15,99 @@ use relm4_icons::icon_names;
 
 use super::about_dialog::{AboutWindow, AboutWindowInput};
 use super::componant_builders;
+use super::offline_dialog::{OfflineDialogInit, OfflineDialogInput, OfflineDialogModel};
+use super::preferences_dialog::{
+    self, PreferencesDialogInit, PreferencesDialogInput, PreferencesDialogModel,
+    PreferencesDialogOutput,
+};
+use super::search_page;
 use super::side_bar;
-use crate::api::stackexchange;
+use super::site_picker;
+use super::tab_page;
+use super::user_page;
+use crate::api::{
+    backend::{self, Backend},
+    error::{ApiError, ApiErrorName, UriError},
+    router::{self, AppUri, Route},
+    scheduler::Quota,
+    sites, stackexchange,
+};
 use crate::fl;
+use crate::storage::bookmarks::{Bookmark, BookmarkStore};
+use crate::storage::history::HistoryStore;
+use crate::storage::session::Session;
+use crate::storage::settings::Settings;
 
 // Save build-time informations
 shadow_rs::shadow!(build_time);
 
 pub const APP_NAME: &str = "StackBloatLess";
 
+// Scroll positions are saved in the history at most once per this delay.
+const HISTORY_SAVE_DELAY: Duration = Duration::from_secs(10);
+
+/// Bookmarks shared with every component that shows them, only `AppModel` modifies them.
+pub static BOOKMARKS: relm4::SharedState<BookmarkStore> = relm4::SharedState::new();
+
+/// Browsing history, only `AppModel` modifies it too.
+pub static HISTORY: relm4::SharedState<HistoryStore> = relm4::SharedState::new();
+
 #[derive(Debug, Clone)]
 pub enum AppInput {
     RequestPagesByUri(stackexchange::Uri),
+    OpenQuestionsByIds(String),
+    SearchQuestions(stackexchange::SearchQuery),
+    SelectSearchSite(stackexchange::Site),
+    QuotaUpdated(Option<Quota>),
+    AddBookmark(Bookmark),
+    UpdateBookmark(Bookmark),
+    RemoveBookmark(String, stackexchange::Id),
+    UpdateHistoryScroll(String, stackexchange::Id, f64),
+    SaveHistory,
+    RemoveHistoryEntry(String, stackexchange::Id, i64),
+    ClearHistory(i64, i64),
     ShowAboutWindow,
+    ShowOfflineDialog,
+    ShowPreferencesDialog,
+    /// Settings were changed, with the error when they couldn't be saved.
+    PreferencesSaved(Result<(), String>),
     ToggleSideBar,
     Quit,
     ToggleSelectedTabPin,
     CloseTab,
     ClosePinnedTab,
+    LoadSelectedTab,
+}
+
+#[derive(Debug)]
+pub enum AppCommand {
+    /// Questions of a site, with a post to focus in the first one.
+    QuestionsLoaded(
+        String,
+        Result<Vec<stackexchange::Question>, ApiError>,
+        Option<componant_builders::PostFocus>,
+    ),
 }
 
 pub struct AppInit {}
 
 pub struct AppModel {
-    stackexchange_client: stackexchange::StackExchange,
+    stackexchange_client: Backend,
+    // Site used for searching and opening questions by ids.
+    search_site: String,
+    // Whether scroll positions in the history were changed without saving them yet.
+    history_save_pending: bool,
+    site_picker_controller: Controller<site_picker::SitePickerModel>,
     side_bar_controller: AsyncController<side_bar::SideBarModel>,
     about_window_connector: Connector<AboutWindow>,
+    offline_dialog_connector: Connector<OfflineDialogModel>,
+    preferences_dialog_controller: Controller<PreferencesDialogModel>,
 }
 
 pub struct AppWidgets {
-    questions_tabs: FactoryVecDeque<componant_builders::QuestionPageModel>,
+    questions_tabs: FactoryVecDeque<tab_page::TabPageModel>,
+    toast_overlay: adw::ToastOverlay,
+    quota_label: gtk::Label,
     sidebar_toggle_button: gtk::ToggleButton,
 }
 
@@ -51,7 +116,7 @@ impl AsyncComponent for AppModel {
     type Root = adw::Window;
     type Widgets = AppWidgets;
     type Input = AppInput;
-    type CommandOutput = ();
+    type CommandOutput = AppCommand;
     type Output = ();
 
     fn init_root() -> Self::Root {
@@ -74,13 +139,81 @@ impl AsyncComponent for AppModel {
         root: Self::Root,
         sender: AsyncComponentSender<Self>,
     ) -> AsyncComponentParts<Self> {
+        let mut load_errors = Vec::new();
+
+        let settings = Settings::load().unwrap_or_else(|error| {
+            load_errors.push(fl!("settings-load-failed", error = error.to_string()));
+            Settings::default()
+        });
+
+        let stackexchange_client =
+            backend::from_env(preferences_dialog::resolve_system_proxy(settings.api.clone()).await);
+
+        match BookmarkStore::load() {
+            Ok(bookmarks) => *BOOKMARKS.write() = bookmarks,
+            Err(error) => load_errors.push(fl!("bookmarks-load-failed", error = error.to_string())),
+        }
+
+        match HistoryStore::load() {
+            Ok(history) => *HISTORY.write() = history,
+            Err(error) => load_errors.push(fl!("history-load-failed", error = error.to_string())),
+        }
+
+        let session = Session::load().unwrap_or_else(|error| {
+            load_errors.push(fl!("session-load-failed", error = error.to_string()));
+            Session::default()
+        });
+
+        let offline_dialog_connector = OfflineDialogModel::builder().launch(OfflineDialogInit {
+            client: stackexchange_client.clone(),
+            main_window: relm4::main_application().active_window().unwrap(),
+        });
+
         let model = AppModel {
-            stackexchange_client: stackexchange::StackExchange::new(),
-            side_bar_controller: side_bar::SideBarModel::builder()
-                .launch(())
-                .forward(sender.input_sender(), |_message| unreachable!()),
+            site_picker_controller: site_picker::SitePickerModel::builder()
+                .launch(site_picker::SitePickerInit {
+                    client: stackexchange_client.clone(),
+                    selected_site: sites::DEFAULT_SITE.to_owned(),
+                })
+                .forward(sender.input_sender(), |message| match message {
+                    site_picker::SitePickerOutput::SiteSelected(site) => {
+                        AppInput::SelectSearchSite(site)
+                    }
+                }),
+            stackexchange_client,
+            search_site: sites::DEFAULT_SITE.to_owned(),
+            history_save_pending: false,
+            side_bar_controller: side_bar::SideBarModel::builder().launch(()).forward(
+                sender.input_sender(),
+                |message| match message {
+                    side_bar::SideBarOutput::RequestPagesByUri(uri) => {
+                        AppInput::RequestPagesByUri(uri)
+                    }
+                    side_bar::SideBarOutput::UpdateBookmark(bookmark) => {
+                        AppInput::UpdateBookmark(bookmark)
+                    }
+                    side_bar::SideBarOutput::RemoveBookmark(site, question_id) => {
+                        AppInput::RemoveBookmark(site, question_id)
+                    }
+                    side_bar::SideBarOutput::RemoveHistoryEntry(site, question_id, visited) => {
+                        AppInput::RemoveHistoryEntry(site, question_id, visited)
+                    }
+                    side_bar::SideBarOutput::ClearHistory(from, to) => {
+                        AppInput::ClearHistory(from, to)
+                    }
+                },
+            ),
             about_window_connector: AboutWindow::builder()
                 .launch(relm4::main_application().active_window().unwrap()),
+            offline_dialog_connector,
+            preferences_dialog_controller: PreferencesDialogModel::builder()
+                .launch(PreferencesDialogInit {
+                    settings,
+                    main_window: relm4::main_application().active_window().unwrap(),
+                })
+                .forward(sender.input_sender(), |message| match message {
+                    PreferencesDialogOutput::Saved(result) => AppInput::PreferencesSaved(result),
+                }),
         };
 
         // Load CSS
@@ -103,23 +236,40 @@ impl AsyncComponent for AppModel {
         // TODO: Use the search entry for searching inside a page.
         // TODO: Use the new tab page as a search page.
         let search_entry = gtk::SearchEntry::builder()
-            // TODO: Make clickable icon to select a stackexchange site to search in.
             .placeholder_text(fl!("search-entry-placeholder"))
+            .hexpand(true)
             .build();
 
         search_entry.connect_activate(gtk::glib::clone!(@strong sender => move |entry| {
             let search_term = entry.text();
-            // TODO: Change how search_term is parsed to support urls and terms at the same time.
-            // TODO: Connect it to search api
-            // TODO: Don't accept uris.
-            // TODO: Support all stackexchange sites: https://api.stackexchange.com/docs/sites
-            sender.input(AppInput::RequestPagesByUri(format!("stackbloatless://stackoverflow/{search_term}")));
-            entry.delete_text(0, search_term.len() as i32);
+            let search_term = search_term.trim();
+
+            if search_term.is_empty() {
+                return;
+            } else if router::is_uri(search_term) {
+                // Pasted links are opened directly.
+                sender.input(AppInput::RequestPagesByUri(search_term.to_owned()));
+            } else if search_term.chars().all(|c| c.is_ascii_digit() || c == ';') {
+                // Questions ids are opened directly.
+                sender.input(AppInput::OpenQuestionsByIds(search_term.to_owned()));
+            } else {
+                sender.input(AppInput::SearchQuestions(search_term.into()));
+            }
+
+            entry.set_text("");
         }));
 
+        // Site selector and search entry are linked together.
+        let search_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .css_classes(["linked"])
+            .build();
+        search_layout.append(model.site_picker_controller.widget());
+        search_layout.append(&search_entry);
+
         // Create header bar
         let header = adw::HeaderBar::builder()
-            .title_widget(&search_entry)
+            .title_widget(&search_layout)
             .hexpand(true)
             .show_end_title_buttons(true)
             .build();
@@ -130,6 +280,8 @@ impl AsyncComponent for AppModel {
         // TODO: Create action to show GtkShortcutsWindow.
         relm4::new_action_group!(MenuActionGroup, "menu");
         relm4::new_stateless_action!(AboutAction, MenuActionGroup, "about");
+        relm4::new_stateless_action!(OfflineAction, MenuActionGroup, "download_offline");
+        relm4::new_stateless_action!(PreferencesAction, MenuActionGroup, "preferences");
         relm4::new_stateless_action!(ToggleSideBarAction, MenuActionGroup, "toggle_sidebar");
         relm4::new_stateless_action!(QuitAction, MenuActionGroup, "quit");
         {
@@ -143,6 +295,22 @@ impl AsyncComponent for AppModel {
                 );
             group.add_action(about_action);
 
+            let offline_action: relm4::actions::RelmAction<OfflineAction> =
+                relm4::actions::RelmAction::new_stateless(
+                    gtk::glib::clone!(@strong sender => move |_| {
+                        sender.input(AppInput::ShowOfflineDialog);
+                    }),
+                );
+            group.add_action(offline_action);
+
+            let preferences_action: relm4::actions::RelmAction<PreferencesAction> =
+                relm4::actions::RelmAction::new_stateless(
+                    gtk::glib::clone!(@strong sender => move |_| {
+                        sender.input(AppInput::ShowPreferencesDialog);
+                    }),
+                );
+            group.add_action(preferences_action);
+
             let toggle_sidebar_action: relm4::actions::RelmAction<ToggleSideBarAction> =
                 relm4::actions::RelmAction::new_stateless(
                     gtk::glib::clone!(@strong sender => move |_| {
@@ -164,12 +332,16 @@ impl AsyncComponent for AppModel {
 
         relm4::menu! {
             main_menu: {
+                &fl!("offline-download") => OfflineAction,
+                &fl!("preferences") => PreferencesAction,
                 &fl!("about") => AboutAction,
                 &fl!("quit") => QuitAction
             }
         }
 
         relm4::main_application().set_accelerators_for_action::<ToggleSideBarAction>(&["F9"]);
+        relm4::main_application()
+            .set_accelerators_for_action::<PreferencesAction>(&["<Control>comma"]);
         relm4::main_application().set_accelerators_for_action::<QuitAction>(&["<Control>q"]);
 
         // Create hamburger menu
@@ -228,9 +400,18 @@ impl AsyncComponent for AppModel {
             .build();
 
         // Create tabs factory to manage tabs.
-        let questions_tabs = FactoryVecDeque::<componant_builders::QuestionPageModel>::builder()
+        let questions_tabs = FactoryVecDeque::<tab_page::TabPageModel>::builder()
             .launch(tab_view)
-            .forward(sender.input_sender(), |_message| unreachable!());
+            .forward(sender.input_sender(), |message| match message {
+                tab_page::TabPageOutput::RequestPagesByUri(uri) => AppInput::RequestPagesByUri(uri),
+                tab_page::TabPageOutput::AddBookmark(bookmark) => AppInput::AddBookmark(bookmark),
+                tab_page::TabPageOutput::RemoveBookmark(site, question_id) => {
+                    AppInput::RemoveBookmark(site, question_id)
+                }
+                tab_page::TabPageOutput::Scrolled(site, question_id, scroll) => {
+                    AppInput::UpdateHistoryScroll(site, question_id, scroll)
+                }
+            });
         // A refrence for TabView that is owned by FactoryVecDeque.
         let tab_view = questions_tabs.widget();
 
@@ -248,6 +429,11 @@ impl AsyncComponent for AppModel {
 
         tab_bar.set_view(Some(tab_view));
 
+        // Restored tabs are only loaded when they are selected.
+        tab_view.connect_selected_page_notify(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(AppInput::LoadSelectedTab);
+        }));
+
         tab_view.connect_setup_menu(|view, page| {
             if let Some(page) = page {
                 view.set_selected_page(page);
@@ -270,16 +456,47 @@ impl AsyncComponent for AppModel {
             .build();
         header.pack_end(&tab_button);
 
+        // Show errors and notifications over the main layout.
+        let toast_overlay = adw::ToastOverlay::builder().child(&main_layout).build();
+
+        for message in load_errors {
+            toast_overlay.add_toast(
+                adw::Toast::builder()
+                    .title(message)
+                    .use_markup(false)
+                    .timeout(0)
+                    .build(),
+            );
+        }
+
         // Create tabs overview
         // FIX: Whene the last tab is closed, close the overview.
         let tab_overview = adw::TabOverview::builder()
             .view(tab_view)
             // TODO: Implement new tab
             // .enable_new_tab(true)
-            .child(&main_layout)
+            .child(&toast_overlay)
             .build();
         root.set_content(Some(&tab_overview));
 
+        // Create API quota indicator in the header
+        let quota_label = gtk::Label::builder()
+            .css_classes(["dim-label", "caption"])
+            .tooltip_text(fl!("quota-tooltip"))
+            .visible(false)
+            .build();
+        header.pack_end(&quota_label);
+
+        // Keep the indicator updated with every response, when the backend has a quota.
+        if let Some(mut quota_receiver) = model.stackexchange_client.subscribe_quota() {
+            relm4::spawn_local(gtk::glib::clone!(@strong sender => async move {
+                while quota_receiver.changed().await.is_ok() {
+                    let quota = *quota_receiver.borrow_and_update();
+                    sender.input(AppInput::QuotaUpdated(quota));
+                }
+            }));
+        }
+
         // Create sidebar button in the header
         let sidebar_toggle_button = gtk::ToggleButton::builder()
             .icon_name(icon_names::DOCK_RIGHT)
@@ -299,11 +516,15 @@ impl AsyncComponent for AppModel {
             }),
         );
 
-        let widgets = AppWidgets {
+        let mut widgets = AppWidgets {
             questions_tabs,
+            toast_overlay,
+            quota_label,
             sidebar_toggle_button,
         };
 
+        model.restore_session(&mut widgets, session);
+
         AsyncComponentParts { model, widgets }
     }
 
@@ -316,18 +537,211 @@ impl AsyncComponent for AppModel {
     ) {
         match message {
             AppInput::RequestPagesByUri(uri) => {
-                // TODO: Handle request errors.
-                let questions = self
-                    .stackexchange_client
-                    .get_questions_from_uri(&uri)
-                    .await
-                    .unwrap();
+                let app_uri = match AppUri::parse(&uri) {
+                    Ok(app_uri) => app_uri,
+                    Err(error) => {
+                        widgets.toast_overlay.add_toast(
+                            adw::Toast::builder()
+                                .title(api_error_message(&error))
+                                .use_markup(false)
+                                .build(),
+                        );
+                        return;
+                    }
+                };
 
-                for question in questions {
-                    widgets
-                        .questions_tabs
-                        .guard()
-                        .push_front(componant_builders::QuestionPageInit { question });
+                let site = app_uri.site;
+
+                match app_uri.route {
+                    // Run it in the background, so concurrent requests could be batched together.
+                    Route::Questions {
+                        ids,
+                        answer_id,
+                        comment_id,
+                    } => {
+                        let client = self.stackexchange_client.clone();
+                        let focus = (answer_id.is_some() || comment_id.is_some()).then_some(
+                            componant_builders::PostFocus {
+                                answer_id,
+                                comment_id,
+                            },
+                        );
+
+                        sender.oneshot_command(async move {
+                            let result = client.get_questions(&site, &ids).await;
+                            AppCommand::QuestionsLoaded(site, result, focus)
+                        });
+                    }
+                    Route::Answer {
+                        answer_id,
+                        comment_id,
+                    } => {
+                        let client = self.stackexchange_client.clone();
+
+                        sender.oneshot_command(async move {
+                            let result = match client.get_answer_question_id(&site, answer_id).await
+                            {
+                                Ok(Some(question_id)) => {
+                                    client.get_questions(&site, &[question_id]).await
+                                }
+                                Ok(None) => Ok(Vec::new()),
+                                Err(error) => Err(error),
+                            };
+
+                            let focus = componant_builders::PostFocus {
+                                answer_id: Some(answer_id),
+                                comment_id,
+                            };
+
+                            AppCommand::QuestionsLoaded(site, result, Some(focus))
+                        });
+                    }
+                    Route::Search(query) => {
+                        Self::open_tab(
+                            widgets,
+                            tab_page::TabPageInit::Search(search_page::SearchPageInit {
+                                client: self.stackexchange_client.clone(),
+                                site,
+                                query,
+                            }),
+                        );
+                    }
+                    Route::Tag(tag) => {
+                        Self::open_tab(
+                            widgets,
+                            tab_page::TabPageInit::Search(search_page::SearchPageInit {
+                                client: self.stackexchange_client.clone(),
+                                site,
+                                query: stackexchange::SearchQuery {
+                                    tagged: vec![tag],
+                                    sort: stackexchange::SearchSort::Votes,
+                                    ..Default::default()
+                                },
+                            }),
+                        );
+                    }
+                    Route::User(user_id) => {
+                        Self::open_tab(
+                            widgets,
+                            tab_page::TabPageInit::User(user_page::UserPageInit {
+                                client: self.stackexchange_client.clone(),
+                                site,
+                                user_id,
+                            }),
+                        );
+                    }
+                }
+            }
+            AppInput::OpenQuestionsByIds(ids) => {
+                sender.input(AppInput::RequestPagesByUri(format!(
+                    "{}://{}/questions/{ids}",
+                    router::URI_SCHEME,
+                    self.search_site
+                )));
+            }
+            AppInput::SearchQuestions(query) => {
+                Self::open_tab(
+                    widgets,
+                    tab_page::TabPageInit::Search(search_page::SearchPageInit {
+                        client: self.stackexchange_client.clone(),
+                        site: self.search_site.clone(),
+                        query,
+                    }),
+                );
+            }
+            AppInput::SelectSearchSite(site) => {
+                self.search_site = site.api_site_parameter;
+            }
+            AppInput::QuotaUpdated(quota) => match quota {
+                Some(quota) => {
+                    widgets.quota_label.set_label(&fl!(
+                        "quota-label",
+                        remaining = quota.remaining,
+                        max = quota.max
+                    ));
+                    widgets.quota_label.set_visible(true);
+                }
+                None => widgets.quota_label.set_visible(false),
+            },
+            AppInput::AddBookmark(bookmark) => {
+                let mut bookmarks = BOOKMARKS.write();
+                bookmarks.add(bookmark);
+
+                if let Err(error) = bookmarks.save() {
+                    Self::show_save_error(
+                        widgets,
+                        fl!("bookmarks-save-failed", error = error.to_string()),
+                    );
+                }
+            }
+            AppInput::UpdateBookmark(bookmark) => {
+                let mut bookmarks = BOOKMARKS.write();
+                bookmarks.update(bookmark);
+
+                if let Err(error) = bookmarks.save() {
+                    Self::show_save_error(
+                        widgets,
+                        fl!("bookmarks-save-failed", error = error.to_string()),
+                    );
+                }
+            }
+            AppInput::RemoveBookmark(site, question_id) => {
+                let mut bookmarks = BOOKMARKS.write();
+                bookmarks.remove(&site, question_id);
+
+                if let Err(error) = bookmarks.save() {
+                    Self::show_save_error(
+                        widgets,
+                        fl!("bookmarks-save-failed", error = error.to_string()),
+                    );
+                }
+            }
+            AppInput::UpdateHistoryScroll(site, question_id, scroll) => {
+                // Scrolling doesn't change what the history page shows, so don't notify it.
+                HISTORY.write_inner().set_scroll(&site, question_id, scroll);
+
+                // Saved later with every position changed meanwhile.
+                if !self.history_save_pending {
+                    self.history_save_pending = true;
+
+                    gtk::glib::timeout_add_local_once(
+                        HISTORY_SAVE_DELAY,
+                        gtk::glib::clone!(@strong sender => move || {
+                            sender.input(AppInput::SaveHistory);
+                        }),
+                    );
+                }
+            }
+            AppInput::SaveHistory => {
+                self.history_save_pending = false;
+
+                if let Err(error) = HISTORY.read().save() {
+                    Self::show_save_error(
+                        widgets,
+                        fl!("history-save-failed", error = error.to_string()),
+                    );
+                }
+            }
+            AppInput::RemoveHistoryEntry(site, question_id, visited) => {
+                let mut history = HISTORY.write();
+                history.remove(&site, question_id, visited);
+
+                if let Err(error) = history.save() {
+                    Self::show_save_error(
+                        widgets,
+                        fl!("history-save-failed", error = error.to_string()),
+                    );
+                }
+            }
+            AppInput::ClearHistory(from, to) => {
+                let mut history = HISTORY.write();
+                history.clear_range(from, to);
+
+                if let Err(error) = history.save() {
+                    Self::show_save_error(
+                        widgets,
+                        fl!("history-save-failed", error = error.to_string()),
+                    );
                 }
             }
             AppInput::ShowAboutWindow => {
@@ -336,6 +750,28 @@ impl AsyncComponent for AppModel {
                     .send(AboutWindowInput::ShowWindow)
                     .unwrap();
             }
+            AppInput::ShowOfflineDialog => {
+                self.offline_dialog_connector
+                    .sender()
+                    .send(OfflineDialogInput::Show(self.search_site.clone()))
+                    .unwrap();
+            }
+            AppInput::ShowPreferencesDialog => {
+                self.preferences_dialog_controller
+                    .emit(PreferencesDialogInput::Show);
+            }
+            AppInput::PreferencesSaved(result) => match result {
+                Ok(()) => {
+                    widgets.toast_overlay.add_toast(
+                        adw::Toast::builder()
+                            .title(fl!("preferences-saved"))
+                            .build(),
+                    );
+                }
+                Err(error) => {
+                    Self::show_save_error(widgets, fl!("settings-save-failed", error = error));
+                }
+            },
             AppInput::ToggleSideBar => {
                 widgets.sidebar_toggle_button.emit_clicked();
             }
@@ -395,8 +831,240 @@ impl AsyncComponent for AppModel {
 
                 widgets.questions_tabs.guard().remove(page_index);
             }
+            AppInput::LoadSelectedTab => {
+                let Some(selected_page) = widgets.questions_tabs.widget().selected_page() else {
+                    return;
+                };
+
+                if let Some(index) = widgets
+                    .questions_tabs
+                    .iter()
+                    .position(|tab| tab.tab_page() == Some(&selected_page))
+                {
+                    widgets
+                        .questions_tabs
+                        .send(index, tab_page::TabPageInput::Load);
+                }
+            }
+        }
+    }
+
+    async fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        _sender: AsyncComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            AppCommand::QuestionsLoaded(site, result, mut focus) => match result {
+                Ok(questions) if questions.is_empty() => {
+                    widgets.toast_overlay.add_toast(
+                        adw::Toast::builder()
+                            .title(fl!("no-questions-found"))
+                            .build(),
+                    );
+                }
+                Ok(questions) => {
+                    {
+                        let mut history = HISTORY.write();
+                        for question in &questions {
+                            history.record(&site, question);
+                        }
+
+                        if let Err(error) = history.save() {
+                            Self::show_save_error(
+                                widgets,
+                                fl!("history-save-failed", error = error.to_string()),
+                            );
+                        }
+                    }
+
+                    for question in questions {
+                        let focus = focus.take();
+                        let is_focused = focus.is_some();
+
+                        let init =
+                            tab_page::TabPageInit::Question(componant_builders::QuestionPageInit {
+                                question,
+                                site: site.clone(),
+                                client: self.stackexchange_client.clone(),
+                                focus,
+                            });
+
+                        // Show the linked post right away.
+                        if is_focused {
+                            Self::open_tab(widgets, init);
+                        } else {
+                            widgets.questions_tabs.guard().push_front(init);
+                        }
+                    }
+                }
+                Err(error) => {
+                    widgets.toast_overlay.add_toast(
+                        adw::Toast::builder()
+                            .title(api_error_message(&error))
+                            .use_markup(false)
+                            .timeout(0)
+                            .build(),
+                    );
+                }
+            },
+        }
+    }
+
+    fn shutdown(&mut self, widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
+        // Nothing could be shown about errors while quitting.
+        let _ = Self::current_session(widgets).save();
+
+        if self.history_save_pending {
+            let _ = HISTORY.read().save();
+        }
+    }
+}
+
+impl AppModel {
+    fn show_save_error(widgets: &AppWidgets, message: String) {
+        widgets.toast_overlay.add_toast(
+            adw::Toast::builder()
+                .title(message)
+                .use_markup(false)
+                .build(),
+        );
+    }
+
+    fn restore_session(&self, widgets: &mut AppWidgets, session: Session) {
+        let pinned: Vec<bool> = session.tabs.iter().map(|tab| tab.pinned).collect();
+
+        {
+            let mut questions_tabs = widgets.questions_tabs.guard();
+
+            for tab in session.tabs {
+                questions_tabs.push_back(tab_page::TabPageInit::Restored {
+                    tab,
+                    client: self.stackexchange_client.clone(),
+                });
+            }
+        }
+
+        let tab_view = widgets.questions_tabs.widget();
+
+        // Pinned tabs are always the first ones, so their order isn't changed.
+        for (tab, pinned) in widgets.questions_tabs.iter().zip(pinned) {
+            if let Some(tab_page) = tab.tab_page().filter(|_| pinned) {
+                tab_view.set_page_pinned(tab_page, true);
+            }
+        }
+
+        if let Some(tab_page) = session
+            .selected
+            .and_then(|index| widgets.questions_tabs.get(index))
+            .and_then(|tab| tab.tab_page())
+        {
+            tab_view.set_selected_page(tab_page);
+        }
+    }
+
+    // Tabs in the same order of the tab bar.
+    fn current_session(widgets: &AppWidgets) -> Session {
+        let tab_view = widgets.questions_tabs.widget();
+        let selected_page = tab_view.selected_page();
+
+        let mut session = Session::default();
+
+        // Closed tabs are still in the factory, only look for the shown ones.
+        for position in 0..tab_view.n_pages() {
+            let tab_page = tab_view.nth_page(position);
+
+            let Some(tab) = widgets
+                .questions_tabs
+                .iter()
+                .find(|tab| tab.tab_page() == Some(&tab_page))
+                .and_then(|tab| tab.session_tab())
+            else {
+                continue;
+            };
+
+            if selected_page.as_ref() == Some(&tab_page) {
+                session.selected = Some(session.tabs.len());
+            }
+
+            session.tabs.push(tab);
         }
+
+        session
     }
 
-    fn shutdown(&mut self, _widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {}
+    /// Open a tab and show it right away.
+    fn open_tab(widgets: &mut AppWidgets, init: tab_page::TabPageInit) {
+        widgets.questions_tabs.guard().push_front(init);
+
+        if let Some(tab_page) = widgets
+            .questions_tabs
+            .front()
+            .and_then(|tab| tab.tab_page())
+        {
+            widgets.questions_tabs.widget().set_selected_page(tab_page);
+        }
+    }
+}
+
+/// User facing description of an API error.
+pub fn api_error_message(error: &ApiError) -> String {
+    match error {
+        ApiError::Transport(_) => fl!("api-error-transport"),
+        ApiError::HttpStatus(status) => fl!("api-error-http-status", status = *status),
+        ApiError::Api {
+            name: ApiErrorName::ThrottleViolation,
+            message,
+            ..
+        } => fl!("api-error-throttle-violation", message = message.as_str()),
+        ApiError::Api {
+            name: ApiErrorName::AccessDenied,
+            message,
+            ..
+        } => fl!("api-error-access-denied", message = message.as_str()),
+        ApiError::Api {
+            name: ApiErrorName::NoMethod,
+            ..
+        } => fl!("api-error-no-method"),
+        ApiError::Api {
+            name: ApiErrorName::TemporarilyUnavailable,
+            ..
+        } => fl!("api-error-temporarily-unavailable"),
+        ApiError::Api { id, name, message } => fl!(
+            "api-error-other",
+            id = *id,
+            name = name.to_string(),
+            message = message.as_str()
+        ),
+        ApiError::Decode(_) => fl!("api-error-decode"),
+        ApiError::InvalidUri { uri, reason } => fl!(
+            "api-error-invalid-uri",
+            uri = uri.as_str(),
+            reason = uri_error_message(reason)
+        ),
+        ApiError::QuotaExhausted => fl!("api-error-quota-exhausted"),
+        ApiError::Throttled { retry_after } => {
+            fl!("api-error-throttled", seconds = retry_after.as_secs())
+        }
+        ApiError::Dump(error) => fl!("api-error-dump", error = error.as_str()),
+        ApiError::Unsupported(_) => fl!("api-error-unsupported"),
+        ApiError::Config(error) => fl!("api-error-config", error = error.as_str()),
+    }
+}
+
+fn uri_error_message(error: &UriError) -> String {
+    match error {
+        UriError::Malformed => fl!("uri-error-malformed"),
+        UriError::UnsupportedScheme(scheme) => {
+            fl!("uri-error-unsupported-scheme", scheme = scheme.as_str())
+        }
+        UriError::UnknownSite(site) => fl!("uri-error-unknown-site", site = site.as_str()),
+        UriError::UnknownRoute(path) => fl!("uri-error-unknown-route", path = path.as_str()),
+        UriError::InvalidId(id) => fl!("uri-error-invalid-id", id = id.as_str()),
+        UriError::MissingParameter(parameter) => {
+            fl!("uri-error-missing-parameter", parameter = *parameter)
+        }
+    }
 }
@@ -7,6 +7,7 @@ glib::wrapper! {
 }
 
 impl CellObject {
+    /// `string` is the Pango markup of the cell.
     pub fn new(string: Option<&str>, is_header: bool) -> Self {
         glib::Object::builder()
             .property("string", string)
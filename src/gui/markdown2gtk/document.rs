@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+
+use markdown::mdast;
+
+/// Markdown that couldn't be parsed, e.g. with syntax of extensions that are not enabled.
+#[derive(Debug, Clone)]
+pub struct MarkdownError {
+    pub message: String,
+}
+
+impl std::fmt::Display for MarkdownError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "can't parse markdown: {}", self.message)
+    }
+}
+
+impl std::error::Error for MarkdownError {}
+
+/// A markdown post as a tree of blocks and inlines, without anything of GTK.
+///
+/// It's what the renderer shows: references are resolved, and site hints and front matter are
+/// dropped. Tables and images are shown as widgets at anchors of the text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    /// Level from 1 to 6.
+    Heading {
+        level: u8,
+        content: Vec<Inline>,
+    },
+    Paragraph(Vec<Inline>),
+    /// Code and math blocks.
+    Code(String),
+    Quote(Vec<Block>),
+    /// `start` is the number of the first item of ordered lists, `None` for bullet lists.
+    List {
+        start: Option<u32>,
+        items: Vec<ListItem>,
+    },
+    /// Rows of cells, the first row is the header.
+    Table(Vec<Vec<Vec<Inline>>>),
+    Rule,
+    Footnote {
+        label: String,
+        blocks: Vec<Block>,
+    },
+    Html(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListItem {
+    /// State of task list items, like `- [x] done`.
+    pub checked: Option<bool>,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Code(String),
+    Styled(Style, Vec<Inline>),
+    Link { url: String, content: Vec<Inline> },
+    Image { url: String, alt: String },
+    FootnoteReference(String),
+    Break,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Strong,
+    Emphasis,
+    Strikethrough,
+}
+
+impl Document {
+    pub fn parse(markdown_text: &str) -> Result<Self, MarkdownError> {
+        // https://stackoverflow.com/editing-help
+        // https://github.com/wooorm/markdown-rs
+        // https://github.com/syntax-tree/mdast
+        // https://docs.rs/markdown/1.0.0-alpha.7/markdown/mdast/enum.Node.html
+        let tree =
+            markdown::to_mdast(markdown_text, &markdown::ParseOptions::gfm()).map_err(|error| {
+                MarkdownError {
+                    message: error.to_string(),
+                }
+            })?;
+
+        let mut builder = Builder::default();
+
+        // References could be used before their definitions.
+        builder.collect_definitions(&tree);
+
+        Ok(Self {
+            blocks: builder.blocks(tree.children().map(Vec::as_slice).unwrap_or_default()),
+        })
+    }
+}
+
+impl Block {
+    pub fn plain_text(&self) -> String {
+        match self {
+            Self::Heading { content, .. } | Self::Paragraph(content) => inlines_plain_text(content),
+            Self::Code(code) => code.clone(),
+            Self::Quote(blocks) => blocks_plain_text(blocks),
+            Self::List { start, items } => items
+                .iter()
+                .enumerate()
+                .map(|(num, item)| {
+                    let marker = match start {
+                        Some(start) => format!("{}. ", *start as usize + num),
+                        None => "• ".to_owned(),
+                    };
+
+                    format!("{marker}{}", blocks_plain_text(&item.blocks))
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Self::Table(rows) => rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| inlines_plain_text(cell))
+                        .collect::<Vec<String>>()
+                        .join("\t")
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Self::Rule => String::new(),
+            Self::Footnote { label, blocks } => format!("[{label}] {}", blocks_plain_text(blocks)),
+            Self::Html(html) => html.clone(),
+        }
+    }
+}
+
+impl Inline {
+    pub fn plain_text(&self) -> String {
+        match self {
+            Self::Text(text) | Self::Code(text) => text.clone(),
+            Self::Styled(_, content) | Self::Link { content, .. } => inlines_plain_text(content),
+            Self::Image { alt, .. } => alt.clone(),
+            Self::FootnoteReference(label) => format!("[{label}]"),
+            Self::Break => "\n".to_owned(),
+        }
+    }
+}
+
+pub fn blocks_plain_text(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .map(Block::plain_text)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn inlines_plain_text(inlines: &[Inline]) -> String {
+    inlines.iter().map(Inline::plain_text).collect()
+}
+
+#[derive(Default)]
+struct Builder {
+    // Urls of link and image references, by their normalized identifier.
+    definitions: HashMap<String, String>,
+}
+
+impl Builder {
+    fn collect_definitions(&mut self, node: &mdast::Node) {
+        if let mdast::Node::Definition(definition) = node {
+            self.definitions
+                .insert(definition.identifier.clone(), definition.url.clone());
+        }
+
+        for child in node.children().into_iter().flatten() {
+            self.collect_definitions(child);
+        }
+    }
+
+    fn blocks(&self, nodes: &[mdast::Node]) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        // Inline nodes outside of a paragraph are grouped in one.
+        let mut inlines = Vec::new();
+
+        for node in nodes {
+            match self.block(node) {
+                Some(Ok(block)) => {
+                    if !inlines.is_empty() {
+                        blocks.push(Block::Paragraph(std::mem::take(&mut inlines)));
+                    }
+
+                    blocks.push(block);
+                }
+                Some(Err(inline)) => inlines.push(inline),
+                None => {}
+            }
+        }
+
+        if !inlines.is_empty() {
+            blocks.push(Block::Paragraph(inlines));
+        }
+
+        blocks
+    }
+
+    // A block, or an inline when the node is not a block, `None` for hidden nodes.
+    fn block(&self, node: &mdast::Node) -> Option<Result<Block, Inline>> {
+        let block = match node {
+            mdast::Node::BlockQuote(quote) => Block::Quote(self.blocks(&quote.children)),
+            mdast::Node::List(list) => Block::List {
+                start: list.ordered.then(|| list.start.unwrap_or(1)),
+                items: list
+                    .children
+                    .iter()
+                    .map(|node| match node {
+                        mdast::Node::ListItem(list_item) => ListItem {
+                            checked: list_item.checked,
+                            blocks: self.blocks(&list_item.children),
+                        },
+                        node => ListItem {
+                            checked: None,
+                            blocks: self.blocks(std::slice::from_ref(node)),
+                        },
+                    })
+                    .collect(),
+            },
+            mdast::Node::ListItem(list_item) => Block::List {
+                start: None,
+                items: vec![ListItem {
+                    checked: list_item.checked,
+                    blocks: self.blocks(&list_item.children),
+                }],
+            },
+            mdast::Node::Heading(heading) => Block::Heading {
+                level: heading.depth.clamp(1, 6),
+                content: self.inlines(&heading.children),
+            },
+            mdast::Node::Table(table) => Block::Table(
+                table
+                    .children
+                    .iter()
+                    .map(|row| {
+                        row.children()
+                            .map(Vec::as_slice)
+                            .unwrap_or_default()
+                            .iter()
+                            .map(|cell| {
+                                self.inlines(cell.children().map(Vec::as_slice).unwrap_or_default())
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            ),
+            mdast::Node::Paragraph(paragraph) => {
+                Block::Paragraph(self.inlines(&paragraph.children))
+            }
+            mdast::Node::Code(code) => Block::Code(code.value.clone()),
+            mdast::Node::Math(math) => Block::Code(math.value.clone()),
+            mdast::Node::ThematicBreak(_) => Block::Rule,
+            mdast::Node::FootnoteDefinition(footnote) => Block::Footnote {
+                label: footnote_label(&footnote.identifier, &footnote.label).to_owned(),
+                blocks: self.blocks(&footnote.children),
+            },
+            mdast::Node::Html(html) => {
+                // Comments are hints for the site, like `<!-- language: lang-rust -->`.
+                if html.value.trim_start().starts_with("<!--") {
+                    return None;
+                }
+
+                // TODO: Convert simple HTML to GTK.
+                Block::Html(html.value.clone())
+            }
+            // Only used to resolve references, see `collect_definitions`.
+            mdast::Node::Definition(_) => return None,
+            // Front matter is not a part of the content.
+            mdast::Node::Yaml(_) | mdast::Node::Toml(_) => return None,
+            node => return self.inline(node).map(Err),
+        };
+
+        Some(Ok(block))
+    }
+
+    fn inlines(&self, nodes: &[mdast::Node]) -> Vec<Inline> {
+        nodes.iter().filter_map(|node| self.inline(node)).collect()
+    }
+
+    fn inline(&self, node: &mdast::Node) -> Option<Inline> {
+        let inline = match node {
+            mdast::Node::Text(text) => Inline::Text(text.value.clone()),
+            mdast::Node::InlineCode(code) => Inline::Code(code.value.clone()),
+            mdast::Node::InlineMath(math) => Inline::Code(math.value.clone()),
+            mdast::Node::Break(_) => Inline::Break,
+            mdast::Node::Strong(strong) => {
+                Inline::Styled(Style::Strong, self.inlines(&strong.children))
+            }
+            mdast::Node::Emphasis(emphasis) => {
+                Inline::Styled(Style::Emphasis, self.inlines(&emphasis.children))
+            }
+            mdast::Node::Delete(delete) => {
+                Inline::Styled(Style::Strikethrough, self.inlines(&delete.children))
+            }
+            mdast::Node::Link(link) => Inline::Link {
+                url: link.url.clone(),
+                content: self.inlines(&link.children),
+            },
+            mdast::Node::LinkReference(link_ref) => {
+                match self.definitions.get(&link_ref.identifier) {
+                    Some(url) => Inline::Link {
+                        url: url.clone(),
+                        content: self.inlines(&link_ref.children),
+                    },
+                    // Not a reference without a definition, like `[this]`.
+                    None => Inline::Text(format!("[{}]", node.to_string())),
+                }
+            }
+            mdast::Node::Image(image) => Inline::Image {
+                url: image.url.clone(),
+                alt: image.alt.clone(),
+            },
+            mdast::Node::ImageReference(image_ref) => {
+                match self.definitions.get(&image_ref.identifier) {
+                    Some(url) => Inline::Image {
+                        url: url.clone(),
+                        alt: image_ref.alt.clone(),
+                    },
+                    None => Inline::Text(format!("![{}]", image_ref.alt)),
+                }
+            }
+            mdast::Node::FootnoteReference(footnote_ref) => Inline::FootnoteReference(
+                footnote_label(&footnote_ref.identifier, &footnote_ref.label).to_owned(),
+            ),
+            mdast::Node::Html(html) => {
+                if html.value.trim_start().starts_with("<!--") {
+                    return None;
+                }
+
+                Inline::Text(html.value.clone())
+            }
+            // Blocks can't be in inlines, anything else (e.g. MDX) is shown as its text.
+            node => Inline::Text(node.to_string()),
+        };
+
+        Some(inline)
+    }
+}
+
+// Footnotes are shown with their label as written, like `[^note]` is shown as `[note]`.
+fn footnote_label<'a>(identifier: &'a str, label: &'a Option<String>) -> &'a str {
+    label.as_deref().unwrap_or(identifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Inline {
+        Inline::Text(text.to_owned())
+    }
+
+    fn paragraph(text: &str) -> Block {
+        Block::Paragraph(vec![Inline::Text(text.to_owned())])
+    }
+
+    fn parse(markdown_text: &str) -> Vec<Block> {
+        Document::parse(markdown_text).unwrap().blocks
+    }
+
+    #[test]
+    fn parses_nested_quotes_and_lists() {
+        assert_eq!(
+            parse("> quote\n>\n> > nested\n\n- a\n  1. b\n  2. c\n- d"),
+            [
+                Block::Quote(vec![
+                    paragraph("quote"),
+                    Block::Quote(vec![paragraph("nested")]),
+                ]),
+                Block::List {
+                    start: None,
+                    items: vec![
+                        ListItem {
+                            checked: None,
+                            blocks: vec![
+                                paragraph("a"),
+                                Block::List {
+                                    start: Some(1),
+                                    items: vec![
+                                        ListItem {
+                                            checked: None,
+                                            blocks: vec![paragraph("b")],
+                                        },
+                                        ListItem {
+                                            checked: None,
+                                            blocks: vec![paragraph("c")],
+                                        },
+                                    ],
+                                },
+                            ],
+                        },
+                        ListItem {
+                            checked: None,
+                            blocks: vec![paragraph("d")],
+                        },
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_task_lists() {
+        let blocks = parse("- [x] done\n- [ ] todo");
+
+        assert_eq!(
+            blocks,
+            [Block::List {
+                start: None,
+                items: vec![
+                    ListItem {
+                        checked: Some(true),
+                        blocks: vec![paragraph("done")],
+                    },
+                    ListItem {
+                        checked: Some(false),
+                        blocks: vec![paragraph("todo")],
+                    },
+                ],
+            }]
+        );
+        assert_eq!(blocks_plain_text(&blocks), "• done\n• todo");
+    }
+
+    #[test]
+    fn parses_footnotes_with_their_labels() {
+        assert_eq!(
+            parse("Text[^Note].\n\n[^Note]: Details."),
+            [
+                Block::Paragraph(vec![
+                    text("Text"),
+                    Inline::FootnoteReference("Note".to_owned()),
+                    text("."),
+                ]),
+                Block::Footnote {
+                    label: "Note".to_owned(),
+                    blocks: vec![paragraph("Details.")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_tables() {
+        let blocks = parse("| a | *b* |\n|---|---|\n| `c` | d |");
+
+        assert_eq!(
+            blocks,
+            [Block::Table(vec![
+                vec![
+                    vec![text("a")],
+                    vec![Inline::Styled(Style::Emphasis, vec![text("b")])],
+                ],
+                vec![vec![Inline::Code("c".to_owned())], vec![text("d")]],
+            ])]
+        );
+        assert_eq!(blocks_plain_text(&blocks), "a\tb\nc\td");
+    }
+
+    #[test]
+    fn resolves_references() {
+        assert_eq!(
+            parse("[link][1], ![][1] and [this]\n\n[1]: https://example.com"),
+            [Block::Paragraph(vec![
+                Inline::Link {
+                    url: "https://example.com".to_owned(),
+                    content: vec![text("link")],
+                },
+                text(", "),
+                Inline::Image {
+                    url: "https://example.com".to_owned(),
+                    alt: String::new(),
+                },
+                // Not a reference without a definition.
+                text(" and [this]"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn parses_images_without_alt_text() {
+        let blocks = parse("![](https://i.sstatic.net/a.png)");
+
+        assert_eq!(
+            blocks,
+            [Block::Paragraph(vec![Inline::Image {
+                url: "https://i.sstatic.net/a.png".to_owned(),
+                alt: String::new(),
+            }])]
+        );
+        assert_eq!(blocks_plain_text(&blocks), "");
+    }
+
+    #[test]
+    fn drops_site_hints() {
+        assert_eq!(
+            parse("<!-- language: lang-rust -->\n\n    fn main() {}"),
+            [Block::Code("fn main() {}".to_owned())]
+        );
+    }
+
+    #[test]
+    fn keeps_malformed_markdown_as_text() {
+        assert_eq!(
+            parse("*unclosed [link](target\n\n<div>"),
+            [
+                paragraph("*unclosed [link](target"),
+                Block::Html("<div>".to_owned()),
+            ]
+        );
+        assert_eq!(parse(""), []);
+    }
+}
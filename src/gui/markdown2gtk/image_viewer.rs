@@ -0,0 +1,153 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use relm4::{
+    adw::{self, prelude::*},
+    gtk::{self, gdk, glib},
+};
+
+use crate::fl;
+
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 8.0;
+// Zoom is multiplied or divided by this for each step.
+const ZOOM_STEP: f64 = 1.25;
+
+const VIEWER_WIDTH: i32 = 800;
+const VIEWER_HEIGHT: i32 = 600;
+
+/// Show an image in a dialog, zoomed with its buttons, Ctrl+scrolling, or pinching.
+pub fn show(parent: &impl IsA<gtk::Widget>, texture: &gdk::Texture, title: &str) {
+    let picture = gtk::Picture::builder()
+        .paintable(texture)
+        .can_shrink(true)
+        .content_fit(gtk::ContentFit::Fill)
+        .build();
+
+    // Pictures are as large as their images at least, clamps limit them to the zoomed size and
+    // center them.
+    let clamps = [gtk::Orientation::Horizontal, gtk::Orientation::Vertical].map(|orientation| {
+        adw::Clamp::builder()
+            .orientation(orientation)
+            .unit(adw::LengthUnit::Px)
+            .build()
+    });
+    clamps[0].set_child(Some(&picture));
+    clamps[1].set_child(Some(&clamps[0]));
+
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .child(&clamps[1])
+        .hexpand(true)
+        .vexpand(true)
+        .build();
+
+    let zoom = Rc::new(Cell::new(1.0));
+
+    let set_zoom: Rc<dyn Fn(f64)> = {
+        let zoom = zoom.clone();
+        let picture = picture.clone();
+        let texture = texture.clone();
+
+        Rc::new(move |value: f64| {
+            let value = value.clamp(MIN_ZOOM, MAX_ZOOM);
+            zoom.set(value);
+
+            let width = (f64::from(texture.width()) * value) as i32;
+            let height = (f64::from(texture.height()) * value) as i32;
+
+            picture.set_size_request(width, height);
+
+            for (clamp, size) in clamps.iter().zip([width, height]) {
+                clamp.set_maximum_size(size);
+                clamp.set_tightening_threshold(size);
+            }
+        })
+    };
+
+    // Large images start fitted in the dialog.
+    set_zoom(
+        (f64::from(VIEWER_WIDTH) / f64::from(texture.width().max(1)))
+            .min(f64::from(VIEWER_HEIGHT) / f64::from(texture.height().max(1)))
+            .min(1.0),
+    );
+
+    let header = adw::HeaderBar::new();
+
+    for (icon_name, tooltip, step) in [
+        (
+            "zoom-out-symbolic",
+            fl!("image-zoom-out"),
+            Some(1.0 / ZOOM_STEP),
+        ),
+        ("zoom-original-symbolic", fl!("image-zoom-original"), None),
+        ("zoom-in-symbolic", fl!("image-zoom-in"), Some(ZOOM_STEP)),
+    ] {
+        let button = gtk::Button::builder()
+            .icon_name(icon_name)
+            .tooltip_text(tooltip)
+            .build();
+
+        button.connect_clicked(glib::clone!(@strong set_zoom, @strong zoom => move |_| {
+            set_zoom(step.map_or(1.0, |step| zoom.get() * step));
+        }));
+
+        header.pack_start(&button);
+    }
+
+    // Scrolling without Ctrl still moves the image.
+    let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+
+    scroll.connect_scroll(
+        glib::clone!(@strong set_zoom, @strong zoom => move |scroll, _, dy| {
+            if !scroll
+                .current_event_state()
+                .contains(gdk::ModifierType::CONTROL_MASK)
+            {
+                return glib::Propagation::Proceed;
+            }
+
+            set_zoom(if dy < 0.0 {
+                zoom.get() * ZOOM_STEP
+            } else {
+                zoom.get() / ZOOM_STEP
+            });
+
+            glib::Propagation::Stop
+        }),
+    );
+
+    scrolled_window.add_controller(scroll);
+
+    let pinch = gtk::GestureZoom::new();
+    // Zoom when the pinch started, the gesture's scale is relative to it.
+    let pinch_start_zoom = Rc::new(Cell::new(1.0));
+
+    pinch.connect_begin(
+        glib::clone!(@strong zoom, @strong pinch_start_zoom => move |_, _| {
+            pinch_start_zoom.set(zoom.get());
+        }),
+    );
+
+    pinch.connect_scale_changed(move |_, scale| {
+        set_zoom(pinch_start_zoom.get() * scale);
+    });
+
+    scrolled_window.add_controller(pinch);
+
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&scrolled_window));
+
+    let dialog = adw::Dialog::builder()
+        .title(if title.is_empty() {
+            fl!("image-viewer")
+        } else {
+            title.to_owned()
+        })
+        .content_width(VIEWER_WIDTH)
+        .content_height(VIEWER_HEIGHT)
+        .child(&toolbar_view)
+        .build();
+
+    dialog.present(parent);
+}
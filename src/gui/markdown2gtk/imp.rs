@@ -1,36 +1,60 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::str::FromStr;
 
-use markdown::mdast;
 use relm4::{
     adw,
-    gtk::{self, prelude::*},
+    gtk::{self, gdk, glib, prelude::*},
 };
 use relm4_icons::icon_names;
 
 use super::cell_object::CellObject;
+use super::document::{
+    inlines_plain_text, Block, Document, Inline, ListItem, MarkdownError, Style,
+};
+use super::image_viewer;
+use crate::api::backend::Backend;
+use crate::api::error::ApiError;
+use crate::api::router::AppUri;
+use crate::fl;
+use crate::gui::main_window::AppInput;
 
-// TODO: Refactor this mess if there is a better structure.
+// Width of images before the view is shown, when its width isn't known.
+const MAX_IMAGE_WIDTH: i32 = 800;
 
 struct TagsColorScheme {
     code_background: String,
     quote_background: String,
     quote_foreground: String,
+    dim_foreground: String,
 }
 
 pub struct MarkdownView {
     pub text_view: gtk::TextView,
     buf: gtk::TextBuffer,
     tag_table: gtk::TextTagTable,
+    // Tags of the elements being rendered, applied to every inserted text.
+    tags: Vec<gtk::TextTag>,
+    // Nesting of quotes and lists.
+    indent_level: i32,
+    // New lines between blocks, 1 inside list items.
+    block_spacing: usize,
+    // The next block continues the line of a list marker.
+    after_marker: bool,
+    // Urls of links, by their tags.
+    links: Rc<RefCell<HashMap<gtk::TextTag, String>>>,
+    // Client to load images with, they are shown as links without it.
+    images: Option<Backend>,
 }
 
 impl FromStr for MarkdownView {
-    type Err = ();
+    type Err = MarkdownError;
 
     fn from_str(markdown_text: &str) -> Result<Self, Self::Err> {
         let mut object = Self::new();
 
-        // TODO: Handle and return errors.
-        object.append_text(markdown_text);
+        object.append_text(markdown_text)?;
 
         Ok(object)
     }
@@ -54,10 +78,20 @@ impl MarkdownView {
 
         let tag_table = buf.tag_table();
 
+        let links = Rc::<RefCell<HashMap<gtk::TextTag, String>>>::default();
+
+        Self::connect_links(&text_view, &links);
+
         let mut object = MarkdownView {
             text_view,
             buf,
             tag_table,
+            tags: Vec::new(),
+            indent_level: 0,
+            block_spacing: 2,
+            after_marker: false,
+            links,
+            images: None,
         };
 
         object.load_text_tags();
@@ -65,6 +99,95 @@ impl MarkdownView {
         object
     }
 
+    // Open links when they are clicked, and show where they point to when hovered.
+    fn connect_links(
+        text_view: &gtk::TextView,
+        links: &Rc<RefCell<HashMap<gtk::TextTag, String>>>,
+    ) {
+        // Url of the link under a point of the view.
+        fn link_at(
+            text_view: &gtk::TextView,
+            links: &HashMap<gtk::TextTag, String>,
+            x: f64,
+            y: f64,
+        ) -> Option<String> {
+            let (x, y) =
+                text_view.window_to_buffer_coords(gtk::TextWindowType::Widget, x as i32, y as i32);
+
+            text_view
+                .iter_at_location(x, y)?
+                .tags()
+                .iter()
+                .find_map(|tag| links.get(tag).cloned())
+        }
+
+        // Listen to all buttons, so the middle one could be handled too.
+        let click = gtk::GestureClick::builder().button(0).build();
+
+        click.connect_released(
+            glib::clone!(@weak text_view, @strong links => move |gesture, _, x, y| {
+                // Selecting the text of a link shouldn't open it.
+                if text_view.buffer().has_selection() {
+                    return;
+                }
+
+                if let Some(url) = link_at(&text_view, &links.borrow(), x, y) {
+                    match gesture.current_button() {
+                        gtk::gdk::BUTTON_PRIMARY => open_link(&text_view, &url),
+                        // Web pages of StackExchange links too.
+                        gtk::gdk::BUTTON_MIDDLE => open_in_browser(&text_view, &url),
+                        _ => {}
+                    }
+                }
+            }),
+        );
+
+        text_view.add_controller(click);
+
+        let motion = gtk::EventControllerMotion::new();
+
+        motion.connect_motion(
+            glib::clone!(@weak text_view, @strong links => move |_, x, y| {
+                let url = link_at(&text_view, &links.borrow(), x, y);
+
+                if text_view.tooltip_text().as_deref() != url.as_deref() {
+                    text_view.set_cursor_from_name(Some(match url {
+                        Some(_) => "pointer",
+                        None => "text",
+                    }));
+                    text_view.set_tooltip_text(url.as_deref());
+                }
+            }),
+        );
+
+        text_view.add_controller(motion);
+    }
+
+    /// Render markdown, it's shown as plain text when it can't be parsed.
+    pub fn render(markdown_text: &str) -> Self {
+        let mut object = Self::new();
+
+        object.append_text_or_plain(markdown_text);
+
+        object
+    }
+
+    /// Render markdown with its images, they are loaded in the background with the client.
+    pub fn render_with_images(markdown_text: &str, client: Backend) -> Self {
+        let mut object = Self::new();
+        object.images = Some(client);
+
+        object.append_text_or_plain(markdown_text);
+
+        object
+    }
+
+    fn append_text_or_plain(&mut self, markdown_text: &str) {
+        if self.append_text(markdown_text).is_err() {
+            self.buf.insert(&mut self.buf.end_iter(), markdown_text);
+        }
+    }
+
     fn load_text_tags(&mut self) {
         let style_manager = adw::StyleManager::default();
 
@@ -75,6 +198,7 @@ impl MarkdownView {
                 code_background: "#050505".to_owned(),
                 quote_background: "#050505".to_owned(),
                 quote_foreground: "#696969".to_owned(),
+                dim_foreground: "#8a8a8a".to_owned(),
             }
         } else {
             // Light mode colors
@@ -82,6 +206,7 @@ impl MarkdownView {
                 code_background: "#b0b0b0".to_owned(),
                 quote_background: "#505050".to_owned(),
                 quote_foreground: "#ffffff".to_owned(),
+                dim_foreground: "#707070".to_owned(),
             }
         };
 
@@ -133,13 +258,49 @@ impl MarkdownView {
                 .build(),
         );
 
+        self.tag_table.add(
+            &gtk::TextTag::builder()
+                .name("STRIKETHROUGH")
+                .strikethrough(true)
+                .build(),
+        );
+
+        self.tag_table.add(
+            &gtk::TextTag::builder()
+                .name("SUPERSCRIPT")
+                .rise(6 * gtk::pango::SCALE)
+                .scale(0.8)
+                .build(),
+        );
+
+        self.tag_table.add(
+            &gtk::TextTag::builder()
+                .name("DIM")
+                .foreground(&color_scheme.dim_foreground)
+                .build(),
+        );
+
+        self.tag_table.add(
+            &gtk::TextTag::builder()
+                .name("CENTER")
+                .justification(gtk::Justification::Center)
+                .build(),
+        );
+
+        self.tag_table.add(
+            &gtk::TextTag::builder()
+                .name("LIST_MARKER")
+                .style(gtk::pango::Style::Italic)
+                .scale(1.3)
+                .build(),
+        );
+
         self.tag_table.add(
             &gtk::TextTag::builder()
                 .name("BLOCK_QUOTE")
                 .background(&color_scheme.quote_background)
                 .paragraph_background(&color_scheme.quote_background)
                 .foreground(&color_scheme.quote_foreground)
-                .left_margin(20)
                 .indent(10)
                 .pixels_below_lines(10)
                 .pixels_above_lines(10)
@@ -170,210 +331,347 @@ impl MarkdownView {
         );
     }
 
-    pub fn append_text(&mut self, markdown_text: &str) {
-        // https://stackoverflow.com/editing-help
-        // https://github.com/wooorm/markdown-rs
-        // https://github.com/syntax-tree/mdast
-        // https://docs.rs/markdown/1.0.0-alpha.7/markdown/mdast/enum.Node.html
-        let tree = markdown::to_mdast(markdown_text, &markdown::ParseOptions::gfm()).unwrap();
-
-        for node in tree.children().unwrap() {
-            match node {
-                mdast::Node::BlockQuote(_quote) => {
-                    self.buf.insert(&mut self.buf.end_iter(), "\n");
-                    self.buf.insert_with_tags_by_name(
-                        &mut self.buf.end_iter(),
-                        &node.to_string(),
-                        &["BLOCK_QUOTE"],
-                    );
-                    self.buf.insert(&mut self.buf.end_iter(), "\n");
-                }
-                mdast::Node::List(list) => {
-                    self.buf.insert(&mut self.buf.end_iter(), "\n\n");
-                    self.md_list2buf(list, 1);
-                    self.buf.insert(&mut self.buf.end_iter(), "\n");
-                }
-                mdast::Node::Heading(header) => {
-                    self.buf.insert(&mut self.buf.end_iter(), "\n");
-
-                    self.buf.insert_with_tags_by_name(
-                        &mut self.buf.end_iter(),
-                        &node.to_string(),
-                        &[
-                            "BOLD",
-                            match header.depth {
-                                1 => "HEADING1",
-                                2 => "HEADING2",
-                                3 => "HEADING3",
-                                4 => "HEADING4",
-                                5 => "HEADING5",
-                                6 => "HEADING6",
-                                _ => unreachable!(),
-                            },
-                        ],
-                    );
+    pub fn append_text(&mut self, markdown_text: &str) -> Result<(), MarkdownError> {
+        let document = Document::parse(markdown_text)?;
 
-                    self.buf.insert(&mut self.buf.end_iter(), "\n");
-                }
-                mdast::Node::Table(table) => {
-                    self.buf.insert(&mut self.buf.end_iter(), "\n");
-                    self.md_table2grid_view(table);
-                    self.buf.insert(&mut self.buf.end_iter(), "\n");
-                }
-                mdast::Node::Paragraph(paragraph) => {
-                    self.md_paragraph2buf(&paragraph.children);
-                }
-                mdast::Node::Code(code) => {
-                    // let lang = code.lang.clone();
-                    // let meta = code.meta.clone();
-                    self.buf.insert(&mut self.buf.end_iter(), "\n\n");
-
-                    self.buf.insert_with_tags_by_name(
-                        &mut self.buf.end_iter(),
-                        &code.value,
-                        &["CODE_BLOCK"],
-                    );
+        self.append_document(&document);
 
-                    self.buf.insert(&mut self.buf.end_iter(), "\n\n");
-                }
-                mdast::Node::Html(_html) => {
-                    // TODO: Convert simple HTML to GTK.
-                    unimplemented!("HTML parsing")
-                }
-                _ => unimplemented!(),
-            }
+        Ok(())
+    }
+
+    pub fn append_document(&mut self, document: &Document) {
+        for block in &document.blocks {
+            self.block2buf(block);
         }
     }
 
-    fn md_paragraph2buf(&mut self, nodes: &Vec<mdast::Node>) {
-        for node in nodes {
-            match node {
-                mdast::Node::InlineCode(code) => {
-                    self.buf.insert_with_tags_by_name(
-                        &mut self.buf.end_iter(),
-                        &code.value,
-                        &["INLINE_CODE"],
-                    );
-                }
-                mdast::Node::Text(text) => self.buf.insert(&mut self.buf.end_iter(), &text.value),
-                mdast::Node::Strong(strong) => {
-                    for node in &strong.children {
-                        match node {
-                            mdast::Node::Text(text) => self.buf.insert_with_tags_by_name(
-                                &mut self.buf.end_iter(),
-                                &text.value,
-                                &["BOLD"],
-                            ),
-                            // FIX: Make other children bold also.
-                            _ => self.md_paragraph2buf(&vec![node.to_owned()]),
-                        }
-                    }
-                }
-                mdast::Node::Emphasis(emphasis) => {
-                    for node in &emphasis.children {
-                        match node {
-                            mdast::Node::Text(text) => self.buf.insert_with_tags_by_name(
-                                &mut self.buf.end_iter(),
-                                &text.value,
-                                &["EMPHASIS"],
-                            ),
-                            // FIX: Make other children emphasis also.
-                            _ => self.md_paragraph2buf(&vec![node.to_owned()]),
-                        }
-                    }
-                }
-                mdast::Node::Link(link) => {
-                    // Show icon along side the hyperlink
-                    let anchor = gtk::TextChildAnchor::new();
-                    self.buf
-                        .insert_child_anchor(&mut self.buf.end_iter(), &anchor);
-
-                    let icon = gtk::Image::builder()
-                        .icon_name(icon_names::EARTH)
-                        .margin_end(3)
-                        .tooltip_text(&link.url)
-                        .build();
+    // Insert text at the end with all of the active tags.
+    fn insert(&self, text: &str) {
+        let tags = self.tags.iter().collect::<Vec<&gtk::TextTag>>();
 
-                    self.text_view.add_child_at_anchor(&icon, &anchor);
+        self.buf
+            .insert_with_tags(&mut self.buf.end_iter(), text, &tags);
+    }
 
-                    // Create a link tag.
-                    let tag = gtk::TextTag::builder()
-                        .foreground("#90c2ff")
-                        .underline(gtk::pango::Underline::Single)
-                        .build();
-                    // TODO: Save and asociate URI with the tag.
-                    // TODO: Asociate a click event to this tag to open the URI.
-                    // TODO: Open stackexchange links inside the app.
-                    self.buf.tag_table().add(&tag);
+    // Render with more active tags, their styles are combined in the order of the tag table.
+    fn with_tags(&mut self, tags: Vec<gtk::TextTag>, render: impl FnOnce(&mut Self)) {
+        let active_count = self.tags.len();
 
-                    self.buf
-                        .insert_with_tags(&mut self.buf.end_iter(), &node.to_string(), &[&tag]);
-                }
-                mdast::Node::LinkReference(_link_ref) => {
-                    todo!("LinkRefrence")
+        self.tags.extend(tags);
+        render(self);
+        self.tags.truncate(active_count);
+    }
+
+    fn named_tags(&self, names: &[&str]) -> Vec<gtk::TextTag> {
+        names
+            .iter()
+            .filter_map(|name| self.tag_table.lookup(name))
+            .collect()
+    }
+
+    // Render indented one more level, for quotes and lists.
+    fn indented(&mut self, render: impl FnOnce(&mut Self)) {
+        self.indent_level += 1;
+
+        let name = format!("INDENT{}", self.indent_level);
+        let tag = self.tag_table.lookup(&name).unwrap_or_else(|| {
+            // Created after the other tags, so it overrides their margins.
+            let tag = gtk::TextTag::builder()
+                .name(name)
+                .left_margin(20 * self.indent_level)
+                .build();
+            self.tag_table.add(&tag);
+            tag
+        });
+
+        self.with_tags(vec![tag], render);
+
+        self.indent_level -= 1;
+    }
+
+    // Blocks start in their own lines, after an empty line, or directly after a list marker.
+    fn start_block(&self) {
+        if self.buf.char_count() == 0 || self.after_marker {
+            return;
+        }
+
+        let mut iter = self.buf.end_iter();
+        let mut new_lines = 0;
+
+        while new_lines < self.block_spacing && iter.backward_char() && iter.char() == '\n' {
+            new_lines += 1;
+        }
+
+        self.insert(&"\n".repeat(self.block_spacing - new_lines));
+    }
+
+    fn block2buf(&mut self, block: &Block) {
+        self.start_block();
+        self.after_marker = false;
+
+        match block {
+            Block::Quote(blocks) => {
+                self.with_tags(self.named_tags(&["BLOCK_QUOTE"]), |this| {
+                    this.indented(|this| {
+                        for block in blocks {
+                            this.block2buf(block);
+                        }
+                    });
+                });
+            }
+            Block::List { start, items } => {
+                self.indented(|this| this.list2buf(*start, items));
+            }
+            Block::Heading { level, content } => {
+                let heading = match level {
+                    1 => "HEADING1",
+                    2 => "HEADING2",
+                    3 => "HEADING3",
+                    4 => "HEADING4",
+                    5 => "HEADING5",
+                    _ => "HEADING6",
+                };
+
+                self.with_tags(self.named_tags(&["BOLD", heading]), |this| {
+                    this.inlines2buf(content);
+                });
+            }
+            Block::Table(rows) => {
+                self.table2grid_view(rows);
+            }
+            Block::Paragraph(content) => {
+                self.inlines2buf(content);
+            }
+            Block::Code(code) => {
+                self.with_tags(self.named_tags(&["CODE_BLOCK"]), |this| {
+                    this.insert(code);
+                });
+            }
+            Block::Rule => {
+                self.with_tags(self.named_tags(&["DIM", "CENTER"]), |this| {
+                    this.insert(&"─".repeat(20));
+                });
+            }
+            Block::Footnote { label, blocks } => {
+                self.with_tags(self.named_tags(&["SUPERSCRIPT"]), |this| {
+                    this.insert(&format!("[{label}] "));
+                });
+
+                self.item_blocks2buf(blocks);
+            }
+            Block::Html(html) => {
+                self.insert(html);
+            }
+        }
+    }
+
+    fn inlines2buf(&mut self, inlines: &[Inline]) {
+        for inline in inlines {
+            match inline {
+                Inline::Code(code) => {
+                    self.with_tags(self.named_tags(&["INLINE_CODE"]), |this| {
+                        this.insert(code);
+                    });
                 }
-                mdast::Node::Image(_image) => {
-                    todo!("Image")
+                Inline::Text(text) => self.insert(text),
+                Inline::Break => self.insert("\n"),
+                Inline::Styled(style, content) => {
+                    let name = match style {
+                        Style::Strong => "BOLD",
+                        Style::Emphasis => "EMPHASIS",
+                        Style::Strikethrough => "STRIKETHROUGH",
+                    };
+
+                    self.with_tags(self.named_tags(&[name]), |this| {
+                        this.inlines2buf(content);
+                    });
                 }
-                mdast::Node::ImageReference(_image_ref) => {
-                    todo!("ImageReference")
+                Inline::Link { url, content } => {
+                    self.link2buf(url, content);
                 }
-                mdast::Node::Paragraph(paragraph) => {
-                    self.md_paragraph2buf(&paragraph.children);
+                Inline::Image { url, alt } => match self.images.clone() {
+                    Some(client) => self.image2buf(url, alt, client),
+                    None => self.link2buf(url, &[Inline::Text(alt.clone())]),
+                },
+                Inline::FootnoteReference(label) => {
+                    self.with_tags(self.named_tags(&["SUPERSCRIPT"]), |this| {
+                        this.insert(&format!("[{label}]"));
+                    });
                 }
-                _ => unimplemented!(),
             }
         }
     }
 
-    fn md_list2buf(&mut self, list: &mdast::List, indent_level: u8) {
+    fn link2buf(&mut self, url: &str, content: &[Inline]) {
+        // Show icon along side the hyperlink
+        let anchor = gtk::TextChildAnchor::new();
+        self.buf
+            .insert_child_anchor(&mut self.buf.end_iter(), &anchor);
+
+        let icon = gtk::Image::builder()
+            .icon_name(icon_names::EARTH)
+            .margin_end(3)
+            .tooltip_text(url)
+            .build();
+
+        self.text_view.add_child_at_anchor(&icon, &anchor);
+
+        // Create a link tag, and remember where it points to for clicks.
         let tag = gtk::TextTag::builder()
-            .indent(20 * indent_level as i32)
-            .style(gtk::pango::Style::Italic)
-            .scale(1.3)
+            .foreground("#90c2ff")
+            .underline(gtk::pango::Underline::Single)
             .build();
         self.buf.tag_table().add(&tag);
+        self.links.borrow_mut().insert(tag.clone(), url.to_owned());
 
-        for (num, node) in list.children.iter().enumerate() {
-            if list.ordered {
-                self.buf.insert_with_tags(
-                    &mut self.buf.end_iter(),
-                    &format!("{}. ", num + 1),
-                    &[&tag],
-                )
+        self.with_tags(vec![tag], |this| {
+            // Links without a text (e.g. images without an alt text) still need something to show.
+            if inlines_plain_text(content).is_empty() {
+                this.insert(url);
             } else {
-                self.buf
-                    .insert_with_tags(&mut self.buf.end_iter(), "• ", &[&tag])
+                this.inlines2buf(content);
             }
+        });
+    }
+
+    fn image2buf(&mut self, url: &str, alt: &str, client: Backend) {
+        let anchor = gtk::TextChildAnchor::new();
+        self.buf
+            .insert_child_anchor(&mut self.buf.end_iter(), &anchor);
+
+        // The alt text is shown while the image is loading, and when it can't be loaded.
+        let icon = gtk::Image::from_icon_name("image-loading-symbolic");
+
+        let placeholder = gtk::Box::builder().spacing(5).build();
+        placeholder.append(&icon);
+        placeholder.append(
+            &gtk::Label::builder()
+                .label(if alt.is_empty() { url } else { alt })
+                .wrap(true)
+                .build(),
+        );
+
+        let button = gtk::Button::builder()
+            .child(&placeholder)
+            .tooltip_text(url)
+            .css_classes(["flat"])
+            .build();
+
+        self.text_view.add_child_at_anchor(&button, &anchor);
+
+        let url = url.to_owned();
+        let alt = alt.to_owned();
+        let text_view = self.text_view.clone();
+
+        relm4::spawn_local(async move {
+            let image_url = url.clone();
+
+            let result = relm4::spawn(async move { client.get_image(&image_url).await })
+                .await
+                .unwrap_or_else(|error| Err(ApiError::Transport(error.to_string())))
+                .and_then(|image| {
+                    gdk::Texture::from_bytes(&glib::Bytes::from_owned(image))
+                        .map_err(|error| ApiError::Decode(error.to_string()))
+                });
+
+            match result {
+                Ok(texture) => {
+                    let picture = gtk::Picture::builder()
+                        .paintable(&texture)
+                        .can_shrink(true)
+                        .content_fit(gtk::ContentFit::ScaleDown)
+                        .alternative_text(alt.as_str())
+                        .build();
 
-            match node {
-                mdast::Node::List(list) => {
-                    self.md_list2buf(list, indent_level + 1);
+                    fit_picture(&picture, &texture, &text_view);
+
+                    // Views of hidden tabs don't have a width yet, and the scrolled window they
+                    // are in is only known once they are shown.
+                    let follows_width = Rc::new(Cell::new(false));
+                    text_view.connect_map(
+                        glib::clone!(@weak picture, @weak texture => move |text_view| {
+                            fit_picture(&picture, &texture, text_view);
+
+                            if !follows_width.replace(true) {
+                                follow_width(&picture, &texture, text_view);
+                            }
+                        }),
+                    );
+
+                    button.set_child(Some(&picture));
+                    button.connect_clicked(move |button| {
+                        image_viewer::show(button, &texture, &alt);
+                    });
+                }
+                Err(error) => {
+                    icon.set_icon_name(Some("image-missing-symbolic"));
+                    button.set_tooltip_text(Some(&fl!(
+                        "image-load-failed",
+                        url = url.as_str(),
+                        error = error.to_string()
+                    )));
+                    button.connect_clicked(move |button| open_in_browser(button, &url));
                 }
-                mdast::Node::ListItem(list_item) => self.md_paragraph2buf(&list_item.children),
-                _ => unimplemented!(),
             }
+        });
+    }
+
+    fn list2buf(&mut self, start: Option<u32>, items: &[ListItem]) {
+        for (num, item) in items.iter().enumerate() {
+            if num > 0 {
+                self.insert("\n");
+            }
+
+            let marker = match start {
+                Some(start) => format!("{}. ", start as usize + num),
+                None => "• ".to_owned(),
+            };
+
+            self.with_tags(self.named_tags(&["LIST_MARKER"]), |this| {
+                this.insert(&marker);
+            });
+
+            // Task list items.
+            match item.checked {
+                Some(true) => self.insert("☑ "),
+                Some(false) => self.insert("☐ "),
+                None => {}
+            }
+
+            self.item_blocks2buf(&item.blocks);
+        }
+    }
 
-            self.buf.insert(&mut self.buf.end_iter(), "\n");
+    // Blocks of list items and footnotes, the first one is shown next to the marker.
+    fn item_blocks2buf(&mut self, blocks: &[Block]) {
+        let block_spacing = self.block_spacing;
+        self.block_spacing = 1;
+
+        for (i, block) in blocks.iter().enumerate() {
+            self.after_marker = i == 0;
+            self.block2buf(block);
         }
+
+        self.after_marker = false;
+        self.block_spacing = block_spacing;
     }
 
-    fn md_table2grid_view(&mut self, table: &mdast::Table) {
+    fn table2grid_view(&mut self, rows: &[Vec<Vec<Inline>>]) {
         let model = gtk::gio::ListStore::new::<CellObject>();
         let mut columns_count = 0;
         let mut max_row_size = 0;
 
         // PERF: Find better way to find row max size.
-        for row in &table.children {
-            let size = row.children().unwrap().iter().len();
+        for row in rows {
+            let size = row.len();
 
             if size > max_row_size {
                 max_row_size = size;
             }
         }
 
-        for (i, row) in table.children.iter().enumerate() {
-            let row_iter = row.children().unwrap().iter();
+        for (i, row) in rows.iter().enumerate() {
+            let row_iter = row.iter();
             let row_size = row_iter.len();
 
             for (j, cell) in row_iter.enumerate() {
@@ -389,7 +687,7 @@ impl MarkdownView {
                 }
 
                 // TODO: Accept cells alighnment in columns option.
-                model.append(&CellObject::new(Some(&cell.to_string()), is_header));
+                model.append(&CellObject::new(Some(&inlines_markup(cell)), is_header));
             }
 
             // Add empty cells if the row is smaller the the longest row.
@@ -406,6 +704,12 @@ impl MarkdownView {
 
         factory.connect_setup(|_factory, list_item| {
             let label = gtk::Label::new(None);
+
+            label.connect_activate_link(|label, url| {
+                open_link(label, url);
+                glib::Propagation::Stop
+            });
+
             list_item
                 .downcast_ref::<gtk::ListItem>()
                 .unwrap()
@@ -430,11 +734,11 @@ impl MarkdownView {
                     .and_downcast::<gtk::Label>()
                     .unwrap();
 
-                // Set label from cell object string
+                // Set label from cell object markup
                 if cell_object.isheader() {
                     label.set_markup(&format!("<b>{}</b>", string));
                 } else {
-                    label.set_label(&string);
+                    label.set_markup(&string);
                 }
             }
         });
@@ -454,3 +758,99 @@ impl MarkdownView {
         self.text_view.add_child_at_anchor(&view, &anchor);
     }
 }
+
+// StackExchange questions and answers are opened in new tabs, other links in the browser.
+fn open_link(widget: &impl IsA<gtk::Widget>, url: &str) {
+    if AppUri::parse(url).is_ok() {
+        crate::BASE_BROKER.send(AppInput::RequestPagesByUri(url.to_owned()));
+    } else {
+        open_in_browser(widget, url);
+    }
+}
+
+// Pango markup of inlines for labels, like table cells, with their text escaped.
+fn inlines_markup(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => glib::markup_escape_text(text).to_string(),
+            Inline::Code(code) => format!("<tt>{}</tt>", glib::markup_escape_text(code)),
+            Inline::Styled(style, content) => {
+                let tag = match style {
+                    Style::Strong => "b",
+                    Style::Emphasis => "i",
+                    Style::Strikethrough => "s",
+                };
+
+                format!("<{tag}>{}</{tag}>", inlines_markup(content))
+            }
+            Inline::Link { url, content } => {
+                let content = match inlines_markup(content) {
+                    content if content.is_empty() => glib::markup_escape_text(url).to_string(),
+                    content => content,
+                };
+
+                format!(
+                    "<a href=\"{}\">{content}</a>",
+                    glib::markup_escape_text(url)
+                )
+            }
+            // Images don't fit in cells, so they are links to themselves.
+            Inline::Image { url, alt } => inlines_markup(&[Inline::Link {
+                url: url.clone(),
+                content: vec![Inline::Text(alt.clone())],
+            }]),
+            Inline::FootnoteReference(label) => {
+                format!("<sup>[{}]</sup>", glib::markup_escape_text(label))
+            }
+            Inline::Break => "\n".to_owned(),
+        })
+        .collect()
+}
+
+fn open_in_browser(widget: &impl IsA<gtk::Widget>, url: &str) {
+    // The desktop tells when there is no app to open it.
+    gtk::UriLauncher::new(url).launch(
+        widget.root().and_downcast_ref::<gtk::Window>(),
+        gtk::gio::Cancellable::NONE,
+        |_| {},
+    );
+}
+
+// Fit the picture again whenever the scrolled window of the view is resized.
+fn follow_width(picture: &gtk::Picture, texture: &gdk::Texture, text_view: &gtk::TextView) {
+    let Some(scrolled_window) = text_view
+        .ancestor(gtk::ScrolledWindow::static_type())
+        .and_downcast::<gtk::ScrolledWindow>()
+    else {
+        return;
+    };
+
+    scrolled_window.hadjustment().connect_page_size_notify(
+        glib::clone!(@weak picture, @weak texture, @weak text_view => move |_| {
+            // The view itself is resized after the adjustment.
+            glib::idle_add_local_once(
+                glib::clone!(@weak picture, @weak texture, @weak text_view => move || {
+                    fit_picture(&picture, &texture, &text_view);
+                }),
+            );
+        }),
+    );
+}
+
+// Images are shown in their size, scaled down to fit in the view.
+fn fit_picture(picture: &gtk::Picture, texture: &gdk::Texture, text_view: &gtk::TextView) {
+    let max_width = match text_view.width() {
+        0 => MAX_IMAGE_WIDTH,
+        // Leave space for the margins of the text.
+        width => width - 20,
+    };
+
+    let width = texture.width().min(max_width).max(1);
+    let height = texture.height() * width / texture.width().max(1);
+
+    // The request is a minimum size, it's updated so the picture follows the width both ways.
+    if picture.size_request() != (width, height) {
+        picture.set_size_request(width, height);
+    }
+}
@@ -1,4 +1,6 @@
 mod cell_object;
+mod document;
+mod image_viewer;
 mod imp;
 
 pub use imp::MarkdownView;
@@ -1,5 +1,13 @@
 mod about_dialog;
+mod bookmarks_page;
 mod componant_builders;
+mod history_page;
 pub mod main_window;
 mod markdown2gtk;
+mod offline_dialog;
+mod preferences_dialog;
+mod search_page;
 mod side_bar;
+mod site_picker;
+mod tab_page;
+mod user_page;
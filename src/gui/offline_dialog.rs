@@ -0,0 +1,462 @@
+use std::path::PathBuf;
+
+use relm4::{
+    adw::{self, prelude::*},
+    prelude::*,
+};
+
+use super::main_window::api_error_message;
+use crate::api::backend::Backend;
+use crate::api::dump::import::{DumpFile, ImportProgress};
+use crate::api::error::ApiError;
+use crate::api::offline::{OfflinePack, PackProgress, PackSource};
+use crate::api::stackexchange::Id;
+use crate::fl;
+
+// Default number of questions downloaded for a tag.
+const DEFAULT_TAG_LIMIT: f64 = 100.0;
+
+pub struct OfflineDialogInit {
+    pub client: Backend,
+    pub main_window: gtk::Window,
+}
+
+/// Downloads questions of a site for reading them without network.
+pub struct OfflineDialogModel {
+    client: Backend,
+    site: String,
+    // A download or an import is running, only one of them runs at a time.
+    busy: bool,
+    // Shown in the same order as their rows.
+    unfinished: Vec<OfflinePack>,
+}
+
+pub struct OfflineDialogWidgets {
+    main_window: gtk::Window,
+    site_row: adw::ActionRow,
+    target_row: adw::EntryRow,
+    limit_row: adw::SpinRow,
+    start_button: gtk::Button,
+    import_button: gtk::Button,
+    progress_bar: gtk::ProgressBar,
+    status_label: gtk::Label,
+    unfinished_group: adw::PreferencesGroup,
+    unfinished_rows: Vec<adw::ActionRow>,
+}
+
+#[derive(Debug)]
+pub enum OfflineDialogInput {
+    /// Present the dialog for downloading questions of a site.
+    Show(String),
+    Start,
+    Resume(usize),
+    ChooseDump,
+    /// Import the extracted dump in a directory as the selected site.
+    ImportDump(PathBuf),
+}
+
+#[derive(Debug)]
+pub enum OfflineDialogCommand {
+    Progress(PackProgress),
+    Finished(usize, Result<(), ApiError>),
+    ImportProgress(ImportProgress),
+    ImportFinished(Result<(), ApiError>),
+}
+
+impl Component for OfflineDialogModel {
+    type CommandOutput = OfflineDialogCommand;
+    type Input = OfflineDialogInput;
+    type Output = ();
+    type Init = OfflineDialogInit;
+    type Root = adw::Dialog;
+    type Widgets = OfflineDialogWidgets;
+
+    fn init_root() -> Self::Root {
+        adw::Dialog::builder()
+            .title(fl!("offline-download"))
+            .content_width(450)
+            .build()
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = OfflineDialogModel {
+            client: init.client,
+            site: String::new(),
+            busy: false,
+            unfinished: Vec::new(),
+        };
+
+        let toolbar_view = adw::ToolbarView::new();
+        toolbar_view.add_top_bar(&adw::HeaderBar::new());
+        root.set_child(Some(&toolbar_view));
+
+        let page = adw::PreferencesPage::new();
+        toolbar_view.set_content(Some(&page));
+
+        let download_group = adw::PreferencesGroup::new();
+        page.add(&download_group);
+
+        let site_row = adw::ActionRow::builder()
+            .title(fl!("offline-site"))
+            .css_classes(["property"])
+            .build();
+        download_group.add(&site_row);
+
+        let target_row = adw::EntryRow::builder()
+            .title(fl!("offline-target"))
+            .build();
+        download_group.add(&target_row);
+
+        target_row.connect_entry_activated(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(OfflineDialogInput::Start);
+        }));
+
+        let limit_row = adw::SpinRow::builder()
+            .title(fl!("offline-limit"))
+            .adjustment(&gtk::Adjustment::new(
+                DEFAULT_TAG_LIMIT,
+                1.0,
+                10_000.0,
+                10.0,
+                100.0,
+                0.0,
+            ))
+            .build();
+        download_group.add(&limit_row);
+
+        let progress_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(10)
+            .margin_top(20)
+            .build();
+        download_group.add(&progress_layout);
+
+        let start_button = gtk::Button::builder()
+            .label(fl!("offline-start"))
+            .halign(gtk::Align::Center)
+            .css_classes(["pill", "suggested-action"])
+            .build();
+        progress_layout.append(&start_button);
+
+        start_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(OfflineDialogInput::Start);
+        }));
+
+        let progress_bar = gtk::ProgressBar::builder()
+            .show_text(true)
+            .visible(false)
+            .build();
+        progress_layout.append(&progress_bar);
+
+        let status_label = gtk::Label::builder()
+            .wrap(true)
+            .css_classes(["dim-label"])
+            .visible(false)
+            .build();
+        progress_layout.append(&status_label);
+
+        let dump_group = adw::PreferencesGroup::builder()
+            .title(fl!("dump-import"))
+            .description(fl!("dump-import-description"))
+            .build();
+        page.add(&dump_group);
+
+        let import_button = gtk::Button::builder()
+            .label(fl!("dump-import-choose"))
+            .valign(gtk::Align::Center)
+            .build();
+
+        let import_row = adw::ActionRow::builder()
+            .title(fl!("dump-import-row"))
+            .activatable_widget(&import_button)
+            .build();
+        import_row.add_suffix(&import_button);
+        dump_group.add(&import_row);
+
+        import_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(OfflineDialogInput::ChooseDump);
+        }));
+
+        let unfinished_group = adw::PreferencesGroup::builder()
+            .title(fl!("offline-unfinished"))
+            .visible(false)
+            .build();
+        page.add(&unfinished_group);
+
+        let widgets = OfflineDialogWidgets {
+            main_window: init.main_window,
+            site_row,
+            target_row,
+            limit_row,
+            start_button,
+            import_button,
+            progress_bar,
+            status_label,
+            unfinished_group,
+            unfinished_rows: Vec::new(),
+        };
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            OfflineDialogInput::Show(site) => {
+                // A running download or import keeps its site until it finishes.
+                if !self.busy {
+                    widgets.site_row.set_subtitle(&site);
+                    self.site = site;
+                }
+
+                self.update_unfinished(widgets, &sender);
+
+                root.present(&widgets.main_window);
+            }
+            OfflineDialogInput::Start => {
+                let Some(source) =
+                    parse_target(&widgets.target_row.text(), widgets.limit_row.value() as u32)
+                else {
+                    return;
+                };
+
+                let pack = OfflinePack::new(&self.site, source);
+                self.start(widgets, &sender, pack);
+            }
+            OfflineDialogInput::Resume(index) => {
+                if let Some(pack) = self.unfinished.get(index).cloned() {
+                    self.start(widgets, &sender, pack);
+                }
+            }
+            OfflineDialogInput::ChooseDump => {
+                let file_dialog = gtk::FileDialog::builder()
+                    .title(fl!("dump-import-choose"))
+                    .modal(true)
+                    .build();
+
+                file_dialog.select_folder(
+                    Some(&widgets.main_window),
+                    gtk::gio::Cancellable::NONE,
+                    gtk::glib::clone!(@strong sender => move |result| {
+                        if let Some(path) = result.ok().and_then(|folder| folder.path()) {
+                            sender.input(OfflineDialogInput::ImportDump(path));
+                        }
+                    }),
+                );
+            }
+            OfflineDialogInput::ImportDump(dir) => {
+                if self.busy {
+                    return;
+                }
+                self.set_busy(widgets, &sender, true);
+
+                widgets.progress_bar.set_fraction(0.0);
+                widgets.progress_bar.set_text(None);
+
+                let client = self.client.clone();
+                let site = self.site.clone();
+
+                // Importing blocks on disk work, so it runs in its own thread.
+                sender.spawn_command(move |out| {
+                    let result = client.import_dump(&site, &dir, &mut |progress| {
+                        let _ = out.send(OfflineDialogCommand::ImportProgress(progress));
+                    });
+
+                    let _ = out.send(OfflineDialogCommand::ImportFinished(result));
+                });
+            }
+        }
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            OfflineDialogCommand::Progress(progress) => match progress.total {
+                Some(total) => {
+                    widgets.progress_bar.set_fraction(if total == 0 {
+                        1.0
+                    } else {
+                        progress.done as f64 / total as f64
+                    });
+                    widgets.progress_bar.set_text(Some(&fl!(
+                        "offline-progress",
+                        done = progress.done,
+                        total = total
+                    )));
+                }
+                None => {
+                    widgets.progress_bar.pulse();
+                    widgets.progress_bar.set_text(Some(&fl!("offline-listing")));
+                }
+            },
+            OfflineDialogCommand::Finished(count, result) => {
+                self.set_busy(widgets, &sender, false);
+
+                widgets.status_label.set_label(&match result {
+                    Ok(()) => fl!("offline-finished", count = count),
+                    Err(error) => fl!("offline-failed", error = api_error_message(&error)),
+                });
+                widgets.status_label.set_visible(true);
+            }
+            OfflineDialogCommand::ImportProgress(progress) => {
+                // Every file is an equal step of the whole import.
+                let step = DumpFile::ALL
+                    .iter()
+                    .position(|file| *file == progress.file)
+                    .unwrap_or_default();
+
+                widgets
+                    .progress_bar
+                    .set_fraction((step as f64 + progress.fraction) / DumpFile::ALL.len() as f64);
+                widgets
+                    .progress_bar
+                    .set_text(Some(progress.file.file_name()));
+            }
+            OfflineDialogCommand::ImportFinished(result) => {
+                self.set_busy(widgets, &sender, false);
+
+                widgets.status_label.set_label(&match result {
+                    Ok(()) => fl!("dump-import-finished", site = self.site.as_str()),
+                    Err(error) => fl!("dump-import-failed", error = api_error_message(&error)),
+                });
+                widgets.status_label.set_visible(true);
+            }
+        }
+    }
+}
+
+impl OfflineDialogModel {
+    fn start(
+        &mut self,
+        widgets: &mut OfflineDialogWidgets,
+        sender: &ComponentSender<Self>,
+        mut pack: OfflinePack,
+    ) {
+        if self.busy {
+            return;
+        }
+        self.set_busy(widgets, sender, true);
+
+        widgets.progress_bar.set_fraction(0.0);
+        widgets.progress_bar.set_text(Some(&fl!("offline-listing")));
+
+        let client = self.client.clone();
+
+        sender.command(|out, shutdown| {
+            shutdown
+                .register(async move {
+                    let result = client
+                        .download_pack(&mut pack, &mut |progress| {
+                            let _ = out.send(OfflineDialogCommand::Progress(progress));
+                        })
+                        .await;
+
+                    let _ = out.send(OfflineDialogCommand::Finished(pack.progress().done, result));
+                })
+                .drop_on_shutdown()
+        });
+    }
+
+    fn set_busy(
+        &mut self,
+        widgets: &mut OfflineDialogWidgets,
+        sender: &ComponentSender<Self>,
+        busy: bool,
+    ) {
+        self.busy = busy;
+
+        widgets.start_button.set_sensitive(!busy);
+        widgets.import_button.set_sensitive(!busy);
+
+        if busy {
+            widgets.status_label.set_visible(false);
+            widgets.progress_bar.set_visible(true);
+        }
+
+        // Resume buttons are only enabled while nothing runs.
+        self.update_unfinished(widgets, sender);
+    }
+
+    fn update_unfinished(
+        &mut self,
+        widgets: &mut OfflineDialogWidgets,
+        sender: &ComponentSender<Self>,
+    ) {
+        for row in widgets.unfinished_rows.drain(..) {
+            widgets.unfinished_group.remove(&row);
+        }
+
+        self.unfinished = OfflinePack::unfinished();
+
+        for (index, pack) in self.unfinished.iter().enumerate() {
+            let progress = pack.progress();
+
+            let row = adw::ActionRow::builder()
+                .title(pack.source.to_string())
+                .subtitle(fl!(
+                    "offline-unfinished-subtitle",
+                    site = pack.site.as_str(),
+                    done = progress.done,
+                    total = progress.total.unwrap_or_default()
+                ))
+                .use_markup(false)
+                .build();
+
+            let resume_button = gtk::Button::builder()
+                .label(fl!("offline-resume"))
+                .valign(gtk::Align::Center)
+                .sensitive(!self.busy)
+                .build();
+            row.add_suffix(&resume_button);
+
+            resume_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+                sender.input(OfflineDialogInput::Resume(index));
+            }));
+
+            widgets.unfinished_group.add(&row);
+            widgets.unfinished_rows.push(row);
+        }
+
+        widgets
+            .unfinished_group
+            .set_visible(!self.unfinished.is_empty());
+    }
+}
+
+// Questions ids are separated like in the search entry, anything else is a tag.
+fn parse_target(target: &str, limit: u32) -> Option<PackSource> {
+    let target = target.trim();
+
+    if target.is_empty() {
+        None
+    } else if target
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == ';' || c == ',' || c.is_whitespace())
+    {
+        let ids = target
+            .split(|c: char| c == ';' || c == ',' || c.is_whitespace())
+            .filter_map(|id| id.parse::<Id>().ok())
+            .collect::<Vec<Id>>();
+
+        (!ids.is_empty()).then_some(PackSource::Questions(ids))
+    } else {
+        Some(PackSource::Tag {
+            tag: target.to_lowercase(),
+            limit,
+        })
+    }
+}
@@ -0,0 +1,438 @@
+use std::path::PathBuf;
+
+use relm4::{
+    adw::{self, prelude::*},
+    prelude::*,
+};
+
+use crate::api::config::{ApiConfig, ProxyConfig, ProxyMode, DEFAULT_API_ENDPOINT};
+use crate::fl;
+use crate::storage::settings::Settings;
+
+// Same order as the proxy mode row's options.
+const PROXY_MODES: [ProxyMode; 3] = [ProxyMode::System, ProxyMode::Direct, ProxyMode::Manual];
+
+pub struct PreferencesDialogInit {
+    pub settings: Settings,
+    pub main_window: gtk::Window,
+}
+
+/// Edits the settings, they are saved when the dialog is closed and used after a restart.
+pub struct PreferencesDialogModel {
+    // Last saved settings, to only save them when something changed.
+    settings: Settings,
+    ca_bundle: Option<PathBuf>,
+}
+
+pub struct PreferencesDialogWidgets {
+    main_window: gtk::Window,
+    endpoint_row: adw::EntryRow,
+    key_row: adw::EntryRow,
+    user_agent_row: adw::EntryRow,
+    connect_timeout_row: adw::SpinRow,
+    request_timeout_row: adw::SpinRow,
+    cache_ttl_row: adw::SpinRow,
+    proxy_mode_row: adw::ComboRow,
+    proxy_url_row: adw::EntryRow,
+    proxy_username_row: adw::EntryRow,
+    proxy_password_row: adw::PasswordEntryRow,
+    no_proxy_row: adw::EntryRow,
+    ca_bundle_row: adw::ActionRow,
+    ca_bundle_clear_button: gtk::Button,
+}
+
+#[derive(Debug)]
+pub enum PreferencesDialogInput {
+    Show,
+    ProxyModeChanged,
+    ChooseCaBundle,
+    SetCaBundle(Option<PathBuf>),
+    Save,
+}
+
+#[derive(Debug)]
+pub enum PreferencesDialogOutput {
+    /// Settings were changed, with the error when they couldn't be saved.
+    Saved(Result<(), String>),
+}
+
+impl Component for PreferencesDialogModel {
+    type CommandOutput = ();
+    type Input = PreferencesDialogInput;
+    type Output = PreferencesDialogOutput;
+    type Init = PreferencesDialogInit;
+    type Root = adw::PreferencesDialog;
+    type Widgets = PreferencesDialogWidgets;
+
+    fn init_root() -> Self::Root {
+        adw::PreferencesDialog::builder()
+            .title(fl!("preferences"))
+            .search_enabled(false)
+            .build()
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = PreferencesDialogModel {
+            ca_bundle: init.settings.api.ca_bundle.clone(),
+            settings: init.settings,
+        };
+
+        root.connect_closed(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(PreferencesDialogInput::Save);
+        }));
+
+        let page = adw::PreferencesPage::builder()
+            .title(fl!("preferences-network"))
+            .icon_name("network-workgroup-symbolic")
+            .build();
+        root.add(&page);
+
+        let api_group = adw::PreferencesGroup::builder()
+            .title(fl!("preferences-api"))
+            .description(fl!("preferences-restart"))
+            .build();
+        page.add(&api_group);
+
+        let endpoint_row = adw::EntryRow::builder()
+            .title(fl!("preferences-endpoint"))
+            .build();
+        api_group.add(&endpoint_row);
+
+        let key_row = adw::EntryRow::builder()
+            .title(fl!("preferences-key"))
+            .build();
+        api_group.add(&key_row);
+
+        let user_agent_row = adw::EntryRow::builder()
+            .title(fl!("preferences-user-agent"))
+            .build();
+        api_group.add(&user_agent_row);
+
+        let connect_timeout_row = adw::SpinRow::builder()
+            .title(fl!("preferences-connect-timeout"))
+            .subtitle(fl!("preferences-timeout-subtitle"))
+            .adjustment(&gtk::Adjustment::new(0.0, 0.0, 600.0, 1.0, 10.0, 0.0))
+            .build();
+        api_group.add(&connect_timeout_row);
+
+        let request_timeout_row = adw::SpinRow::builder()
+            .title(fl!("preferences-request-timeout"))
+            .subtitle(fl!("preferences-timeout-subtitle"))
+            .adjustment(&gtk::Adjustment::new(0.0, 0.0, 600.0, 1.0, 10.0, 0.0))
+            .build();
+        api_group.add(&request_timeout_row);
+
+        let cache_ttl_row = adw::SpinRow::builder()
+            .title(fl!("preferences-cache-ttl"))
+            .subtitle(fl!("preferences-cache-ttl-subtitle"))
+            .adjustment(&gtk::Adjustment::new(
+                0.0,
+                0.0,
+                7.0 * 24.0 * 60.0,
+                1.0,
+                60.0,
+                0.0,
+            ))
+            .build();
+        api_group.add(&cache_ttl_row);
+
+        let proxy_group = adw::PreferencesGroup::builder()
+            .title(fl!("preferences-proxy"))
+            .build();
+        page.add(&proxy_group);
+
+        let proxy_mode_row = adw::ComboRow::builder()
+            .title(fl!("preferences-proxy-mode"))
+            .model(&gtk::StringList::new(&[
+                fl!("preferences-proxy-system").as_str(),
+                fl!("preferences-proxy-direct").as_str(),
+                fl!("preferences-proxy-manual").as_str(),
+            ]))
+            .build();
+        proxy_group.add(&proxy_mode_row);
+
+        proxy_mode_row.connect_selected_notify(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(PreferencesDialogInput::ProxyModeChanged);
+        }));
+
+        let proxy_url_row = adw::EntryRow::builder()
+            .title(fl!("preferences-proxy-url"))
+            .build();
+        proxy_group.add(&proxy_url_row);
+
+        let proxy_username_row = adw::EntryRow::builder()
+            .title(fl!("preferences-proxy-username"))
+            .build();
+        proxy_group.add(&proxy_username_row);
+
+        let proxy_password_row = adw::PasswordEntryRow::builder()
+            .title(fl!("preferences-proxy-password"))
+            .build();
+        proxy_group.add(&proxy_password_row);
+
+        let no_proxy_row = adw::EntryRow::builder()
+            .title(fl!("preferences-no-proxy"))
+            .build();
+        proxy_group.add(&no_proxy_row);
+
+        let certificates_group = adw::PreferencesGroup::builder()
+            .title(fl!("preferences-certificates"))
+            .build();
+        page.add(&certificates_group);
+
+        let ca_bundle_choose_button = gtk::Button::builder()
+            .icon_name("document-open-symbolic")
+            .tooltip_text(fl!("preferences-ca-bundle-choose"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+
+        ca_bundle_choose_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(PreferencesDialogInput::ChooseCaBundle);
+        }));
+
+        let ca_bundle_clear_button = gtk::Button::builder()
+            .icon_name("edit-clear-symbolic")
+            .tooltip_text(fl!("preferences-ca-bundle-clear"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+
+        ca_bundle_clear_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(PreferencesDialogInput::SetCaBundle(None));
+        }));
+
+        let ca_bundle_row = adw::ActionRow::builder()
+            .title(fl!("preferences-ca-bundle"))
+            .activatable_widget(&ca_bundle_choose_button)
+            .build();
+        ca_bundle_row.add_suffix(&ca_bundle_clear_button);
+        ca_bundle_row.add_suffix(&ca_bundle_choose_button);
+        certificates_group.add(&ca_bundle_row);
+
+        let widgets = PreferencesDialogWidgets {
+            main_window: init.main_window,
+            endpoint_row,
+            key_row,
+            user_agent_row,
+            connect_timeout_row,
+            request_timeout_row,
+            cache_ttl_row,
+            proxy_mode_row,
+            proxy_url_row,
+            proxy_username_row,
+            proxy_password_row,
+            no_proxy_row,
+            ca_bundle_row,
+            ca_bundle_clear_button,
+        };
+
+        model.load_settings(&widgets);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            PreferencesDialogInput::Show => {
+                root.present(&widgets.main_window);
+            }
+            PreferencesDialogInput::ProxyModeChanged => update_proxy_rows(widgets),
+            PreferencesDialogInput::ChooseCaBundle => {
+                let filter = gtk::FileFilter::new();
+                filter.set_name(Some(&fl!("preferences-ca-bundle-filter")));
+                filter.add_suffix("pem");
+                filter.add_suffix("crt");
+
+                let filters = gtk::gio::ListStore::new::<gtk::FileFilter>();
+                filters.append(&filter);
+
+                let file_dialog = gtk::FileDialog::builder()
+                    .title(fl!("preferences-ca-bundle-choose"))
+                    .filters(&filters)
+                    .modal(true)
+                    .build();
+
+                file_dialog.open(
+                    Some(&widgets.main_window),
+                    gtk::gio::Cancellable::NONE,
+                    gtk::glib::clone!(@strong sender => move |result| {
+                        if let Some(path) = result.ok().and_then(|file| file.path()) {
+                            sender.input(PreferencesDialogInput::SetCaBundle(Some(path)));
+                        }
+                    }),
+                );
+            }
+            PreferencesDialogInput::SetCaBundle(path) => {
+                self.ca_bundle = path;
+                self.update_ca_bundle(widgets);
+            }
+            PreferencesDialogInput::Save => {
+                let settings = Settings {
+                    api: self.read_api_config(widgets),
+                };
+
+                if settings == self.settings {
+                    return;
+                }
+
+                let result = settings.save().map_err(|error| error.to_string());
+                self.settings = settings;
+
+                sender
+                    .output(PreferencesDialogOutput::Saved(result))
+                    .unwrap();
+            }
+        }
+    }
+}
+
+impl PreferencesDialogModel {
+    fn load_settings(&self, widgets: &PreferencesDialogWidgets) {
+        let api = &self.settings.api;
+
+        widgets.endpoint_row.set_text(&api.endpoint);
+        widgets
+            .key_row
+            .set_text(api.key.as_deref().unwrap_or_default());
+        widgets
+            .user_agent_row
+            .set_text(api.user_agent.as_deref().unwrap_or_default());
+        widgets
+            .connect_timeout_row
+            .set_value(api.connect_timeout as f64);
+        widgets
+            .request_timeout_row
+            .set_value(api.request_timeout as f64);
+        // Shown in minutes, seconds are too fine for caching.
+        widgets.cache_ttl_row.set_value((api.cache_ttl / 60) as f64);
+
+        widgets.proxy_mode_row.set_selected(
+            PROXY_MODES
+                .iter()
+                .position(|mode| *mode == api.proxy.mode)
+                .unwrap_or_default() as u32,
+        );
+        widgets.proxy_url_row.set_text(&api.proxy.url);
+        widgets
+            .proxy_username_row
+            .set_text(api.proxy.username.as_deref().unwrap_or_default());
+        widgets
+            .proxy_password_row
+            .set_text(api.proxy.password.as_deref().unwrap_or_default());
+        widgets.no_proxy_row.set_text(&api.proxy.no_proxy);
+
+        update_proxy_rows(widgets);
+        self.update_ca_bundle(widgets);
+    }
+
+    fn read_api_config(&self, widgets: &PreferencesDialogWidgets) -> ApiConfig {
+        fn optional(text: gtk::glib::GString) -> Option<String> {
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_owned())
+        }
+
+        let cache_ttl_minutes = widgets.cache_ttl_row.value() as u64;
+
+        ApiConfig {
+            endpoint: optional(widgets.endpoint_row.text())
+                .unwrap_or_else(|| DEFAULT_API_ENDPOINT.to_owned()),
+            key: optional(widgets.key_row.text()),
+            user_agent: optional(widgets.user_agent_row.text()),
+            connect_timeout: widgets.connect_timeout_row.value() as u64,
+            request_timeout: widgets.request_timeout_row.value() as u64,
+            // Keep seconds that were set in the file when the minutes weren't changed.
+            cache_ttl: if cache_ttl_minutes == self.settings.api.cache_ttl / 60 {
+                self.settings.api.cache_ttl
+            } else {
+                cache_ttl_minutes * 60
+            },
+            proxy: ProxyConfig {
+                mode: selected_proxy_mode(widgets),
+                url: widgets.proxy_url_row.text().trim().to_owned(),
+                username: optional(widgets.proxy_username_row.text()),
+                // Passwords might have meaningful spaces.
+                password: Some(widgets.proxy_password_row.text().to_string())
+                    .filter(|password| !password.is_empty()),
+                no_proxy: widgets.no_proxy_row.text().trim().to_owned(),
+            },
+            ca_bundle: self.ca_bundle.clone(),
+        }
+    }
+
+    fn update_ca_bundle(&self, widgets: &PreferencesDialogWidgets) {
+        match &self.ca_bundle {
+            Some(path) => widgets
+                .ca_bundle_row
+                .set_subtitle(&path.display().to_string()),
+            None => widgets
+                .ca_bundle_row
+                .set_subtitle(&fl!("preferences-ca-bundle-none")),
+        }
+        widgets
+            .ca_bundle_clear_button
+            .set_visible(self.ca_bundle.is_some());
+    }
+}
+
+// Proxy details are only used in the manual mode.
+fn update_proxy_rows(widgets: &PreferencesDialogWidgets) {
+    let is_manual = selected_proxy_mode(widgets) == ProxyMode::Manual;
+
+    widgets.proxy_url_row.set_sensitive(is_manual);
+    widgets.proxy_username_row.set_sensitive(is_manual);
+    widgets.proxy_password_row.set_sensitive(is_manual);
+    widgets.no_proxy_row.set_sensitive(is_manual);
+}
+
+fn selected_proxy_mode(widgets: &PreferencesDialogWidgets) -> ProxyMode {
+    PROXY_MODES
+        .get(widgets.proxy_mode_row.selected() as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Resolve the desktop's proxy for the API endpoint, the API client only knows about the
+/// environment variables.
+///
+/// Other modes are returned as they are.
+pub async fn resolve_system_proxy(mut config: ApiConfig) -> ApiConfig {
+    if config.proxy.mode != ProxyMode::System {
+        return config;
+    }
+
+    let proxies = gtk::gio::ProxyResolver::default()
+        .lookup_future(&config.endpoint)
+        .await
+        .unwrap_or_default();
+
+    // Proxies are in order of preference, `direct://` means that no proxy is needed.
+    if let Some(proxy) = proxies
+        .first()
+        .filter(|proxy| proxy.as_str() != "direct://")
+    {
+        // GIO doesn't tell the SOCKS version, the API client needs one.
+        let url = match proxy.strip_prefix("socks://") {
+            Some(address) => format!("socks5h://{address}"),
+            None => proxy.to_string(),
+        };
+
+        config.proxy = ProxyConfig {
+            mode: ProxyMode::Manual,
+            url,
+            ..ProxyConfig::default()
+        };
+    }
+
+    config
+}
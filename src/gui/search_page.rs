@@ -0,0 +1,355 @@
+use relm4::{adw, gtk::prelude::*, prelude::*};
+
+use super::main_window::api_error_message;
+use crate::api::backend::Backend;
+use crate::api::error::ApiError;
+use crate::api::router::{AppUri, Route};
+use crate::api::stackexchange::{DateExt, Id, Page, QuestionSummary, SearchQuery, Tag, Uri};
+use crate::fl;
+
+pub struct SearchPageModel {
+    client: Backend,
+    site: String,
+    query: SearchQuery,
+    // Questions ids in the same order of the results list rows.
+    results: Vec<Id>,
+    next_page: u32,
+    loading: bool,
+}
+
+pub struct SearchPageInit {
+    pub client: Backend,
+    pub site: String,
+    pub query: SearchQuery,
+}
+
+pub struct SearchPageWidgets {
+    tag_label: gtk::Label,
+    results_list: gtk::ListBox,
+    load_more_button: gtk::Button,
+    spinner: gtk::Spinner,
+    status_label: gtk::Label,
+}
+
+#[derive(Debug)]
+pub enum SearchPageInput {
+    LoadMore,
+    OpenResult(usize),
+}
+
+#[derive(Debug)]
+pub enum SearchPageOutput {
+    RequestPagesByUri(Uri),
+}
+
+#[derive(Debug)]
+pub enum SearchPageCommand {
+    ResultsLoaded(Result<Page<QuestionSummary>, ApiError>),
+    TagLoaded(Result<Option<Tag>, ApiError>),
+}
+
+impl Component for SearchPageModel {
+    type CommandOutput = SearchPageCommand;
+    type Input = SearchPageInput;
+    type Output = SearchPageOutput;
+    type Init = SearchPageInit;
+    type Root = gtk::ScrolledWindow;
+    type Widgets = SearchPageWidgets;
+
+    fn init_root() -> Self::Root {
+        gtk::ScrolledWindow::builder()
+            .vexpand(true)
+            .hexpand(true)
+            .build()
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = SearchPageModel {
+            client: init.client,
+            site: init.site,
+            query: init.query,
+            results: Vec::new(),
+            next_page: 1,
+            loading: false,
+        };
+
+        let main_layout = adw::Clamp::builder().maximum_size(1000).build();
+        root.set_child(Some(&main_layout));
+
+        let results_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .margin_top(10)
+            .margin_bottom(10)
+            .margin_start(10)
+            .margin_end(10)
+            .spacing(10)
+            .build();
+        main_layout.set_child(Some(&results_layout));
+
+        let tag_label = gtk::Label::builder()
+            .css_classes(["dim-label"])
+            .halign(gtk::Align::Start)
+            .visible(false)
+            .build();
+        results_layout.append(&tag_label);
+
+        let results_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        results_layout.append(&results_list);
+
+        results_list.connect_row_activated(gtk::glib::clone!(@strong sender => move |_, row| {
+            sender.input(SearchPageInput::OpenResult(row.index() as usize));
+        }));
+
+        let status_label = gtk::Label::builder()
+            .css_classes(["dim-label"])
+            .wrap(true)
+            .visible(false)
+            .build();
+        results_layout.append(&status_label);
+
+        let spinner = gtk::Spinner::builder().halign(gtk::Align::Center).build();
+        results_layout.append(&spinner);
+
+        let load_more_button = gtk::Button::builder()
+            .label(fl!("search-load-more"))
+            .halign(gtk::Align::Center)
+            .visible(false)
+            .build();
+        results_layout.append(&load_more_button);
+
+        load_more_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(SearchPageInput::LoadMore);
+        }));
+
+        let widgets = SearchPageWidgets {
+            tag_label,
+            results_list,
+            load_more_button,
+            spinner,
+            status_label,
+        };
+
+        sender.input(SearchPageInput::LoadMore);
+
+        // Searching a single tag is like browsing it, so tell how many questions it has.
+        if let [tag] = model.query.tagged.as_slice() {
+            let client = model.client.clone();
+            let site = model.site.clone();
+            let tag = tag.clone();
+
+            sender.oneshot_command(async move {
+                SearchPageCommand::TagLoaded(client.get_tag(&site, &tag).await)
+            });
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            SearchPageInput::LoadMore => {
+                if self.loading {
+                    return;
+                }
+                self.loading = true;
+
+                widgets.load_more_button.set_visible(false);
+                widgets.spinner.start();
+
+                let client = self.client.clone();
+                let site = self.site.clone();
+                let query = self.query.clone();
+                let page = self.next_page;
+
+                sender.oneshot_command(async move {
+                    SearchPageCommand::ResultsLoaded(
+                        client.search_questions(&site, &query, page).await,
+                    )
+                });
+            }
+            SearchPageInput::OpenResult(index) => {
+                if let Some(question_id) = self.results.get(index) {
+                    sender
+                        .output(SearchPageOutput::RequestPagesByUri(
+                            AppUri::new(
+                                &self.site,
+                                Route::Questions {
+                                    ids: vec![*question_id],
+                                    answer_id: None,
+                                    comment_id: None,
+                                },
+                            )
+                            .to_string(),
+                        ))
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            SearchPageCommand::ResultsLoaded(result) => {
+                self.loading = false;
+                widgets.spinner.stop();
+
+                match result {
+                    Ok(page) => {
+                        for question in &page.items {
+                            widgets.results_list.append(&Self::st_result(question));
+                            self.results.push(question.question_id);
+                        }
+
+                        self.next_page += 1;
+                        widgets.load_more_button.set_visible(page.has_more);
+
+                        // Also hides errors of previous attempts.
+                        widgets.status_label.set_label(&fl!("search-no-results"));
+                        widgets.status_label.set_visible(self.results.is_empty());
+                    }
+                    Err(error) => {
+                        widgets
+                            .status_label
+                            .set_label(&fl!("search-failed", error = api_error_message(&error)));
+                        widgets.status_label.set_visible(true);
+                        // Allow retrying the same page.
+                        widgets.load_more_button.set_visible(true);
+                    }
+                }
+            }
+            // It's only a hint, so failures are not shown.
+            SearchPageCommand::TagLoaded(Ok(Some(tag))) => {
+                widgets.tag_label.set_label(&fl!(
+                    "search-tag-questions",
+                    tag = tag.name,
+                    count = tag.count
+                ));
+                widgets.tag_label.set_visible(true);
+            }
+            SearchPageCommand::TagLoaded(_) => {}
+        }
+    }
+}
+
+impl SearchPageModel {
+    pub(super) fn st_result(question: &QuestionSummary) -> gtk::ListBoxRow {
+        let main_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .margin_top(5)
+            .margin_bottom(5)
+            .margin_start(5)
+            .margin_end(5)
+            .build();
+
+        // Score and answers sidebar
+        {
+            let sidebar_layout = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .margin_start(5)
+                .margin_end(10)
+                .valign(gtk::Align::Center)
+                .build();
+            main_layout.append(&sidebar_layout);
+
+            sidebar_layout.append(
+                &gtk::Label::builder()
+                    .label(question.score.to_string())
+                    .css_classes(if question.score >= 0 {
+                        ["success"]
+                    } else {
+                        ["error"]
+                    })
+                    .build(),
+            );
+
+            sidebar_layout.append(
+                &gtk::Label::builder()
+                    .label(fl!("answers-count", count = question.answer_count))
+                    .css_classes(if question.is_answered {
+                        ["success", "caption"]
+                    } else {
+                        ["dim-label", "caption"]
+                    })
+                    .build(),
+            );
+        }
+
+        let body_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .hexpand(true)
+            .build();
+        main_layout.append(&body_layout);
+
+        body_layout.append(
+            &gtk::Label::builder()
+                .label(&question.title)
+                .css_classes(["heading"])
+                .halign(gtk::Align::Start)
+                .xalign(0.0)
+                .wrap(true)
+                .build(),
+        );
+
+        // Tags and meta data
+        {
+            let meta_layout = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(5)
+                .margin_top(5)
+                .build();
+            body_layout.append(&meta_layout);
+
+            for tag in &question.tags {
+                meta_layout.append(
+                    &gtk::Label::builder()
+                        .label(tag)
+                        .css_classes(["tag", "caption"])
+                        .build(),
+                );
+            }
+
+            meta_layout.append(
+                &gtk::Label::builder()
+                    .use_markup(true)
+                    .label(fl!(
+                        "last-active-time",
+                        time = question.last_activity_date.formate_date_time_string()
+                    ))
+                    .css_classes(["caption"])
+                    .halign(gtk::Align::End)
+                    .hexpand(true)
+                    .build(),
+            );
+        }
+
+        gtk::ListBoxRow::builder()
+            .child(&main_layout)
+            .activatable(true)
+            .tooltip_markup(fl!(
+                "search-result-tooltip",
+                id = question.question_id,
+                views = question.view_count,
+                owner = gtk::glib::markup_escape_text(&question.owner.display_name).as_str()
+            ))
+            .build()
+    }
+}
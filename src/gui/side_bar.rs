@@ -5,21 +5,40 @@ use relm4::{
 };
 use relm4_icons::icon_names;
 
+use super::bookmarks_page::{BookmarksPageModel, BookmarksPageOutput};
+use super::history_page::{HistoryPageModel, HistoryPageOutput};
+use crate::api::stackexchange::{Id, Uri};
 use crate::fl;
+use crate::storage::bookmarks::Bookmark;
 
-pub struct SideBarModel;
+pub struct SideBarModel {
+    bookmarks_page: Controller<BookmarksPageModel>,
+    history_page: Controller<HistoryPageModel>,
+}
 
 pub struct SideBarWidgest;
 
 #[derive(Debug)]
-pub struct SideBarInput;
+pub enum SideBarInput {
+    Bookmarks(BookmarksPageOutput),
+    History(HistoryPageOutput),
+}
+
+#[derive(Debug)]
+pub enum SideBarOutput {
+    RequestPagesByUri(Uri),
+    UpdateBookmark(Bookmark),
+    RemoveBookmark(String, Id),
+    RemoveHistoryEntry(String, Id, i64),
+    ClearHistory(i64, i64),
+}
 
 impl SimpleAsyncComponent for SideBarModel {
     type Init = ();
     type Root = gtk::Box;
     type Widgets = SideBarWidgest;
     type Input = SideBarInput;
-    type Output = ();
+    type Output = SideBarOutput;
 
     fn init_root() -> Self::Root {
         gtk::Box::new(gtk::Orientation::Vertical, 0)
@@ -28,9 +47,16 @@ impl SimpleAsyncComponent for SideBarModel {
     async fn init(
         _init: Self::Init,
         root: Self::Root,
-        _sender: AsyncComponentSender<Self>,
+        sender: AsyncComponentSender<Self>,
     ) -> AsyncComponentParts<Self> {
-        let model = SideBarModel {};
+        let model = SideBarModel {
+            bookmarks_page: BookmarksPageModel::builder()
+                .launch(())
+                .forward(sender.input_sender(), SideBarInput::Bookmarks),
+            history_page: HistoryPageModel::builder()
+                .launch(())
+                .forward(sender.input_sender(), SideBarInput::History),
+        };
 
         let side_bar_view = adw::ViewStack::builder()
             .css_classes(["background"])
@@ -48,27 +74,17 @@ impl SimpleAsyncComponent for SideBarModel {
         root.append(&side_bar_view);
 
         // Side bar pages
-        // TODO: Implement bookmarks
         side_bar_view.add_titled_with_icon(
-            &adw::StatusPage::builder()
-                .title("Bookmarks")
-                .child(&gtk::Label::new(Some(&fl!("placeholder"))))
-                .icon_name(icon_names::LIBRARY)
-                .build(),
+            model.bookmarks_page.widget(),
             None,
-            "Bookmarks",
+            &fl!("bookmarks"),
             icon_names::LIBRARY,
         );
 
-        // TODO: Implement history
         side_bar_view.add_titled_with_icon(
-            &adw::StatusPage::builder()
-                .title("History")
-                .child(&gtk::Label::new(Some(&fl!("placeholder"))))
-                .icon_name(icon_names::HISTORY_UNDO)
-                .build(),
+            model.history_page.widget(),
             None,
-            "History",
+            &fl!("history"),
             icon_names::HISTORY_UNDO,
         );
 
@@ -76,4 +92,43 @@ impl SimpleAsyncComponent for SideBarModel {
 
         AsyncComponentParts { model, widgets }
     }
+
+    async fn update(&mut self, message: Self::Input, sender: AsyncComponentSender<Self>) {
+        match message {
+            SideBarInput::Bookmarks(BookmarksPageOutput::RequestPagesByUri(uri)) => {
+                sender
+                    .output(SideBarOutput::RequestPagesByUri(uri))
+                    .unwrap();
+            }
+            SideBarInput::Bookmarks(BookmarksPageOutput::UpdateBookmark(bookmark)) => {
+                sender
+                    .output(SideBarOutput::UpdateBookmark(bookmark))
+                    .unwrap();
+            }
+            SideBarInput::Bookmarks(BookmarksPageOutput::RemoveBookmark(site, question_id)) => {
+                sender
+                    .output(SideBarOutput::RemoveBookmark(site, question_id))
+                    .unwrap();
+            }
+            SideBarInput::History(HistoryPageOutput::RequestPagesByUri(uri)) => {
+                sender
+                    .output(SideBarOutput::RequestPagesByUri(uri))
+                    .unwrap();
+            }
+            SideBarInput::History(HistoryPageOutput::RemoveEntry(site, question_id, visited)) => {
+                sender
+                    .output(SideBarOutput::RemoveHistoryEntry(
+                        site,
+                        question_id,
+                        visited,
+                    ))
+                    .unwrap();
+            }
+            SideBarInput::History(HistoryPageOutput::Clear(from, to)) => {
+                sender
+                    .output(SideBarOutput::ClearHistory(from, to))
+                    .unwrap();
+            }
+        }
+    }
 }
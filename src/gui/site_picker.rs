@@ -0,0 +1,237 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use relm4::{
+    adw::{self, prelude::*},
+    gtk::{gdk, glib},
+    prelude::*,
+};
+use relm4_icons::icon_names;
+
+use crate::api::backend::Backend;
+use crate::api::error::ApiError;
+use crate::api::sites::SiteCatalog;
+use crate::api::stackexchange::Site;
+use crate::fl;
+
+const SITE_ICON_SIZE: i32 = 24;
+
+/// Button in the header to select the site used for searching.
+pub struct SitePickerModel {
+    catalog: SiteCatalog,
+    client: Backend,
+}
+
+pub struct SitePickerInit {
+    pub client: Backend,
+    pub selected_site: String,
+}
+
+pub struct SitePickerWidgets {
+    sites_list: gtk::ListBox,
+    popover: gtk::Popover,
+}
+
+#[derive(Debug)]
+pub enum SitePickerInput {
+    Select(usize),
+}
+
+#[derive(Debug)]
+pub enum SitePickerOutput {
+    SiteSelected(Site),
+}
+
+#[derive(Debug)]
+pub enum SitePickerCommand {
+    CatalogRefreshed(Result<SiteCatalog, ApiError>),
+}
+
+impl Component for SitePickerModel {
+    type CommandOutput = SitePickerCommand;
+    type Input = SitePickerInput;
+    type Output = SitePickerOutput;
+    type Init = SitePickerInit;
+    type Root = gtk::MenuButton;
+    type Widgets = SitePickerWidgets;
+
+    fn init_root() -> Self::Root {
+        gtk::MenuButton::builder()
+            .tooltip_text(fl!("site-picker-tooltip"))
+            .build()
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = SitePickerModel {
+            catalog: SiteCatalog::load_local(),
+            client: init.client,
+        };
+
+        root.set_label(
+            &model
+                .catalog
+                .find(&init.selected_site)
+                .map_or(init.selected_site.clone(), |site| site.name.clone()),
+        );
+
+        let popover_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(5)
+            .build();
+
+        let filter_entry = gtk::SearchEntry::builder()
+            .placeholder_text(fl!("site-picker-filter-placeholder"))
+            .build();
+        popover_layout.append(&filter_entry);
+
+        let sites_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["navigation-sidebar"])
+            .build();
+
+        popover_layout.append(
+            &gtk::ScrolledWindow::builder()
+                .child(&sites_list)
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .min_content_height(400)
+                .min_content_width(300)
+                .build(),
+        );
+
+        // Match the filter text with the site name or its audience.
+        sites_list.set_filter_func(gtk::glib::clone!(@strong filter_entry => move |row| {
+            let filter_text = filter_entry.text().to_lowercase();
+
+            match row.downcast_ref::<adw::ActionRow>() {
+                Some(row) => {
+                    row.title().to_lowercase().contains(&filter_text)
+                        || row.subtitle().is_some_and(|subtitle| {
+                            subtitle.to_lowercase().contains(&filter_text)
+                        })
+                }
+                None => true,
+            }
+        }));
+
+        filter_entry.connect_search_changed(gtk::glib::clone!(@strong sites_list => move |_| {
+            sites_list.invalidate_filter();
+        }));
+
+        sites_list.connect_row_activated(gtk::glib::clone!(@strong sender => move |_, row| {
+            sender.input(SitePickerInput::Select(row.index() as usize));
+        }));
+
+        let popover = gtk::Popover::builder().child(&popover_layout).build();
+        root.set_popover(Some(&popover));
+
+        let widgets = SitePickerWidgets {
+            sites_list,
+            popover,
+        };
+
+        model.fill_sites_list(&widgets.sites_list);
+
+        if SiteCatalog::is_stale() {
+            let client = model.client.clone();
+
+            sender.oneshot_command(async move {
+                SitePickerCommand::CatalogRefreshed(SiteCatalog::refresh(client.as_ref()).await)
+            });
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            SitePickerInput::Select(index) => {
+                if let Some(site) = self.catalog.sites().get(index) {
+                    root.set_label(&site.name);
+                    widgets.popover.popdown();
+
+                    sender
+                        .output(SitePickerOutput::SiteSelected(site.clone()))
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            SitePickerCommand::CatalogRefreshed(Ok(catalog)) => {
+                self.catalog = catalog;
+                self.fill_sites_list(&widgets.sites_list);
+            }
+            // Keep using the local catalog.
+            SitePickerCommand::CatalogRefreshed(Err(_)) => {}
+        }
+    }
+}
+
+impl SitePickerModel {
+    fn fill_sites_list(&self, sites_list: &gtk::ListBox) {
+        sites_list.remove_all();
+
+        for site in self.catalog.sites() {
+            let row = adw::ActionRow::builder()
+                .title(&site.name)
+                .subtitle(&site.audience)
+                .subtitle_lines(1)
+                .use_markup(false)
+                .activatable(true)
+                .tooltip_text(&site.site_url)
+                .build();
+
+            // Shown until the site's icon is loaded, or when it can't be.
+            let icon = gtk::Image::builder()
+                .icon_name(icon_names::EARTH)
+                .pixel_size(SITE_ICON_SIZE)
+                .build();
+            row.add_prefix(&icon);
+
+            // Icons are only downloaded once the list is shown, then they are in the images cache.
+            if !site.icon_url.is_empty() {
+                let client = self.client.clone();
+                let icon_url = site.icon_url.clone();
+                let requested = Rc::new(Cell::new(false));
+
+                row.connect_map(move |_| {
+                    if !requested.replace(true) {
+                        load_site_icon(client.clone(), icon_url.clone(), icon.clone());
+                    }
+                });
+            }
+
+            sites_list.append(&row);
+        }
+    }
+}
+
+fn load_site_icon(client: Backend, icon_url: String, icon: gtk::Image) {
+    relm4::spawn_local(async move {
+        let image = relm4::spawn(async move { client.get_image(&icon_url).await }).await;
+
+        if let Ok(Ok(image)) = image {
+            if let Ok(texture) = gdk::Texture::from_bytes(&glib::Bytes::from_owned(image)) {
+                icon.set_paintable(Some(&texture));
+            }
+        }
+    });
+}
@@ -0,0 +1,404 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use relm4::{adw, gtk::prelude::*, prelude::*};
+
+use super::componant_builders::{QuestionPageInit, QuestionPageModel, QuestionPageOutput};
+use super::main_window::api_error_message;
+use super::search_page::{SearchPageInit, SearchPageModel, SearchPageOutput};
+use super::user_page::{UserPageInit, UserPageModel, UserPageOutput};
+use crate::api::backend::Backend;
+use crate::api::error::ApiError;
+use crate::api::router::{AppUri, Route};
+use crate::api::stackexchange::{Id, Question, Uri};
+use crate::fl;
+use crate::storage::bookmarks::Bookmark;
+use crate::storage::session::SessionTab;
+
+// Stop restoring the scroll offset of a page that doesn't grow enough after this.
+const RESTORE_SCROLL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Every kind of page that could be opened in a tab.
+pub enum TabPageInit {
+    Question(QuestionPageInit),
+    Search(SearchPageInit),
+    User(UserPageInit),
+    /// A tab of the last session, only loaded once it's selected.
+    Restored {
+        tab: SessionTab,
+        client: Backend,
+    },
+}
+
+enum TabContent {
+    Pending {
+        client: Backend,
+        placeholder: adw::StatusPage,
+        loading: bool,
+    },
+    Question(Controller<QuestionPageModel>),
+    Search(Controller<SearchPageModel>),
+    User(Controller<UserPageModel>),
+}
+
+/// A tab in the main `TabView`, hosting one page component.
+pub struct TabPageModel {
+    title: String,
+    // Application uri of the page, to restore it in the next session.
+    uri: Uri,
+    // Scroll offset to restore once the content is loaded.
+    scroll: f64,
+    content: TabContent,
+    tab_page: Option<adw::TabPage>,
+}
+
+pub struct TabPageWidgets;
+
+#[derive(Debug)]
+pub enum TabPageInput {
+    SetTitle(String),
+    /// Load the content of a restored tab, does nothing for other tabs.
+    Load,
+}
+
+#[derive(Debug)]
+pub enum TabPageOutput {
+    RequestPagesByUri(Uri),
+    AddBookmark(Bookmark),
+    RemoveBookmark(String, Id),
+    Scrolled(String, Id, f64),
+}
+
+#[derive(Debug)]
+pub enum TabPageCommand {
+    QuestionLoaded(String, Result<Vec<Question>, ApiError>),
+}
+
+impl FactoryComponent for TabPageModel {
+    type ParentWidget = adw::TabView;
+    type CommandOutput = TabPageCommand;
+    type Input = TabPageInput;
+    type Output = TabPageOutput;
+    type Init = TabPageInit;
+    type Root = gtk::Box;
+    type Widgets = TabPageWidgets;
+    type Index = DynamicIndex;
+
+    fn init_model(init: Self::Init, _index: &Self::Index, sender: FactorySender<Self>) -> Self {
+        let scroll = match &init {
+            TabPageInit::Restored { tab, .. } => tab.scroll,
+            _ => 0.0,
+        };
+
+        let (title, uri, content) = Self::launch_content(init, &sender);
+
+        TabPageModel {
+            title,
+            uri,
+            scroll,
+            content,
+            tab_page: None,
+        }
+    }
+
+    fn init_root(&self) -> Self::Root {
+        gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build()
+    }
+
+    fn init_widgets(
+        &mut self,
+        _index: &Self::Index,
+        root: Self::Root,
+        retured_widget: &adw::TabPage,
+        _sender: FactorySender<Self>,
+    ) -> Self::Widgets {
+        root.append(&self.content_widget());
+
+        retured_widget.set_title(&self.title);
+
+        // TODO: Pass question tags as keywords.
+        // retured_widget.set_keyword(keyword);
+
+        self.tab_page = Some(retured_widget.clone());
+
+        TabPageWidgets {}
+    }
+
+    fn update(&mut self, message: Self::Input, sender: FactorySender<Self>) {
+        match message {
+            TabPageInput::SetTitle(title) => {
+                if let Some(tab_page) = &self.tab_page {
+                    tab_page.set_title(&title);
+                }
+                self.title = title;
+            }
+            TabPageInput::Load => {
+                let TabContent::Pending {
+                    client,
+                    placeholder,
+                    loading,
+                } = &mut self.content
+                else {
+                    return;
+                };
+
+                if *loading {
+                    return;
+                }
+
+                let init = match AppUri::parse(&self.uri) {
+                    Ok(AppUri {
+                        site,
+                        route: Route::Questions { ids, .. },
+                    }) => {
+                        *loading = true;
+                        placeholder.set_description(None);
+                        placeholder
+                            .set_child(Some(&gtk::Spinner::builder().spinning(true).build()));
+
+                        let client = client.clone();
+
+                        sender.oneshot_command(async move {
+                            let result = client.get_questions(&site, &ids[..1]).await;
+                            TabPageCommand::QuestionLoaded(site, result)
+                        });
+
+                        return;
+                    }
+                    Ok(AppUri {
+                        site,
+                        route: Route::Search(query),
+                    }) => TabPageInit::Search(SearchPageInit {
+                        client: client.clone(),
+                        site,
+                        query,
+                    }),
+                    Ok(AppUri {
+                        site,
+                        route: Route::User(user_id),
+                    }) => TabPageInit::User(UserPageInit {
+                        client: client.clone(),
+                        site,
+                        user_id,
+                    }),
+                    Ok(_) => return,
+                    Err(error) => {
+                        placeholder.set_description(Some(&api_error_message(&error)));
+                        return;
+                    }
+                };
+
+                self.replace_content(init, &sender);
+            }
+        }
+    }
+
+    fn update_cmd(&mut self, message: Self::CommandOutput, sender: FactorySender<Self>) {
+        match message {
+            TabPageCommand::QuestionLoaded(site, result) => {
+                let TabContent::Pending {
+                    client,
+                    placeholder,
+                    loading,
+                } = &mut self.content
+                else {
+                    return;
+                };
+
+                *loading = false;
+
+                let error_message = match result {
+                    Ok(mut questions) if !questions.is_empty() => {
+                        let init = TabPageInit::Question(QuestionPageInit {
+                            question: questions.swap_remove(0),
+                            site,
+                            client: client.clone(),
+                            focus: None,
+                        });
+
+                        self.replace_content(init, &sender);
+                        return;
+                    }
+                    Ok(_) => fl!("no-questions-found"),
+                    Err(error) => api_error_message(&error),
+                };
+
+                let retry_button = gtk::Button::builder()
+                    .label(fl!("retry"))
+                    .halign(gtk::Align::Center)
+                    .css_classes(["pill"])
+                    .build();
+
+                retry_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+                    sender.input(TabPageInput::Load);
+                }));
+
+                placeholder.set_description(Some(&error_message));
+                placeholder.set_child(Some(&retry_button));
+            }
+        }
+    }
+}
+
+impl TabPageModel {
+    pub fn tab_page(&self) -> Option<&adw::TabPage> {
+        self.tab_page.as_ref()
+    }
+
+    /// State of the tab to be restored in the next session.
+    pub fn session_tab(&self) -> Option<SessionTab> {
+        let tab_page = self.tab_page.as_ref()?;
+
+        Some(SessionTab {
+            uri: self.uri.clone(),
+            title: self.title.clone(),
+            pinned: tab_page.is_pinned(),
+            scroll: self
+                .scrolled_window()
+                .map_or(self.scroll, |scrolled_window| {
+                    scrolled_window.vadjustment().value()
+                }),
+        })
+    }
+
+    fn launch_content(
+        init: TabPageInit,
+        sender: &FactorySender<Self>,
+    ) -> (String, Uri, TabContent) {
+        match init {
+            TabPageInit::Question(init) => (
+                init.question.title.clone(),
+                AppUri::new(
+                    &init.site,
+                    Route::Questions {
+                        ids: vec![init.question.question_id],
+                        answer_id: None,
+                        comment_id: None,
+                    },
+                )
+                .to_string(),
+                TabContent::Question(QuestionPageModel::builder().launch(init).forward(
+                    sender.output_sender(),
+                    |message| match message {
+                        QuestionPageOutput::AddBookmark(bookmark) => {
+                            TabPageOutput::AddBookmark(bookmark)
+                        }
+                        QuestionPageOutput::RemoveBookmark(site, question_id) => {
+                            TabPageOutput::RemoveBookmark(site, question_id)
+                        }
+                        QuestionPageOutput::Scrolled(site, question_id, scroll) => {
+                            TabPageOutput::Scrolled(site, question_id, scroll)
+                        }
+                    },
+                )),
+            ),
+            TabPageInit::Search(init) => (
+                fl!("search-tab-title", query = init.query.to_string()),
+                AppUri::new(&init.site, Route::Search(init.query.clone())).to_string(),
+                TabContent::Search(SearchPageModel::builder().launch(init).forward(
+                    sender.output_sender(),
+                    |message| match message {
+                        SearchPageOutput::RequestPagesByUri(uri) => {
+                            TabPageOutput::RequestPagesByUri(uri)
+                        }
+                    },
+                )),
+            ),
+            TabPageInit::User(init) => (
+                fl!("user-tab-title", id = init.user_id),
+                AppUri::new(&init.site, Route::User(init.user_id)).to_string(),
+                TabContent::User(UserPageModel::builder().launch(init).connect_receiver(
+                    gtk::glib::clone!(@strong sender => move |_, message| match message {
+                        UserPageOutput::RequestPagesByUri(uri) => {
+                            sender.output(TabPageOutput::RequestPagesByUri(uri)).unwrap();
+                        }
+                        UserPageOutput::TitleChanged(title) => {
+                            sender.input(TabPageInput::SetTitle(title));
+                        }
+                    }),
+                )),
+            ),
+            TabPageInit::Restored { tab, client } => (
+                tab.title.clone(),
+                tab.uri,
+                TabContent::Pending {
+                    client,
+                    placeholder: adw::StatusPage::builder()
+                        .title(&tab.title)
+                        .vexpand(true)
+                        .build(),
+                    loading: false,
+                },
+            ),
+        }
+    }
+
+    // Show the loaded page of a restored tab in place of its placeholder.
+    fn replace_content(&mut self, init: TabPageInit, sender: &FactorySender<Self>) {
+        let (_, _, content) = Self::launch_content(init, sender);
+        self.content = content;
+
+        if let Some(root) = self
+            .tab_page
+            .as_ref()
+            .and_then(|tab_page| tab_page.child().downcast::<gtk::Box>().ok())
+        {
+            while let Some(child) = root.first_child() {
+                root.remove(&child);
+            }
+            root.append(&self.content_widget());
+        }
+
+        if let Some(scrolled_window) = self.scrolled_window().filter(|_| self.scroll > 0.0) {
+            restore_scroll(scrolled_window, self.scroll);
+        }
+    }
+
+    fn content_widget(&self) -> gtk::Widget {
+        match &self.content {
+            TabContent::Pending { placeholder, .. } => placeholder.clone().upcast(),
+            TabContent::Question(controller) => controller.widget().clone().upcast(),
+            TabContent::Search(controller) => controller.widget().clone().upcast(),
+            TabContent::User(controller) => controller.widget().clone().upcast(),
+        }
+    }
+
+    fn scrolled_window(&self) -> Option<&gtk::ScrolledWindow> {
+        match &self.content {
+            TabContent::Pending { .. } => None,
+            TabContent::Question(controller) => Some(controller.widget()),
+            TabContent::Search(controller) => Some(controller.widget()),
+            TabContent::User(controller) => Some(controller.widget()),
+        }
+    }
+}
+
+// Pages grow while their content is loaded, so keep scrolling until the offset is reached.
+fn restore_scroll(scrolled_window: &gtk::ScrolledWindow, offset: f64) {
+    let adjustment = scrolled_window.vadjustment();
+    let handler = Rc::new(RefCell::new(None));
+
+    let handler_id =
+        adjustment.connect_changed(gtk::glib::clone!(@strong handler => move |adjustment| {
+            adjustment.set_value(offset);
+
+            if adjustment.value() >= offset {
+                if let Some(handler_id) = handler.take() {
+                    adjustment.disconnect(handler_id);
+                }
+            }
+        }));
+    handler.replace(Some(handler_id));
+
+    gtk::glib::timeout_add_local_once(
+        RESTORE_SCROLL_TIMEOUT,
+        gtk::glib::clone!(@weak adjustment => move || {
+            if let Some(handler_id) = handler.take() {
+                adjustment.disconnect(handler_id);
+            }
+        }),
+    );
+}
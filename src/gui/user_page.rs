@@ -0,0 +1,373 @@
+use relm4::{adw, gtk::prelude::*, prelude::*};
+
+use super::main_window::api_error_message;
+use super::search_page::SearchPageModel;
+use crate::api::backend::Backend;
+use crate::api::error::ApiError;
+use crate::api::router::{AppUri, Route};
+use crate::api::stackexchange::{
+    DateExt, Id, Page, QuestionSummary, SearchQuery, SearchSort, Uri, UserProfile,
+};
+use crate::fl;
+
+/// A user profile with a list of its questions.
+pub struct UserPageModel {
+    client: Backend,
+    site: String,
+    user_id: Id,
+    // Questions ids in the same order of the questions list rows.
+    questions: Vec<Id>,
+    next_page: u32,
+    loading: bool,
+}
+
+pub struct UserPageInit {
+    pub client: Backend,
+    pub site: String,
+    pub user_id: Id,
+}
+
+pub struct UserPageWidgets {
+    profile_layout: gtk::Box,
+    questions_list: gtk::ListBox,
+    load_more_button: gtk::Button,
+    spinner: gtk::Spinner,
+    status_label: gtk::Label,
+}
+
+#[derive(Debug)]
+pub enum UserPageInput {
+    LoadMore,
+    OpenQuestion(usize),
+}
+
+#[derive(Debug)]
+pub enum UserPageOutput {
+    RequestPagesByUri(Uri),
+    TitleChanged(String),
+}
+
+#[derive(Debug)]
+pub enum UserPageCommand {
+    ProfileLoaded(Result<Option<UserProfile>, ApiError>),
+    QuestionsLoaded(Result<Page<QuestionSummary>, ApiError>),
+}
+
+impl Component for UserPageModel {
+    type CommandOutput = UserPageCommand;
+    type Input = UserPageInput;
+    type Output = UserPageOutput;
+    type Init = UserPageInit;
+    type Root = gtk::ScrolledWindow;
+    type Widgets = UserPageWidgets;
+
+    fn init_root() -> Self::Root {
+        gtk::ScrolledWindow::builder()
+            .vexpand(true)
+            .hexpand(true)
+            .build()
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = UserPageModel {
+            client: init.client,
+            site: init.site,
+            user_id: init.user_id,
+            questions: Vec::new(),
+            next_page: 1,
+            loading: false,
+        };
+
+        let main_layout = adw::Clamp::builder().maximum_size(1000).build();
+        root.set_child(Some(&main_layout));
+
+        let page_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .margin_top(10)
+            .margin_bottom(10)
+            .margin_start(10)
+            .margin_end(10)
+            .spacing(10)
+            .build();
+        main_layout.set_child(Some(&page_layout));
+
+        let profile_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(5)
+            .build();
+        page_layout.append(&profile_layout);
+
+        page_layout.append(
+            &gtk::Label::builder()
+                .label(fl!("user-questions"))
+                .css_classes(["title-4"])
+                .halign(gtk::Align::Start)
+                .margin_top(10)
+                .build(),
+        );
+
+        let questions_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        page_layout.append(&questions_list);
+
+        questions_list.connect_row_activated(gtk::glib::clone!(@strong sender => move |_, row| {
+            sender.input(UserPageInput::OpenQuestion(row.index() as usize));
+        }));
+
+        let status_label = gtk::Label::builder()
+            .css_classes(["dim-label"])
+            .wrap(true)
+            .visible(false)
+            .build();
+        page_layout.append(&status_label);
+
+        let spinner = gtk::Spinner::builder().halign(gtk::Align::Center).build();
+        page_layout.append(&spinner);
+
+        let load_more_button = gtk::Button::builder()
+            .label(fl!("search-load-more"))
+            .halign(gtk::Align::Center)
+            .visible(false)
+            .build();
+        page_layout.append(&load_more_button);
+
+        load_more_button.connect_clicked(gtk::glib::clone!(@strong sender => move |_| {
+            sender.input(UserPageInput::LoadMore);
+        }));
+
+        let widgets = UserPageWidgets {
+            profile_layout,
+            questions_list,
+            load_more_button,
+            spinner,
+            status_label,
+        };
+
+        {
+            let client = model.client.clone();
+            let site = model.site.clone();
+            let user_id = model.user_id;
+
+            sender.oneshot_command(async move {
+                UserPageCommand::ProfileLoaded(client.get_user(&site, user_id).await)
+            });
+        }
+
+        sender.input(UserPageInput::LoadMore);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            UserPageInput::LoadMore => {
+                if self.loading {
+                    return;
+                }
+                self.loading = true;
+
+                widgets.load_more_button.set_visible(false);
+                widgets.spinner.start();
+
+                let client = self.client.clone();
+                let site = self.site.clone();
+                let query = SearchQuery {
+                    user: Some(self.user_id),
+                    sort: SearchSort::Votes,
+                    ..Default::default()
+                };
+                let page = self.next_page;
+
+                sender.oneshot_command(async move {
+                    UserPageCommand::QuestionsLoaded(
+                        client.search_questions(&site, &query, page).await,
+                    )
+                });
+            }
+            UserPageInput::OpenQuestion(index) => {
+                if let Some(question_id) = self.questions.get(index) {
+                    sender
+                        .output(UserPageOutput::RequestPagesByUri(
+                            AppUri::new(
+                                &self.site,
+                                Route::Questions {
+                                    ids: vec![*question_id],
+                                    answer_id: None,
+                                    comment_id: None,
+                                },
+                            )
+                            .to_string(),
+                        ))
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            UserPageCommand::ProfileLoaded(Ok(Some(user))) => {
+                sender
+                    .output(UserPageOutput::TitleChanged(user.display_name.clone()))
+                    .unwrap();
+
+                Self::fill_profile(&widgets.profile_layout, &user);
+            }
+            UserPageCommand::ProfileLoaded(Ok(None)) => {
+                widgets.profile_layout.append(
+                    &gtk::Label::builder()
+                        .label(fl!("user-not-found", id = self.user_id))
+                        .css_classes(["title-2"])
+                        .build(),
+                );
+            }
+            UserPageCommand::ProfileLoaded(Err(error)) => {
+                widgets.profile_layout.append(
+                    &gtk::Label::builder()
+                        .label(fl!(
+                            "user-loading-failed",
+                            error = api_error_message(&error)
+                        ))
+                        .css_classes(["dim-label"])
+                        .wrap(true)
+                        .build(),
+                );
+            }
+            UserPageCommand::QuestionsLoaded(result) => {
+                self.loading = false;
+                widgets.spinner.stop();
+
+                match result {
+                    Ok(page) => {
+                        for question in &page.items {
+                            widgets
+                                .questions_list
+                                .append(&SearchPageModel::st_result(question));
+                            self.questions.push(question.question_id);
+                        }
+
+                        self.next_page += 1;
+                        widgets.load_more_button.set_visible(page.has_more);
+
+                        // Also hides errors of previous attempts.
+                        widgets.status_label.set_label(&fl!("user-no-questions"));
+                        widgets.status_label.set_visible(self.questions.is_empty());
+                    }
+                    Err(error) => {
+                        widgets
+                            .status_label
+                            .set_label(&fl!("search-failed", error = api_error_message(&error)));
+                        widgets.status_label.set_visible(true);
+                        // Allow retrying the same page.
+                        widgets.load_more_button.set_visible(true);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl UserPageModel {
+    fn fill_profile(profile_layout: &gtk::Box, user: &UserProfile) {
+        let header_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(10)
+            .build();
+        profile_layout.append(&header_layout);
+
+        // TODO: Show user image.
+        header_layout.append(
+            &adw::Avatar::builder()
+                .name(&user.display_name)
+                .show_initials(true)
+                .size(64)
+                .build(),
+        );
+
+        let details_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .valign(gtk::Align::Center)
+            .spacing(3)
+            .build();
+        header_layout.append(&details_layout);
+
+        details_layout.append(
+            &gtk::Label::builder()
+                .label(&user.display_name)
+                .css_classes(["title-2"])
+                .halign(gtk::Align::Start)
+                .wrap(true)
+                .build(),
+        );
+
+        details_layout.append(
+            &gtk::Label::builder()
+                .use_markup(true)
+                .label(fl!(
+                    "user-reputation",
+                    reputation = user.reputation,
+                    gold = user.badge_counts.gold,
+                    silver = user.badge_counts.silver,
+                    bronze = user.badge_counts.bronze
+                ))
+                .halign(gtk::Align::Start)
+                .build(),
+        );
+
+        let meta_layout = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(3)
+            .build();
+        profile_layout.append(&meta_layout);
+
+        if let Some(location) = &user.location {
+            meta_layout.append(
+                &gtk::Label::builder()
+                    .label(location)
+                    .css_classes(["dim-label"])
+                    .halign(gtk::Align::Start)
+                    .build(),
+            );
+        }
+
+        meta_layout.append(
+            &gtk::Label::builder()
+                .use_markup(true)
+                .label(fl!(
+                    "user-member-since",
+                    time = user.creation_date.formate_date_time_string()
+                ))
+                .css_classes(["caption"])
+                .halign(gtk::Align::Start)
+                .build(),
+        );
+
+        if let Some(website_url) = user.website_url.as_deref().filter(|url| !url.is_empty()) {
+            meta_layout.append(
+                &gtk::LinkButton::builder()
+                    .label(website_url)
+                    .uri(website_url)
+                    .halign(gtk::Align::Start)
+                    .build(),
+            );
+        }
+    }
+}
@@ -4,6 +4,9 @@ use relm4::gtk;
 mod api;
 mod gui;
 mod i18n;
+mod storage;
+#[cfg(test)]
+mod test_utils;
 mod utils;
 
 const APP_ID: &str = "io.github.zefr0x.stackbloatless";
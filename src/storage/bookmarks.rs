@@ -0,0 +1,160 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::stackexchange::{Id, Question};
+
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+/// A saved question.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub site: String,
+    pub question_id: Id,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub score: i32,
+    /// Unix time of when it was bookmarked.
+    pub added: i64,
+    /// User defined folder, `None` for unfiled bookmarks.
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// User defined tags, unrelated to the site's tags of the question.
+    #[serde(default)]
+    pub local_tags: Vec<String>,
+    /// Free-form markdown note.
+    #[serde(default)]
+    pub note: String,
+}
+
+impl Bookmark {
+    pub fn new(site: &str, question: &Question) -> Self {
+        Self {
+            site: site.to_owned(),
+            question_id: question.question_id,
+            title: question.title.clone(),
+            tags: question.tags.clone(),
+            score: question.score,
+            added: super::now(),
+            folder: None,
+            local_tags: Vec::new(),
+            note: String::new(),
+        }
+    }
+}
+
+/// Bookmarks saved in the user data directory.
+#[derive(Debug, Default)]
+pub struct BookmarkStore {
+    bookmarks: Vec<Bookmark>,
+}
+
+impl BookmarkStore {
+    pub fn load() -> io::Result<Self> {
+        Ok(Self {
+            bookmarks: super::read_json(&super::data_file(BOOKMARKS_FILE))?.unwrap_or_default(),
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        super::write_json(&super::data_file(BOOKMARKS_FILE), &self.bookmarks)
+    }
+
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    pub fn contains(&self, site: &str, question_id: Id) -> bool {
+        self.bookmarks
+            .iter()
+            .any(|bookmark| bookmark.site == site && bookmark.question_id == question_id)
+    }
+
+    /// Sorted names of folders that have bookmarks.
+    pub fn folders(&self) -> Vec<String> {
+        let mut folders: Vec<String> = self
+            .bookmarks
+            .iter()
+            .filter_map(|bookmark| bookmark.folder.clone())
+            .collect();
+        folders.sort_unstable();
+        folders.dedup();
+
+        folders
+    }
+
+    /// Add a bookmark, or update it if the question is already bookmarked.
+    pub fn add(&mut self, bookmark: Bookmark) {
+        match self.find_mut(&bookmark.site, bookmark.question_id) {
+            // Keep the original date, and how the user organized it.
+            Some(existing) => {
+                *existing = Bookmark {
+                    added: existing.added,
+                    folder: existing.folder.take(),
+                    local_tags: std::mem::take(&mut existing.local_tags),
+                    note: std::mem::take(&mut existing.note),
+                    ..bookmark
+                }
+            }
+            None => self.bookmarks.push(bookmark),
+        }
+    }
+
+    /// Replace an existing bookmark, such as after editing its folder, tags or note.
+    pub fn update(&mut self, bookmark: Bookmark) {
+        if let Some(existing) = self.find_mut(&bookmark.site, bookmark.question_id) {
+            *existing = bookmark;
+        }
+    }
+
+    pub fn remove(&mut self, site: &str, question_id: Id) {
+        self.bookmarks
+            .retain(|bookmark| !(bookmark.site == site && bookmark.question_id == question_id));
+    }
+
+    fn find_mut(&mut self, site: &str, question_id: Id) -> Option<&mut Bookmark> {
+        self.bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.site == site && bookmark.question_id == question_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bookmark, BookmarkStore};
+    use crate::test_utils::question;
+
+    #[test]
+    fn keeps_how_re_added_bookmarks_were_organized() {
+        let mut store = BookmarkStore::default();
+        store.add(Bookmark {
+            added: 1,
+            folder: Some("Rust".to_owned()),
+            local_tags: vec!["later".to_owned()],
+            note: "Read the *second* answer".to_owned(),
+            ..Bookmark::new("stackoverflow", &question("Old title".to_owned(), 1))
+        });
+        store.add(Bookmark::new(
+            "superuser",
+            &question("Other site".to_owned(), 1),
+        ));
+
+        let mut renamed = question("New title".to_owned(), 1);
+        renamed.score = 10;
+        store.add(Bookmark::new("stackoverflow", &renamed));
+
+        assert_eq!(store.bookmarks().len(), 2);
+        assert_eq!(
+            store.bookmarks()[0],
+            Bookmark {
+                added: 1,
+                folder: Some("Rust".to_owned()),
+                local_tags: vec!["later".to_owned()],
+                note: "Read the *second* answer".to_owned(),
+                ..Bookmark::new("stackoverflow", &renamed)
+            }
+        );
+        assert_eq!(store.bookmarks()[1].title, "Other site");
+        assert_eq!(store.folders(), ["Rust"]);
+    }
+}
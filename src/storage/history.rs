@@ -0,0 +1,123 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::stackexchange::{Id, Question};
+
+const HISTORY_FILE: &str = "history.json";
+
+// Older entries are forgotten after this, to keep the file small.
+const MAX_ENTRIES: usize = 2000;
+
+/// A visit of a question.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub site: String,
+    pub question_id: Id,
+    pub title: String,
+    /// Unix time of when it was opened.
+    pub visited: i64,
+    /// How far the page was scrolled, from 0 to 1.
+    #[serde(default)]
+    pub scroll: f64,
+}
+
+/// Browsing history saved in the user data directory, oldest first.
+#[derive(Debug, Default)]
+pub struct HistoryStore {
+    entries: Vec<HistoryEntry>,
+}
+
+impl HistoryStore {
+    pub fn load() -> io::Result<Self> {
+        Ok(Self {
+            entries: super::read_json(&super::data_file(HISTORY_FILE))?.unwrap_or_default(),
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        super::write_json(&super::data_file(HISTORY_FILE), &self.entries)
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn record(&mut self, site: &str, question: &Question) {
+        self.entries.push(HistoryEntry {
+            site: site.to_owned(),
+            question_id: question.question_id,
+            title: question.title.clone(),
+            visited: super::now(),
+            scroll: 0.0,
+        });
+
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+    }
+
+    /// Update the latest visit of a question, keeping the furthest position.
+    pub fn set_scroll(&mut self, site: &str, question_id: Id, scroll: f64) {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.site == site && entry.question_id == question_id)
+        {
+            entry.scroll = entry.scroll.max(scroll.clamp(0.0, 1.0));
+        }
+    }
+
+    pub fn remove(&mut self, site: &str, question_id: Id, visited: i64) {
+        self.entries.retain(|entry| {
+            !(entry.site == site && entry.question_id == question_id && entry.visited == visited)
+        });
+    }
+
+    /// Remove entries visited from `from` until before `to`.
+    pub fn clear_range(&mut self, from: i64, to: i64) {
+        self.entries
+            .retain(|entry| !(from..to).contains(&entry.visited));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HistoryStore, MAX_ENTRIES};
+    use crate::api::stackexchange::Id;
+    use crate::test_utils::question;
+
+    #[test]
+    fn forgets_the_oldest_entries() {
+        let mut history = HistoryStore::default();
+        for id in 0..MAX_ENTRIES as Id + 2 {
+            history.record("stackoverflow", &question(String::new(), id));
+        }
+
+        assert_eq!(history.entries().len(), MAX_ENTRIES);
+        assert_eq!(history.entries()[0].question_id, 2);
+        assert_eq!(
+            history.entries().last().unwrap().question_id,
+            MAX_ENTRIES as Id + 1
+        );
+    }
+
+    #[test]
+    fn clears_visits_from_the_start_until_before_the_end() {
+        let mut history = HistoryStore::default();
+        for visited in 1..=4 {
+            history.record("stackoverflow", &question(String::new(), 1));
+            history.entries.last_mut().unwrap().visited = visited * 10;
+        }
+
+        history.clear_range(20, 40);
+
+        let visits: Vec<i64> = history
+            .entries()
+            .iter()
+            .map(|entry| entry.visited)
+            .collect();
+        assert_eq!(visits, [10, 40]);
+    }
+}
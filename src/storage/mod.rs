@@ -0,0 +1,75 @@
+use std::fs::{OpenOptions, Permissions};
+use std::io::{self, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json as json;
+
+pub mod bookmarks;
+pub mod history;
+pub mod session;
+pub mod settings;
+
+/// Current unix time in seconds.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+fn data_file(name: &str) -> PathBuf {
+    crate::utils::data_dir().join(name)
+}
+
+/// Read a JSON file, `None` when it doesn't exist yet.
+///
+/// Unreadable files are moved aside, so they are not overwritten by the next save.
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path) -> io::Result<Option<T>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    json::from_str(&content).map(Some).map_err(|error| {
+        let _ = std::fs::rename(path, path.with_extension("json.broken"));
+        io::Error::from(error)
+    })
+}
+
+/// Write a JSON file through a temporary file, so it's never left half written.
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    write_json_file(path, value, None)
+}
+
+// Like `write_json`, for files that only the user can read, e.g. with passwords.
+fn write_private_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    write_json_file(path, value, Some(0o600))
+}
+
+fn write_json_file<T: Serialize>(path: &Path, value: &T, mode: Option<u32>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let temporary_path = path.with_extension("json.tmp");
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    if let Some(mode) = mode {
+        options.mode(mode);
+    }
+
+    let mut file = options.open(&temporary_path)?;
+    // Files left by an interrupted save keep their own mode, the content is only written after
+    // it's restricted.
+    if let Some(mode) = mode {
+        file.set_permissions(Permissions::from_mode(mode))?;
+    }
+    file.write_all(&json::to_vec_pretty(value)?)?;
+    drop(file);
+
+    std::fs::rename(temporary_path, path)
+}
@@ -0,0 +1,37 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::stackexchange::Uri;
+
+const SESSION_FILE: &str = "session.json";
+
+/// A tab to be reopened on the next launch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    /// Application uri of the tab's page.
+    pub uri: Uri,
+    pub title: String,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Vertical scroll offset of the page.
+    #[serde(default)]
+    pub scroll: f64,
+}
+
+/// Open tabs of the last run, in the same order.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    pub tabs: Vec<SessionTab>,
+    pub selected: Option<usize>,
+}
+
+impl Session {
+    pub fn load() -> io::Result<Self> {
+        Ok(super::read_json(&super::data_file(SESSION_FILE))?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        super::write_json(&super::data_file(SESSION_FILE), self)
+    }
+}
@@ -0,0 +1,31 @@
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::config::ApiConfig;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// User preferences, missing fields keep their defaults so older files still load.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub api: ApiConfig,
+}
+
+impl Settings {
+    pub fn load() -> io::Result<Self> {
+        Ok(super::read_json(&Self::settings_file())?.unwrap_or_default())
+    }
+
+    /// Save the settings, readable by the user only since they might have a proxy password.
+    pub fn save(&self) -> io::Result<()> {
+        super::write_private_json(&Self::settings_file(), self)
+    }
+
+    // Kept with the configuration rather than the data, so it could be managed with dotfiles.
+    fn settings_file() -> PathBuf {
+        crate::utils::config_dir().join(SETTINGS_FILE)
+    }
+}
@@ -0,0 +1,141 @@
+//! Helpers shared by the unit tests of every module.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::api::backend::QaBackend;
+use crate::api::error::ApiError;
+use crate::api::stackexchange::{
+    Comment, Id, Page, Question, QuestionSummary, SearchQuery, Site, Tag, UserProfile,
+};
+
+/// Empty directory for a test, removed when it's dropped.
+pub struct TestDir(pub PathBuf);
+
+impl TestDir {
+    /// `name` must be unique among the tests, since they run in parallel.
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("stackbloatless-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// Question without answers or comments.
+pub fn question(title: String, question_id: Id) -> Question {
+    serde_json::from_value(json!({
+        "answer_count": 0,
+        "body_markdown": "",
+        "comment_count": 0,
+        "creation_date": 0,
+        "is_answered": false,
+        "last_activity_date": 0,
+        "owner": { "display_name": "" },
+        "question_id": question_id,
+        "score": 0,
+        "title": title,
+        "view_count": 0,
+    }))
+    .unwrap()
+}
+
+/// Backend with a question for any id, or failing like when the API can't be reached.
+#[derive(Default)]
+pub struct FakeBackend {
+    pub offline: AtomicBool,
+    /// Ids of every questions lookup, the questions are titled with the index of theirs.
+    pub requests: Mutex<Vec<Vec<Id>>>,
+}
+
+impl FakeBackend {
+    fn respond<T>(&self, value: T) -> Result<T, ApiError> {
+        if self.offline.load(Ordering::Relaxed) {
+            Err(ApiError::Transport("offline".to_owned()))
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+#[async_trait]
+impl QaBackend for FakeBackend {
+    async fn get_questions(&self, _site: &str, ids: &[Id]) -> Result<Vec<Question>, ApiError> {
+        let mut requests = self.requests.lock().unwrap();
+        requests.push(ids.to_vec());
+        let request = requests.len() - 1;
+
+        self.respond(
+            ids.iter()
+                .map(|id| question(format!("request {request}"), *id))
+                .collect(),
+        )
+    }
+
+    async fn complete_question(
+        &self,
+        _site: &str,
+        question: Question,
+    ) -> Result<Question, ApiError> {
+        self.respond(question)
+    }
+
+    async fn search_questions(
+        &self,
+        _site: &str,
+        _query: &SearchQuery,
+        _page: u32,
+    ) -> Result<Page<QuestionSummary>, ApiError> {
+        self.respond(Page {
+            items: Vec::new(),
+            has_more: false,
+            backoff: None,
+            quota_max: None,
+            quota_remaining: None,
+        })
+    }
+
+    async fn get_post_comments(&self, _site: &str, _post_id: Id) -> Result<Vec<Comment>, ApiError> {
+        self.respond(Vec::new())
+    }
+
+    async fn get_user(&self, _site: &str, _user_id: Id) -> Result<Option<UserProfile>, ApiError> {
+        self.respond(None)
+    }
+
+    async fn get_answer_question_id(
+        &self,
+        _site: &str,
+        _answer_id: Id,
+    ) -> Result<Option<Id>, ApiError> {
+        self.respond(None)
+    }
+
+    async fn get_tag(&self, _site: &str, _name: &str) -> Result<Option<Tag>, ApiError> {
+        self.respond(None)
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>, ApiError> {
+        self.respond(Vec::new())
+    }
+}
@@ -15,3 +15,33 @@ fn get_system_time_locale() -> String {
     // Don't include charset postfix.
     system_time_locale.split('.').next().unwrap().to_owned()
 }
+
+/// Application's directory for non-essential cached data.
+pub fn cache_dir() -> std::path::PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// Application's directory for user settings.
+pub fn config_dir() -> std::path::PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// Application's directory for user data that should be kept.
+pub fn data_dir() -> std::path::PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+// Ref: https://specifications.freedesktop.org/basedir-spec/latest/
+fn xdg_dir(env_var: &str, home_fallback: &str) -> std::path::PathBuf {
+    use std::env;
+    use std::path::PathBuf;
+
+    env::var_os(env_var)
+        .map(PathBuf::from)
+        // Relative paths are invalid according to the specification.
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| {
+            PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(home_fallback)
+        })
+        .join(env!("CARGO_PKG_NAME"))
+}